@@ -0,0 +1,268 @@
+// Save as: programs/nexus-common/src/lib.rs
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+// Shared account layouts and verification helpers for the Nexus programs.
+// Governance and utility depend on nexus_economics directly, for its account
+// types and CPIs. Economics reads governance's proposals and voter profiles
+// through this crate instead, since depending on nexus_governance would
+// close a cycle.
+
+/// Anchor account discriminator: the first 8 bytes of `sha256("account:<name>")`.
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("account:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Checks that `info` is a `name` account owned by `program_id`.
+pub fn verify_account_type(info: &AccountInfo, program_id: &Pubkey, name: &str) -> Result<()> {
+    require_keys_eq!(*info.owner, *program_id, CommonError::InvalidAccountOwner);
+
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == account_discriminator(name),
+        CommonError::InvalidAccountType
+    );
+
+    Ok(())
+}
+
+/// Verifies and decodes the leading fields of a `name` account owned by `program_id`.
+/// Trailing fields are ignored, so `T` only has to mirror a prefix of the layout.
+pub fn load_header<T: AnchorDeserialize>(
+    info: &AccountInfo,
+    program_id: &Pubkey,
+    name: &str,
+) -> Result<T> {
+    verify_account_type(info, program_id, name)?;
+
+    let data = info.try_borrow_data()?;
+    T::deserialize(&mut &data[8..]).map_err(|_| error!(CommonError::InvalidAccountData))
+}
+
+/// Leading fields of `nexus_economics::LockAccount`, through the economics
+/// deployment and mint the lock belongs to. Locks not yet migrated to that
+/// layout fail to decode.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LockHeader {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub locked: bool,
+    pub rewards_claimed: u64,
+    pub version: u8,
+    pub last_claim_ts: i64,
+    pub auto_stake_rewards: bool,
+    pub vesting_total: u64,
+    pub vesting_released: u64,
+    pub vesting_start: i64,
+    pub economics: Pubkey,
+    pub token_mint: Pubkey,
+}
+
+/// Mirrors `nexus_governance::ProposalType` so proposal headers decode.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum ProposalKind {
+    Core,
+    Technical,
+    Operational,
+    Custom(u8),
+}
+
+/// Leading fields of `nexus_governance::Proposal`, through the realm it
+/// belongs to and the action it commits to.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposalHeader {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub proposal_type: ProposalKind,
+    pub title: String,
+    pub description: String,
+    pub link: String,
+    pub created_at: i64,
+    pub voting_starts_at: i64,
+    pub voting_ends_at: i64,
+    pub executed: bool,
+    pub cancelled: bool,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub veto_votes: u64,
+    pub abstain_votes: u64,
+    pub quorum: u8,
+    pub deposit: u64,
+    pub deposit_settled: bool,
+    pub total_extension: i64,
+    pub description_hash: [u8; 32],
+    pub type_index: u64,
+    pub quorum_floor: u64,
+    pub governance: Pubkey,
+    pub eta: i64,
+    pub voter_count: u64,
+    pub threshold_locked: u64,
+    pub action_hash: [u8; 32],
+}
+
+/// Leading fields of `nexus_governance::VoterProfile`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VoterProfileHeader {
+    pub voter: Pubkey,
+    pub proposals_voted: u64,
+    pub rewarded_votes: u64,
+    pub last_voted_at: i64,
+}
+
+/// Loads an economics lock account.
+pub fn load_lock(info: &AccountInfo, economics_program: &Pubkey) -> Result<LockHeader> {
+    load_header(info, economics_program, "LockAccount")
+}
+
+/// Loads an economics lock account and checks it belongs to `owner`.
+pub fn verify_lock_owner(
+    info: &AccountInfo,
+    economics_program: &Pubkey,
+    owner: &Pubkey,
+) -> Result<LockHeader> {
+    let lock = load_lock(info, economics_program)?;
+    require_keys_eq!(lock.owner, *owner, CommonError::LockOwnerMismatch);
+    Ok(lock)
+}
+
+/// Loads a governance voter profile.
+pub fn load_voter_profile(info: &AccountInfo, governance_program: &Pubkey) -> Result<VoterProfileHeader> {
+    load_header(info, governance_program, "VoterProfile")
+}
+
+/// Loads a governance proposal and checks it was executed and not cancelled.
+pub fn verify_executed_proposal(
+    info: &AccountInfo,
+    governance_program: &Pubkey,
+) -> Result<ProposalHeader> {
+    let proposal: ProposalHeader = load_header(info, governance_program, "Proposal")?;
+    require!(
+        proposal.executed && !proposal.cancelled,
+        CommonError::ProposalNotExecuted
+    );
+    Ok(proposal)
+}
+
+/// Length of an Arweave (and Irys) transaction id.
+pub const ARWEAVE_TX_ID_LEN: usize = 43;
+
+/// Arweave (and Irys) transaction ids are 43 characters of base64url.
+pub fn is_arweave_tx_id(id: &str) -> bool {
+    id.len() == ARWEAVE_TX_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Basis point denominator shared by all percentage math: 10_000 = 100%.
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Which way a fixed-point result that is not a whole unit is rounded.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// `value * numerator / denominator` with a u128 intermediate, so the product
+/// cannot overflow and precision is only lost in the final rounding step.
+pub fn mul_div(value: u64, numerator: u64, denominator: u64, rounding: Rounding) -> Result<u64> {
+    require!(denominator > 0, CommonError::DivisionByZero);
+
+    let product = value as u128 * numerator as u128;
+    let denominator = denominator as u128;
+    let quotient = match rounding {
+        Rounding::Down => product / denominator,
+        Rounding::Up => (product + denominator - 1) / denominator,
+    };
+    u64::try_from(quotient).map_err(|_| error!(CommonError::MathOverflow))
+}
+
+/// Scales `value` by `bps` basis points.
+pub fn apply_bps(value: u64, bps: u64, rounding: Rounding) -> Result<u64> {
+    mul_div(value, bps, BPS_DENOMINATOR, rounding)
+}
+
+/// Floor of the square root, computed by Newton's method. u128 keeps
+/// `x + 1` and `x + n / x` from overflowing near `u64::MAX`.
+pub fn integer_sqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+
+    let n = value as u128;
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x as u64
+}
+
+/// Splits `amount` into shares given in basis points, which must sum to
+/// `BPS_DENOMINATOR`. Every share rounds down except the last, which takes the
+/// remainder, so the parts always sum back to `amount` exactly.
+pub fn split_bps<const N: usize>(amount: u64, shares_bps: [u64; N]) -> Result<[u64; N]> {
+    require!(
+        N > 0 && shares_bps.iter().sum::<u64>() == BPS_DENOMINATOR,
+        CommonError::InvalidShares
+    );
+
+    let mut parts = [0u64; N];
+    let mut allocated = 0u64;
+    for index in 0..N - 1 {
+        parts[index] = apply_bps(amount, shares_bps[index], Rounding::Down)?;
+        allocated += parts[index];
+    }
+    parts[N - 1] = amount - allocated;
+    Ok(parts)
+}
+
+/// A raw token amount paired with its mint's decimals, so clients never have
+/// to guess the scaling. The human value is `raw / 10^decimals`: with the
+/// 9 decimals NEXUS uses, `raw = 1_500_000_000` is 1.5 NEXUS.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub struct TokenAmount {
+    pub raw: u64,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn new(raw: u64, decimals: u8) -> Self {
+        Self { raw, decimals }
+    }
+
+    /// Whole tokens, rounded down. `None` if `10^decimals` overflows a u64.
+    pub fn whole(&self) -> Option<u64> {
+        Some(self.raw / 10u64.checked_pow(self.decimals as u32)?)
+    }
+
+    /// Base units left over after `whole`. `None` if `10^decimals` overflows a u64.
+    pub fn fraction(&self) -> Option<u64> {
+        Some(self.raw % 10u64.checked_pow(self.decimals as u32)?)
+    }
+}
+
+#[error_code(offset = 9000)]
+pub enum CommonError {
+    #[msg("Account is not owned by the expected program")]
+    InvalidAccountOwner,
+    #[msg("Account discriminator does not match the expected type")]
+    InvalidAccountType,
+    #[msg("Account data could not be decoded")]
+    InvalidAccountData,
+    #[msg("Lock is not owned by the expected wallet")]
+    LockOwnerMismatch,
+    #[msg("Proposal has not been executed")]
+    ProposalNotExecuted,
+    #[msg("Division by zero")]
+    DivisionByZero,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Shares must sum to 10000 basis points")]
+    InvalidShares,
+}
@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, Token, TokenAccount};
+use nexus_common::{apply_bps, mul_div, split_bps, Rounding, BPS_DENOMINATOR};
 
 declare_id!("NEXUSECONxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
@@ -23,9 +26,428 @@ pub mod nexus_economics {
         config: EconomicsConfig,
     ) -> Result<()> {
         let economics = &mut ctx.accounts.economics;
+        validate_config(&config)?;
+
+        economics.authority = ctx.accounts.authority.key();
+        economics.token_mint = ctx.accounts.token_mint.key();
         economics.config = config;
+        economics.fee_shares = FeeShares {
+            venexus: VENEXUS_SHARE,
+            ainexus: AINEXUS_SHARE,
+            treasury: TREASURY_SHARE,
+            burn: BURN_SHARE,
+        };
+        economics.governance_program = Pubkey::default();
+        economics.governance = Pubkey::default();
+        economics.paused = false;
+        economics.pause_windows = Vec::new();
+        economics.participation_boost = ParticipationBoost {
+            governance: Pubkey::default(),
+            min_votes: 0,
+            recency: 0,
+            boost_bps: 0,
+        };
+        economics.period_start = Clock::get()?.unix_timestamp;
+        economics.period_emitted = 0;
         economics.total_fees_collected = 0;
         economics.total_burned = 0;
+        economics.reward_schedule = Vec::new();
+        economics.fee_tokens = Vec::new();
+        economics.payment_accounts = Vec::new();
+        economics.treasury_signers = Vec::new();
+        economics.treasury_threshold = 0;
+        economics.withdrawal_count = 0;
+        economics.bump = ctx.bumps.economics;
+        Ok(())
+    }
+
+    // Until this is set no proposal can authorize update_economics_params.
+    // Only proposals of the `governance` realm under that program are trusted.
+    pub fn set_governance_program(
+        ctx: Context<UpdateGovernanceProgram>,
+        governance_program: Pubkey,
+        governance: Pubkey,
+    ) -> Result<()> {
+        let economics = &mut ctx.accounts.economics;
+        economics.governance_program = governance_program;
+        economics.governance = governance;
+        Ok(())
+    }
+
+    // Applies a bundle of parameter changes enacted by an executed core
+    // proposal whose action hash commits to exactly this bundle. The bundle is
+    // validated as a whole, so either every change lands or none do, and each
+    // proposal can be applied once.
+    pub fn update_economics_params(
+        ctx: Context<UpdateEconomicsParams>,
+        params: EconomicsParams,
+    ) -> Result<()> {
+        let economics = &mut ctx.accounts.economics;
+        let proposal = verify_core_proposal(economics, &ctx.accounts.proposal, &ctx.accounts.proposer.key())?;
+        require!(
+            proposal.action_hash == economics_params_hash(&economics.key(), &params)?,
+            EconomicsError::ActionHashMismatch
+        );
+
+        let mut config = economics.config.clone();
+        let mut fee_shares = economics.fee_shares.clone();
+        if let Some(shares) = params.fee_shares {
+            fee_shares = shares;
+        }
+        if let Some(max_lock_duration) = params.max_lock_duration {
+            config.max_lock_duration = max_lock_duration;
+        }
+        if let Some(reward_rate) = params.reward_rate {
+            config.reward_rate = reward_rate;
+        }
+        if let Some(boost_factor) = params.boost_factor {
+            config.boost_factor = boost_factor;
+        }
+        if let Some(min_stake) = params.min_stake {
+            config.min_stake = min_stake;
+        }
+        if let Some(max_rewards_per_period) = params.max_rewards_per_period {
+            config.max_rewards_per_period = max_rewards_per_period;
+        }
+        if let Some(reward_period) = params.reward_period {
+            config.reward_period = reward_period;
+        }
+        if let Some(relock_cooldown) = params.relock_cooldown {
+            config.relock_cooldown = relock_cooldown;
+        }
+        if let Some(min_fee_amount) = params.min_fee_amount {
+            config.min_fee_amount = min_fee_amount;
+        }
+        if let Some(max_lock_amount) = params.max_lock_amount {
+            config.max_lock_amount = max_lock_amount;
+        }
+        if let Some(min_claim_delay) = params.min_claim_delay {
+            config.min_claim_delay = min_claim_delay;
+        }
+        if let Some(reward_vesting_period) = params.reward_vesting_period {
+            config.reward_vesting_period = reward_vesting_period;
+        }
+
+        validate_config(&config)?;
+        require!(fee_shares.total() == 100, EconomicsError::InvalidFeeShares);
+
+        economics.config = config;
+        economics.fee_shares = fee_shares;
+
+        let applied = &mut ctx.accounts.applied_proposal;
+        applied.economics = economics.key();
+        applied.proposal = ctx.accounts.proposal.key();
+        Ok(())
+    }
+
+    // Moves already-collected funds between two treasury accounts held by the
+    // treasury PDA, as enacted by an executed core proposal committing to the
+    // exact accounts and amount. Shares only steer future fees; this
+    // rebalances what has accumulated so far.
+    pub fn rebalance_treasuries(ctx: Context<RebalanceTreasuries>, amount: u64) -> Result<()> {
+        let economics = &ctx.accounts.economics;
+        let proposal = verify_core_proposal(economics, &ctx.accounts.proposal, &ctx.accounts.proposer.key())?;
+        let action_hash = rebalance_hash(
+            &economics.key(),
+            &ctx.accounts.source.key(),
+            &ctx.accounts.destination.key(),
+            amount,
+        );
+        require!(proposal.action_hash == action_hash, EconomicsError::ActionHashMismatch);
+        require!(
+            amount > 0 && amount <= ctx.accounts.source.amount,
+            EconomicsError::InvalidRebalanceAmount
+        );
+
+        let economics_key = economics.key();
+        let seeds: &[&[u8]] = &[
+            b"treasury",
+            economics_key.as_ref(),
+            &[ctx.bumps.treasury_authority],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.treasury_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+
+        let applied = &mut ctx.accounts.applied_proposal;
+        applied.economics = economics_key;
+        applied.proposal = ctx.accounts.proposal.key();
+        Ok(())
+    }
+
+    pub fn add_fee_token(ctx: Context<UpdateFeeTokens>, fee_token: FeeToken) -> Result<()> {
+        let economics = &mut ctx.accounts.economics;
+
+        require!(
+            economics.fee_tokens.len() < MAX_FEE_TOKENS,
+            EconomicsError::TooManyFeeTokens
+        );
+        require!(
+            economics.fee_token(&fee_token.mint).is_none(),
+            EconomicsError::FeeTokenAlreadyApproved
+        );
+        require!(fee_token.nexus_rate_bps > 0, EconomicsError::InvalidConversionRate);
+
+        economics.fee_tokens.push(fee_token);
+        Ok(())
+    }
+
+    pub fn remove_fee_token(ctx: Context<UpdateFeeTokens>, mint: Pubkey) -> Result<()> {
+        let economics = &mut ctx.accounts.economics;
+
+        let index = economics.fee_tokens
+            .iter()
+            .position(|fee_token| fee_token.mint == mint)
+            .ok_or(EconomicsError::FeeTokenNotApproved)?;
+        economics.fee_tokens.remove(index);
+        Ok(())
+    }
+
+    // Service fee accounts that pay_from_lock may pay into
+    pub fn add_payment_account(ctx: Context<UpdatePaymentAccounts>, account: Pubkey) -> Result<()> {
+        let economics = &mut ctx.accounts.economics;
+        require!(
+            !economics.is_payment_account(&account),
+            EconomicsError::PaymentAccountAlreadyApproved
+        );
+        require!(
+            economics.payment_accounts.len() < MAX_PAYMENT_ACCOUNTS,
+            EconomicsError::TooManyPaymentAccounts
+        );
+
+        economics.payment_accounts.push(account);
+        Ok(())
+    }
+
+    pub fn remove_payment_account(ctx: Context<UpdatePaymentAccounts>, account: Pubkey) -> Result<()> {
+        let economics = &mut ctx.accounts.economics;
+        require!(
+            economics.is_payment_account(&account),
+            EconomicsError::PaymentAccountNotApproved
+        );
+
+        economics.payment_accounts.retain(|approved| *approved != account);
+        Ok(())
+    }
+
+    pub fn set_reward_schedule(
+        ctx: Context<SetRewardSchedule>,
+        schedule: Vec<RateCheckpoint>,
+    ) -> Result<()> {
+        require!(
+            schedule.len() <= MAX_RATE_CHECKPOINTS,
+            EconomicsError::InvalidRewardSchedule
+        );
+        // Checkpoints must be strictly increasing so accrual can walk them in order
+        require!(
+            schedule.windows(2).all(|pair| pair[0].start_ts < pair[1].start_ts),
+            EconomicsError::InvalidRewardSchedule
+        );
+
+        ctx.accounts.economics.reward_schedule = schedule;
+        Ok(())
+    }
+
+    // Halts reward accrual from now until unpause_rewards. Lock principal
+    // is untouched; only accrual and claims stop.
+    pub fn pause_rewards(ctx: Context<SetRewardsPaused>) -> Result<()> {
+        let economics = &mut ctx.accounts.economics;
+        require!(!economics.paused, EconomicsError::RewardsPaused);
+
+        // A window that ended a full lock duration before the sweep grace
+        // period can only overlap locks already past it, so it is dropped to
+        // make room rather than capping the number of pauses for good
+        let now = Clock::get()?.unix_timestamp;
+        let horizon = now - MAX_LOCK_DURATION - SWEEP_GRACE_PERIOD;
+        economics.pause_windows.retain(|window| window.end_ts > horizon);
+        require!(
+            economics.pause_windows.len() < MAX_PAUSE_WINDOWS,
+            EconomicsError::TooManyPauseWindows
+        );
+
+        economics.pause_windows.push(PauseWindow {
+            start_ts: now,
+            end_ts: i64::MAX,
+        });
+        economics.paused = true;
+        Ok(())
+    }
+
+    // Closes the open pause window; accrual resumes from now without
+    // crediting the paused time
+    pub fn unpause_rewards(ctx: Context<SetRewardsPaused>) -> Result<()> {
+        let economics = &mut ctx.accounts.economics;
+        require!(economics.paused, EconomicsError::RewardsNotPaused);
+
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(window) = economics.pause_windows.last_mut() {
+            window.end_ts = now;
+        }
+        economics.paused = false;
+        Ok(())
+    }
+
+    // Locks whose owner has voted on at least `min_votes` proposals in the
+    // configured governance, most recently within `recency` seconds, are paid
+    // `boost_bps` on top of their rewards
+    pub fn set_participation_boost(
+        ctx: Context<SetParticipationBoost>,
+        boost: ParticipationBoost,
+    ) -> Result<()> {
+        require!(
+            boost.boost_bps <= MAX_PARTICIPATION_BOOST_BPS && boost.recency >= 0,
+            EconomicsError::InvalidParticipationBoost
+        );
+
+        ctx.accounts.economics.participation_boost = boost;
+        Ok(())
+    }
+
+    pub fn set_treasury_multisig(
+        ctx: Context<UpdateTreasuryMultisig>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            signers.len() <= MAX_TREASURY_SIGNERS,
+            EconomicsError::InvalidMultisig
+        );
+        require!(
+            threshold > 0 && threshold as usize <= signers.len(),
+            EconomicsError::InvalidMultisig
+        );
+        for (index, signer) in signers.iter().enumerate() {
+            require!(!signers[..index].contains(signer), EconomicsError::InvalidMultisig);
+        }
+
+        let economics = &mut ctx.accounts.economics;
+        economics.treasury_signers = signers;
+        economics.treasury_threshold = threshold;
+        Ok(())
+    }
+
+    pub fn request_withdrawal(ctx: Context<RequestWithdrawal>, amount: u64) -> Result<()> {
+        let economics = &mut ctx.accounts.economics;
+        let signer = ctx.accounts.signer.key();
+
+        require!(
+            economics.treasury_signers.contains(&signer),
+            EconomicsError::NotTreasurySigner
+        );
+        require!(amount > 0, EconomicsError::InvalidFeeAmount);
+
+        // The requester's signature counts as the first approval
+        let request = &mut ctx.accounts.withdrawal_request;
+        request.economics = economics.key();
+        request.request_id = economics.withdrawal_count;
+        request.treasury = ctx.accounts.treasury.key();
+        request.destination = ctx.accounts.destination.key();
+        request.amount = amount;
+        request.approvals = vec![signer];
+        request.executed = false;
+
+        economics.withdrawal_count += 1;
+        Ok(())
+    }
+
+    // Returns tokens sent to a treasury account by mistake. Only mints the
+    // protocol never collects can be swept, so NEXUS and approved fee tokens
+    // stay behind the multisig.
+    pub fn rescue_tokens(ctx: Context<RescueTokens>) -> Result<()> {
+        let economics = &ctx.accounts.economics;
+        let mint = ctx.accounts.source.mint;
+        require!(
+            mint != economics.token_mint && economics.fee_token(&mint).is_none(),
+            EconomicsError::ProtectedMint
+        );
+        let amount = ctx.accounts.source.amount;
+        require!(amount > 0, EconomicsError::NothingToRescue);
+
+        let economics_key = economics.key();
+        let seeds: &[&[u8]] = &[
+            b"treasury",
+            economics_key.as_ref(),
+            &[ctx.bumps.treasury_authority],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.treasury_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn approve_withdrawal(ctx: Context<ApproveWithdrawal>) -> Result<()> {
+        let economics = &ctx.accounts.economics;
+        let request = &mut ctx.accounts.withdrawal_request;
+        let signer = ctx.accounts.signer.key();
+
+        require!(
+            economics.treasury_signers.contains(&signer),
+            EconomicsError::NotTreasurySigner
+        );
+        require!(!request.executed, EconomicsError::WithdrawalAlreadyExecuted);
+        require!(!request.approvals.contains(&signer), EconomicsError::AlreadyApproved);
+
+        // Approvals from signers removed since they approved no longer count
+        request.approvals.retain(|approver| economics.treasury_signers.contains(approver));
+        request.approvals.push(signer);
+        Ok(())
+    }
+
+    pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+        let economics = &ctx.accounts.economics;
+        let request = &mut ctx.accounts.withdrawal_request;
+
+        require!(!request.executed, EconomicsError::WithdrawalAlreadyExecuted);
+
+        let approvals = request.approvals
+            .iter()
+            .filter(|approver| economics.treasury_signers.contains(approver))
+            .count();
+        require!(
+            economics.treasury_threshold > 0 && approvals >= economics.treasury_threshold as usize,
+            EconomicsError::InsufficientApprovals
+        );
+
+        request.executed = true;
+
+        let economics_key = economics.key();
+        let seeds: &[&[u8]] = &[
+            b"treasury",
+            economics_key.as_ref(),
+            &[ctx.bumps.treasury_authority],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.treasury.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.treasury_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            request.amount,
+        )?;
+
         Ok(())
     }
 
@@ -33,14 +455,59 @@ pub mod nexus_economics {
         ctx: Context<ProcessFee>,
         amount: u64,
         fee_type: FeeType,
+        min_amount: Option<u64>,
+        max_amount: Option<u64>,
     ) -> Result<()> {
         let economics = &mut ctx.accounts.economics;
-        
-        // Calculate fee distributions
-        let venexus_amount = (amount * VENEXUS_SHARE as u64) / 100;
-        let ainexus_amount = (amount * AINEXUS_SHARE as u64) / 100;
-        let treasury_amount = (amount * TREASURY_SHARE as u64) / 100;
-        let burn_amount = (amount * BURN_SHARE as u64) / 100;
+
+        // Reject stale amounts outside the caller's expected range
+        if let Some(min_amount) = min_amount {
+            require!(amount >= min_amount, EconomicsError::InvalidFeeAmount);
+        }
+        if let Some(max_amount) = max_amount {
+            require!(amount <= max_amount, EconomicsError::InvalidFeeAmount);
+        }
+
+        // Spamming dust fees would only churn the accumulator
+        require!(
+            amount >= economics.config.min_fee_amount,
+            EconomicsError::FeeBelowMinimum
+        );
+
+        // Once an allowlist is configured, fees are only accepted in approved
+        // tokens and must be routed to that token's treasuries. An empty list
+        // keeps the original single-token behavior.
+        let mint = ctx.accounts.token_mint.key();
+        require_keys_eq!(ctx.accounts.fee_account.mint, mint, EconomicsError::FeeTokenMismatch);
+        require!(ctx.accounts.fee_account.amount >= amount, EconomicsError::InsufficientFunds);
+        let (nexus_rate_bps, burnable) = if economics.fee_tokens.is_empty() {
+            (RATE_BPS_DENOMINATOR, true)
+        } else {
+            let fee_token = economics.fee_token(&mint).ok_or(EconomicsError::FeeTokenNotApproved)?;
+            require_keys_eq!(ctx.accounts.venexus_treasury.key(), fee_token.venexus_treasury, EconomicsError::FeeTokenMismatch);
+            require_keys_eq!(ctx.accounts.ainexus_treasury.key(), fee_token.ainexus_treasury, EconomicsError::FeeTokenMismatch);
+            require_keys_eq!(ctx.accounts.protocol_treasury.key(), fee_token.protocol_treasury, EconomicsError::FeeTokenMismatch);
+            (fee_token.nexus_rate_bps, fee_token.burnable)
+        };
+
+        // Calculate fee distributions. The treasury takes the rounding remainder
+        // so the shares always add up to the amount recorded below.
+        let shares = &economics.fee_shares;
+        let [venexus_amount, ainexus_amount, mut burn_amount, mut treasury_amount] = split_bps(
+            amount,
+            [
+                shares.venexus as u64 * 100,
+                shares.ainexus as u64 * 100,
+                shares.burn as u64 * 100,
+                shares.treasury as u64 * 100,
+            ],
+        )?;
+
+        // Tokens the protocol does not burn send the burn share to the treasury
+        if !burnable {
+            treasury_amount += burn_amount;
+            burn_amount = 0;
+        }
 
         // Transfer to veNEXUS holders
         token::transfer(
@@ -82,24 +549,43 @@ pub mod nexus_economics {
         )?;
 
         // Burn tokens
-        token::burn(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Burn {
-                    mint: ctx.accounts.token_mint.to_account_info(),
-                    from: ctx.accounts.fee_account.to_account_info(),
-                    authority: ctx.accounts.fee_authority.to_account_info(),
-                },
-            ),
-            burn_amount,
-        )?;
+        if burn_amount > 0 {
+            token::burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Burn {
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        from: ctx.accounts.fee_account.to_account_info(),
+                        authority: ctx.accounts.fee_authority.to_account_info(),
+                    },
+                ),
+                burn_amount,
+            )?;
+        }
 
-        // Update economics state
-        economics.total_fees_collected = economics.total_fees_collected.checked_add(amount)
+        // Update economics state, tracked in NEXUS-equivalent units
+        let to_nexus = |value: u64| -> Result<u64> {
+            mul_div(value, nexus_rate_bps, RATE_BPS_DENOMINATOR, Rounding::Down)
+        };
+        economics.total_fees_collected = economics.total_fees_collected.checked_add(to_nexus(amount)?)
             .ok_or(EconomicsError::Overflow)?;
-        economics.total_burned = economics.total_burned.checked_add(burn_amount)
+        economics.total_burned = economics.total_burned.checked_add(to_nexus(burn_amount)?)
             .ok_or(EconomicsError::Overflow)?;
 
+        // Amounts as transferred and burned above, after remainder routing
+        emit!(FeeDistributed {
+            economics: economics.key(),
+            fee_account: ctx.accounts.fee_account.key(),
+            fee_authority: ctx.accounts.fee_authority.key(),
+            mint,
+            fee_type,
+            amount,
+            venexus_amount,
+            ainexus_amount,
+            treasury_amount,
+            burn_amount,
+        });
+
         Ok(())
     }
 
@@ -112,6 +598,10 @@ pub mod nexus_economics {
             duration >= MIN_LOCK_DURATION && duration <= MAX_LOCK_DURATION,
             EconomicsError::InvalidLockDuration
         );
+        require!(
+            ctx.accounts.economics.config.within_lock_cap(amount),
+            EconomicsError::InvalidLockAmount
+        );
 
         let lock = &mut ctx.accounts.lock;
         lock.owner = ctx.accounts.owner.key();
@@ -119,8 +609,18 @@ pub mod nexus_economics {
         lock.start_time = Clock::get()?.unix_timestamp;
         lock.end_time = lock.start_time + duration;
         lock.locked = true;
+        lock.rewards_claimed = 0;
+        lock.version = LOCK_VERSION;
+        lock.last_claim_ts = 0;
+        lock.auto_stake_rewards = false;
+        lock.economics = ctx.accounts.economics.key();
+        lock.token_mint = ctx.accounts.economics.token_mint;
 
         // Transfer tokens to lock account
+        require!(
+            ctx.accounts.owner_token_account.amount >= amount,
+            EconomicsError::InsufficientFunds
+        );
         token::transfer(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
@@ -136,21 +636,133 @@ pub mod nexus_economics {
         Ok(())
     }
 
-    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    pub fn claim_rewards(ctx: Context<ClaimRewards>, min_rewards: Option<u64>) -> Result<()> {
         let lock = &mut ctx.accounts.lock;
-        let economics = &ctx.accounts.economics;
+        let economics = &mut ctx.accounts.economics;
+        let now = Clock::get()?.unix_timestamp;
 
         require!(lock.locked, EconomicsError::LockNotActive);
+        require!(!economics.paused, EconomicsError::RewardsPaused);
+        // A fresh lock cannot claim straight away, so depositing just to skim
+        // accrued fees and leave does not pay
+        require!(
+            now - lock.start_time >= economics.config.min_claim_delay,
+            EconomicsError::ClaimTooEarly
+        );
 
-        // Calculate rewards
-        let rewards = calculate_rewards(
+        // Calculate rewards not yet paid out to this lock
+        let accrued = calculate_rewards(
             lock.amount,
             lock.start_time,
             lock.end_time,
             economics.total_fees_collected,
+            now,
+            &economics.reward_schedule,
+            &economics.pause_windows,
+        )?;
+        let owed = accrued.saturating_sub(lock.rewards_claimed);
+
+        // Active governance participants are paid a bounded boost on top
+        let boost_bps = participation_boost_bps(
+            economics,
+            ctx.accounts.voter_profile.as_deref(),
+            &lock.owner,
+            now,
         )?;
+        let boosted = apply_bps(owed, BPS_DENOMINATOR + boost_bps, Rounding::Down)?;
+        let mut rewards = boosted;
+
+        // Scale the payout down to what is left of this period's emission cap.
+        // The unpaid remainder stays claimable in a later period.
+        if economics.config.max_rewards_per_period > 0 {
+            if now >= economics.period_start + economics.config.reward_period {
+                let elapsed_periods = (now - economics.period_start) / economics.config.reward_period;
+                economics.period_start += elapsed_periods * economics.config.reward_period;
+                economics.period_emitted = 0;
+            }
+            let remaining = economics.config.max_rewards_per_period.saturating_sub(economics.period_emitted);
+            rewards = rewards.min(remaining);
+            economics.period_emitted += rewards;
+        }
+
+        // Guards against the schedule, fees or cap moving between quote and claim
+        if let Some(min_rewards) = min_rewards {
+            require!(rewards >= min_rewards, EconomicsError::RewardsBelowMinimum);
+        }
+
+        // `rewards_claimed` tracks accrual, so only the unboosted part of the
+        // payout settles it
+        let settled = if rewards == boosted {
+            owed
+        } else {
+            mul_div(rewards, BPS_DENOMINATOR, BPS_DENOMINATOR + boost_bps, Rounding::Down)?
+        };
+        lock.rewards_claimed += settled;
+        lock.last_claim_ts = now;
+
+        // Auto-staked rewards compound into the lock, raising its voting stake
+        let destination = if lock.auto_stake_rewards {
+            let lock_token_account = ctx.accounts.lock_token_account.as_ref()
+                .ok_or(EconomicsError::InvalidStakeTarget)?;
+            require_keys_eq!(
+                lock_token_account.mint,
+                ctx.accounts.rewards_treasury.mint,
+                EconomicsError::InvalidStakeTarget
+            );
+            top_up_lock(lock, rewards, economics, now)?;
+            lock_token_account.to_account_info()
+        } else {
+            ctx.accounts.owner_token_account.to_account_info()
+        };
+
+        // With vesting on, the claim restarts the linear release: what has
+        // unlocked so far is paid now, and the rest plus this claim unlocks
+        // over a fresh period. Auto-staked rewards are already locked.
+        let vesting_period = economics.config.reward_vesting_period;
+        let payout = if vesting_period > 0 && !lock.auto_stake_rewards {
+            let vested = lock.vested_rewards(now, vesting_period)?;
+            let releasable = vested.saturating_sub(lock.vesting_released);
+            lock.vesting_total = (lock.vesting_total - vested)
+                .checked_add(rewards)
+                .ok_or(EconomicsError::Overflow)?;
+            lock.vesting_released = 0;
+            lock.vesting_start = now;
+            releasable
+        } else {
+            rewards
+        };
 
         // Transfer rewards
+        if payout > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.rewards_treasury.to_account_info(),
+                        to: destination,
+                        authority: ctx.accounts.rewards_authority.to_account_info(),
+                    },
+                ),
+                payout,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Pays out the part of a lock's claimed rewards that has vested since
+    // the last claim or release
+    pub fn release_vested_rewards(ctx: Context<ReleaseVestedRewards>) -> Result<()> {
+        require!(!ctx.accounts.economics.paused, EconomicsError::RewardsPaused);
+
+        let lock = &mut ctx.accounts.lock;
+        let now = Clock::get()?.unix_timestamp;
+
+        let vested = lock.vested_rewards(now, ctx.accounts.economics.config.reward_vesting_period)?;
+        let releasable = vested.saturating_sub(lock.vesting_released);
+        require!(releasable > 0, EconomicsError::NothingVested);
+        lock.vesting_released = vested;
+
         token::transfer(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
@@ -160,227 +772,3073 @@ pub mod nexus_economics {
                     authority: ctx.accounts.rewards_authority.to_account_info(),
                 },
             ),
-            rewards,
+            releasable,
         )?;
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct InitializeEconomics<'info> {
-    #[account(init, payer = authority, space = 8 + size_of::<EconomicsState>())]
-    pub economics: Account<'info, EconomicsState>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    pub fn extend_lock(
+        ctx: Context<ExtendLock>,
+        additional_amount: u64,
+        additional_duration: i64,
+    ) -> Result<()> {
+        let lock = &mut ctx.accounts.lock;
+        let economics = &ctx.accounts.economics;
+        let now = Clock::get()?.unix_timestamp;
 
-#[derive(Accounts)]
-pub struct ProcessFee<'info> {
-    #[account(mut)]
-    pub economics: Account<'info, EconomicsState>,
-    #[account(mut)]
-    pub fee_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub venexus_treasury: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub ainexus_treasury: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub protocol_treasury: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub token_mint: Account<'info, token::Mint>,
-    pub fee_authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-}
+        require!(lock.locked, EconomicsError::LockNotActive);
 
-#[derive(Accounts)]
-pub struct CreateLock<'info> {
-    #[account(init, payer = owner, space = 8 + size_of::<LockAccount>())]
-    pub lock: Account<'info, LockAccount>,
-    #[account(mut)]
-    pub owner: Signer<'info>,
-    #[account(mut)]
-    pub owner_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub lock_token_account: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
+        // Relocking straight after a claim would let a lock re-checkpoint at will
+        require!(
+            now >= lock.last_claim_ts.saturating_add(economics.config.relock_cooldown),
+            EconomicsError::RelockCooldown
+        );
 
-#[derive(Accounts)]
-pub struct ClaimRewards<'info> {
-    #[account(mut)]
-    pub lock: Account<'info, LockAccount>,
-    pub economics: Account<'info, EconomicsState>,
-    #[account(mut)]
+        require!(additional_duration >= 0, EconomicsError::InvalidLockDuration);
+        let end_time = lock.end_time
+            .checked_add(additional_duration)
+            .ok_or(EconomicsError::Overflow)?;
+        require!(
+            end_time - lock.start_time <= MAX_LOCK_DURATION,
+            EconomicsError::InvalidLockDuration
+        );
+
+        if additional_amount > 0 {
+            let total = lock.amount
+                .checked_add(additional_amount)
+                .ok_or(EconomicsError::Overflow)?;
+            require!(
+                economics.config.within_lock_cap(total),
+                EconomicsError::InvalidLockAmount
+            );
+            require!(
+                ctx.accounts.owner_token_account.amount >= additional_amount,
+                EconomicsError::InsufficientFunds
+            );
+            top_up_lock(lock, additional_amount, economics, now)?;
+
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.owner_token_account.to_account_info(),
+                        to: ctx.accounts.lock_token_account.to_account_info(),
+                        authority: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                additional_amount,
+            )?;
+        }
+
+        lock.end_time = end_time;
+
+        Ok(())
+    }
+
+    // Spends part of a lock's principal on a service fee so holders can pay
+    // without unlocking. Only approved service fee accounts can be paid.
+    // Claims are checkpointed pro rata, as in split_lock, so the spent tokens
+    // take their share of the claimed rewards with them.
+    pub fn pay_from_lock(ctx: Context<PayFromLock>, amount: u64) -> Result<()> {
+        let lock = &mut ctx.accounts.lock;
+
+        require!(lock.locked, EconomicsError::LockNotActive);
+        require!(amount > 0 && amount <= lock.amount, EconomicsError::InsufficientFunds);
+
+        let spent_claimed = mul_div(lock.rewards_claimed, amount, lock.amount, Rounding::Down)?;
+        lock.rewards_claimed -= spent_claimed;
+        lock.amount -= amount;
+
+        let economics_key = ctx.accounts.economics.key();
+        let seeds: &[&[u8]] = &[
+            CUSTODY_AUTHORITY_SEED,
+            economics_key.as_ref(),
+            &[ctx.bumps.custody_authority],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.lock_token_account.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.custody_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn set_auto_stake(ctx: Context<SetAutoStake>, enabled: bool) -> Result<()> {
+        ctx.accounts.lock.auto_stake_rewards = enabled;
+        Ok(())
+    }
+
+    pub fn split_lock(ctx: Context<SplitLock>, amount: u64) -> Result<()> {
+        let lock = &mut ctx.accounts.lock;
+        let new_lock = &mut ctx.accounts.new_lock;
+
+        require!(lock.locked, EconomicsError::LockNotActive);
+        require!(amount > 0 && amount < lock.amount, EconomicsError::InvalidSplitAmount);
+
+        // Claims are checkpointed pro rata so neither half can re-claim the other's rewards
+        let moved_claimed = mul_div(lock.rewards_claimed, amount, lock.amount, Rounding::Down)?;
+
+        new_lock.owner = lock.owner;
+        new_lock.amount = amount;
+        new_lock.start_time = lock.start_time;
+        new_lock.end_time = lock.end_time;
+        new_lock.locked = true;
+        new_lock.rewards_claimed = moved_claimed;
+        new_lock.version = LOCK_VERSION;
+        new_lock.last_claim_ts = lock.last_claim_ts;
+        new_lock.auto_stake_rewards = lock.auto_stake_rewards;
+        new_lock.economics = lock.economics;
+        new_lock.token_mint = lock.token_mint;
+
+        lock.amount -= amount;
+        lock.rewards_claimed -= moved_claimed;
+
+        // Each lock keeps its principal in its own custody account
+        let economics_key = ctx.accounts.economics.key();
+        let seeds: &[&[u8]] = &[
+            CUSTODY_AUTHORITY_SEED,
+            economics_key.as_ref(),
+            &[ctx.bumps.custody_authority],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.lock_token_account.to_account_info(),
+                    to: ctx.accounts.new_lock_token_account.to_account_info(),
+                    authority: ctx.accounts.custody_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn merge_locks(ctx: Context<MergeLocks>) -> Result<()> {
+        let lock = &mut ctx.accounts.lock;
+        let absorbed = &ctx.accounts.absorbed;
+        let economics = &ctx.accounts.economics;
+        let now = Clock::get()?.unix_timestamp;
+
+        // Both locks belong to this economics, so matching owners is all that is left to check
+        require!(lock.locked && absorbed.locked, EconomicsError::LockNotActive);
+        // Closing the absorbed lock would drop whatever it still has vesting
+        require!(
+            absorbed.vesting_released >= absorbed.vesting_total,
+            EconomicsError::RewardsStillVesting
+        );
+
+        let pending = |l: &LockAccount| -> Result<u64> {
+            Ok(calculate_rewards(
+                l.amount,
+                l.start_time,
+                l.end_time,
+                economics.total_fees_collected,
+                now,
+                &economics.reward_schedule,
+                &economics.pause_windows,
+            )?.saturating_sub(l.rewards_claimed))
+        };
+        let unclaimed = pending(lock)?
+            .checked_add(pending(absorbed)?)
+            .ok_or(EconomicsError::Overflow)?;
+
+        // Keep the earlier start and the later end so neither lock is shortened
+        lock.amount = lock.amount
+            .checked_add(absorbed.amount)
+            .ok_or(EconomicsError::Overflow)?;
+        require!(
+            economics.config.within_lock_cap(lock.amount),
+            EconomicsError::InvalidLockAmount
+        );
+        lock.start_time = lock.start_time.min(absorbed.start_time);
+        lock.end_time = lock.end_time.max(absorbed.end_time);
+        lock.last_claim_ts = lock.last_claim_ts.max(absorbed.last_claim_ts);
+
+        // Checkpoint so the merged lock owes exactly what the two locks owed
+        let accrued = calculate_rewards(
+            lock.amount,
+            lock.start_time,
+            lock.end_time,
+            economics.total_fees_collected,
+            now,
+            &economics.reward_schedule,
+            &economics.pause_windows,
+        )?;
+        lock.rewards_claimed = accrued.saturating_sub(unclaimed);
+
+        // Fold the absorbed lock's custody into the kept lock's and close it
+        let economics_key = economics.key();
+        let seeds: &[&[u8]] = &[
+            CUSTODY_AUTHORITY_SEED,
+            economics_key.as_ref(),
+            &[ctx.bumps.custody_authority],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.absorbed_token_account.to_account_info(),
+                    to: ctx.accounts.lock_token_account.to_account_info(),
+                    authority: ctx.accounts.custody_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            ctx.accounts.absorbed_token_account.amount,
+        )?;
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.absorbed_token_account.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.custody_authority.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        Ok(())
+    }
+
+    // `paid_out` is what the pre-v2 program actually paid the lock in rewards,
+    // taken from its transfer history; later versions ignore it
+    pub fn migrate_lock(ctx: Context<MigrateLock>, paid_out: u64) -> Result<()> {
+        let lock_info = ctx.accounts.lock.to_account_info();
+
+        nexus_common::verify_account_type(&lock_info, &crate::ID, "LockAccount")
+            .map_err(|_| error!(EconomicsError::InvalidLockAccount))?;
+
+        // Grow v1 accounts to the current layout, topping up rent from the authority
+        let new_len = LockAccount::LEN;
+        if lock_info.data_len() < new_len {
+            let required = Rent::get()?.minimum_balance(new_len);
+            let top_up = required.saturating_sub(lock_info.lamports());
+            if top_up > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.authority.to_account_info(),
+                            to: lock_info.clone(),
+                        },
+                    ),
+                    top_up,
+                )?;
+            }
+            lock_info.realloc(new_len, true)?;
+            require!(
+                Rent::get()?.is_exempt(lock_info.lamports(), new_len),
+                EconomicsError::NotRentExempt
+            );
+        }
+
+        // Fields added after v1 read back as zero once the account is grown
+        let mut lock = Account::<LockAccount>::try_from(&lock_info)?;
+        require!(lock.version < LOCK_VERSION, EconomicsError::LockAlreadyMigrated);
+
+        // v1 locks never tracked claims, so checkpoint what was paid out, which
+        // cannot exceed what has accrued; the rest stays claimable
+        if lock.version < 2 {
+            let accrued = calculate_rewards(
+                lock.amount,
+                lock.start_time,
+                lock.end_time,
+                ctx.accounts.economics.total_fees_collected,
+                Clock::get()?.unix_timestamp,
+                &ctx.accounts.economics.reward_schedule,
+                &ctx.accounts.economics.pause_windows,
+            )?;
+            require!(paid_out <= accrued, EconomicsError::PaidOutExceedsAccrued);
+            lock.rewards_claimed = paid_out;
+        }
+        // Locks predating v6 are bound to the economics migrating them, and
+        // their principal moves from the shared custody account into their own
+        if lock.version < 6 {
+            lock.economics = ctx.accounts.economics.key();
+            lock.token_mint = ctx.accounts.economics.token_mint;
+
+            let economics_key = ctx.accounts.economics.key();
+            let seeds: &[&[u8]] = &[
+                b"treasury",
+                economics_key.as_ref(),
+                &[ctx.bumps.treasury_authority],
+            ];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.legacy_custody.to_account_info(),
+                        to: ctx.accounts.lock_token_account.to_account_info(),
+                        authority: ctx.accounts.treasury_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                lock.amount,
+            )?;
+        }
+        lock.version = LOCK_VERSION;
+        lock.exit(&crate::ID)?;
+
+        Ok(())
+    }
+
+    // v6 locks already have their own custody, but it is owned by the treasury
+    // authority; hand it over to the custody authority
+    pub fn migrate_custody(ctx: Context<MigrateCustody>) -> Result<()> {
+        let lock = &mut ctx.accounts.lock;
+        require!(lock.version < LOCK_VERSION, EconomicsError::LockAlreadyMigrated);
+
+        let economics_key = ctx.accounts.economics.key();
+        let seeds: &[&[u8]] = &[
+            b"treasury",
+            economics_key.as_ref(),
+            &[ctx.bumps.treasury_authority],
+        ];
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::SetAuthority {
+                    current_authority: ctx.accounts.treasury_authority.to_account_info(),
+                    account_or_mint: ctx.accounts.lock_token_account.to_account_info(),
+                },
+                &[seeds],
+            ),
+            AuthorityType::AccountOwner,
+            Some(ctx.accounts.custody_authority.key()),
+        )?;
+        lock.version = LOCK_VERSION;
+
+        Ok(())
+    }
+
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<HealthStatus> {
+        let economics = &ctx.accounts.economics;
+
+        if economics.fee_shares.total() != 100 {
+            return Ok(HealthStatus::SharesMismatch);
+        }
+        if economics.total_burned > economics.total_fees_collected {
+            return Ok(HealthStatus::BurnExceedsFees);
+        }
+        if economics.config.max_lock_duration < MIN_LOCK_DURATION
+            || economics.config.max_lock_duration > MAX_LOCK_DURATION
+        {
+            return Ok(HealthStatus::InvalidLockDuration);
+        }
+
+        Ok(HealthStatus::Healthy)
+    }
+
+    pub fn sweep_unclaimed(ctx: Context<SweepUnclaimed>) -> Result<()> {
+        let lock = &mut ctx.accounts.lock;
+        let economics = &ctx.accounts.economics;
+        let now = Clock::get()?.unix_timestamp;
+
+        // Only locks that expired more than a grace period ago can be swept
+        require!(
+            now >= lock.end_time.saturating_add(SWEEP_GRACE_PERIOD),
+            EconomicsError::SweepTooEarly
+        );
+
+        let accrued = calculate_rewards(
+            lock.amount,
+            lock.start_time,
+            lock.end_time,
+            economics.total_fees_collected,
+            lock.end_time,
+            &economics.reward_schedule,
+            &economics.pause_windows,
+        )?;
+        let unclaimed = accrued.saturating_sub(lock.rewards_claimed);
+
+        // Return unclaimed rewards to the protocol treasury
+        if unclaimed > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.rewards_treasury.to_account_info(),
+                        to: ctx.accounts.protocol_treasury.to_account_info(),
+                        authority: ctx.accounts.rewards_authority.to_account_info(),
+                    },
+                ),
+                unclaimed,
+            )?;
+        }
+
+        // Return the principal to the owner and close the lock's custody
+        let economics_key = economics.key();
+        let seeds: &[&[u8]] = &[
+            CUSTODY_AUTHORITY_SEED,
+            economics_key.as_ref(),
+            &[ctx.bumps.custody_authority],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.lock_token_account.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: ctx.accounts.custody_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            ctx.accounts.lock_token_account.amount,
+        )?;
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.lock_token_account.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.custody_authority.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        lock.locked = false;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeEconomics<'info> {
+    // `init` fails on an existing account, so live state can never be re-initialized
+    #[account(
+        init,
+        payer = authority,
+        space = EconomicsState::LEN,
+        seeds = [ECONOMICS_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub economics: Account<'info, EconomicsState>,
+    pub token_mint: Account<'info, token::Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardSchedule<'info> {
+    #[account(mut, has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetParticipationBoost<'info> {
+    #[account(mut, has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardsPaused<'info> {
+    #[account(mut, has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGovernanceProgram<'info> {
+    #[account(mut, has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEconomicsParams<'info> {
+    #[account(mut)]
+    pub economics: Account<'info, EconomicsState>,
+    /// CHECK: verified as an executed proposal of the configured governance program
+    pub proposal: AccountInfo<'info>,
+    // Marks the proposal as consumed; `init` fails on a replay
+    #[account(
+        init,
+        payer = proposer,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied", proposal.key().as_ref()],
+        bump
+    )]
+    pub applied_proposal: Account<'info, AppliedProposal>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RebalanceTreasuries<'info> {
+    pub economics: Account<'info, EconomicsState>,
+    /// CHECK: verified as an executed proposal of the configured governance program
+    pub proposal: AccountInfo<'info>,
+    // Shares the parameter bundle marker, so a proposal enacts one action once
+    #[account(
+        init,
+        payer = proposer,
+        space = AppliedProposal::LEN,
+        seeds = [b"applied", proposal.key().as_ref()],
+        bump
+    )]
+    pub applied_proposal: Account<'info, AppliedProposal>,
+    /// CHECK: PDA that owns the treasury token accounts
+    #[account(seeds = [b"treasury", economics.key().as_ref()], bump)]
+    pub treasury_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = treasury_authority)]
+    pub source: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = treasury_authority,
+        token::mint = source.mint,
+        constraint = destination.key() != source.key() @ EconomicsError::InvalidRebalanceAmount
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeTokens<'info> {
+    #[account(mut, has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePaymentAccounts<'info> {
+    #[account(mut, has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasuryMultisig<'info> {
+    #[account(mut, has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RequestWithdrawal<'info> {
+    #[account(mut)]
+    pub economics: Account<'info, EconomicsState>,
+    #[account(
+        init,
+        payer = signer,
+        space = WithdrawalRequest::LEN,
+        seeds = [b"withdrawal", economics.key().as_ref(), &economics.withdrawal_count.to_le_bytes()],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    /// CHECK: PDA that owns the treasury token account
+    #[account(seeds = [b"treasury", economics.key().as_ref()], bump)]
+    pub treasury_authority: AccountInfo<'info>,
+    #[account(token::authority = treasury_authority)]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(token::mint = treasury.mint)]
+    pub destination: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RescueTokens<'info> {
+    #[account(has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    pub authority: Signer<'info>,
+    /// CHECK: PDA that owns the treasury token accounts
+    #[account(seeds = [b"treasury", economics.key().as_ref()], bump)]
+    pub treasury_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = treasury_authority)]
+    pub source: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = source.mint)]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveWithdrawal<'info> {
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut, has_one = economics)]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut, has_one = economics, has_one = treasury, has_one = destination)]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    /// CHECK: PDA that owns the treasury token account
+    #[account(seeds = [b"treasury", economics.key().as_ref()], bump)]
+    pub treasury_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessFee<'info> {
+    #[account(mut)]
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut)]
+    pub fee_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub venexus_treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub ainexus_treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub protocol_treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub token_mint: Account<'info, token::Mint>,
+    pub fee_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLock<'info> {
+    #[account(init, payer = owner, space = LockAccount::LEN)]
+    pub lock: Account<'info, LockAccount>,
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(mut, token::mint = economics.token_mint)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    #[account(address = economics.token_mint)]
+    pub token_mint: Account<'info, token::Mint>,
+    /// CHECK: PDA that owns lock custody accounts
+    #[account(seeds = [CUSTODY_AUTHORITY_SEED, economics.key().as_ref()], bump)]
+    pub custody_authority: AccountInfo<'info>,
+    // The lock's own custody, so no lock can ever move another lock's principal
+    #[account(
+        init,
+        payer = owner,
+        seeds = [CUSTODY_SEED, lock.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = custody_authority
+    )]
+    pub lock_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut, has_one = economics)]
+    pub lock: Account<'info, LockAccount>,
+    #[account(mut)]
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut)]
     pub owner_token_account: Account<'info, TokenAccount>,
     #[account(mut)]
     pub rewards_treasury: Account<'info, TokenAccount>,
     pub rewards_authority: Signer<'info>,
+    // Custody account of the lock; required when the lock auto-stakes rewards
+    #[account(mut, seeds = [CUSTODY_SEED, lock.key().as_ref()], bump)]
+    pub lock_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: governance voter profile of the lock owner, verified in the handler
+    pub voter_profile: Option<UncheckedAccount<'info>>,
     pub token_program: Program<'info, Token>,
 }
 
-#[account]
-pub struct EconomicsState {
-    pub config: EconomicsConfig,
-    pub total_fees_collected: u64,
-    pub total_burned: u64,
-}
+#[derive(Accounts)]
+pub struct ReleaseVestedRewards<'info> {
+    #[account(mut, has_one = economics)]
+    pub lock: Account<'info, LockAccount>,
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut, token::authority = lock.owner)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub rewards_treasury: Account<'info, TokenAccount>,
+    pub rewards_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct PayFromLock<'info> {
+    #[account(mut, has_one = owner, has_one = economics)]
+    pub lock: Account<'info, LockAccount>,
+    pub economics: Account<'info, EconomicsState>,
+    pub owner: Signer<'info>,
+    /// CHECK: PDA that owns lock custody accounts
+    #[account(seeds = [CUSTODY_AUTHORITY_SEED, economics.key().as_ref()], bump)]
+    pub custody_authority: AccountInfo<'info>,
+    #[account(mut, seeds = [CUSTODY_SEED, lock.key().as_ref()], bump)]
+    pub lock_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = economics.token_mint,
+        constraint = economics.is_payment_account(&destination.key()) @ EconomicsError::PaymentAccountNotApproved
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoStake<'info> {
+    #[account(mut, has_one = owner)]
+    pub lock: Account<'info, LockAccount>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendLock<'info> {
+    #[account(mut, has_one = owner, has_one = economics)]
+    pub lock: Account<'info, LockAccount>,
+    pub economics: Account<'info, EconomicsState>,
+    pub owner: Signer<'info>,
+    #[account(mut, token::mint = economics.token_mint)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [CUSTODY_SEED, lock.key().as_ref()], bump)]
+    pub lock_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SplitLock<'info> {
+    #[account(mut, has_one = owner, has_one = economics)]
+    pub lock: Account<'info, LockAccount>,
+    #[account(init, payer = owner, space = LockAccount::LEN)]
+    pub new_lock: Account<'info, LockAccount>,
+    pub economics: Account<'info, EconomicsState>,
+    #[account(address = economics.token_mint)]
+    pub token_mint: Account<'info, token::Mint>,
+    /// CHECK: PDA that owns lock custody accounts
+    #[account(seeds = [CUSTODY_AUTHORITY_SEED, economics.key().as_ref()], bump)]
+    pub custody_authority: AccountInfo<'info>,
+    #[account(mut, seeds = [CUSTODY_SEED, lock.key().as_ref()], bump)]
+    pub lock_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [CUSTODY_SEED, new_lock.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = custody_authority
+    )]
+    pub new_lock_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MergeLocks<'info> {
+    #[account(mut, has_one = owner, has_one = economics)]
+    pub lock: Account<'info, LockAccount>,
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = economics,
+        close = owner,
+        constraint = absorbed.key() != lock.key() @ EconomicsError::InvalidLockAccount
+    )]
+    pub absorbed: Account<'info, LockAccount>,
+    pub economics: Account<'info, EconomicsState>,
+    /// CHECK: PDA that owns lock custody accounts
+    #[account(seeds = [CUSTODY_AUTHORITY_SEED, economics.key().as_ref()], bump)]
+    pub custody_authority: AccountInfo<'info>,
+    #[account(mut, seeds = [CUSTODY_SEED, lock.key().as_ref()], bump)]
+    pub lock_token_account: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [CUSTODY_SEED, absorbed.key().as_ref()], bump)]
+    pub absorbed_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLock<'info> {
+    #[account(has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    /// CHECK: may still use the v1 layout; owner and discriminator are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub lock: UncheckedAccount<'info>,
+    #[account(address = economics.token_mint)]
+    pub token_mint: Account<'info, token::Mint>,
+    /// CHECK: PDA that held the shared custody of locks before v6
+    #[account(seeds = [b"treasury", economics.key().as_ref()], bump)]
+    pub treasury_authority: AccountInfo<'info>,
+    /// CHECK: PDA that owns lock custody accounts
+    #[account(seeds = [CUSTODY_AUTHORITY_SEED, economics.key().as_ref()], bump)]
+    pub custody_authority: AccountInfo<'info>,
+    // Custody shared by locks before v6, holding this lock's principal
+    #[account(
+        mut,
+        token::authority = treasury_authority,
+        token::mint = economics.token_mint
+    )]
+    pub legacy_custody: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [CUSTODY_SEED, lock.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = custody_authority
+    )]
+    pub lock_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateCustody<'info> {
+    #[account(has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut, has_one = economics)]
+    pub lock: Account<'info, LockAccount>,
+    /// CHECK: PDA that owned lock custody accounts before v7
+    #[account(seeds = [b"treasury", economics.key().as_ref()], bump)]
+    pub treasury_authority: AccountInfo<'info>,
+    /// CHECK: PDA that owns lock custody accounts
+    #[account(seeds = [CUSTODY_AUTHORITY_SEED, economics.key().as_ref()], bump)]
+    pub custody_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [CUSTODY_SEED, lock.key().as_ref()],
+        bump,
+        token::authority = treasury_authority
+    )]
+    pub lock_token_account: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    pub economics: Account<'info, EconomicsState>,
+}
+
+#[derive(Accounts)]
+pub struct SweepUnclaimed<'info> {
+    #[account(has_one = authority)]
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut, has_one = owner, has_one = economics, close = owner)]
+    pub lock: Account<'info, LockAccount>,
+    /// CHECK: receives the closed lock's rent, validated by `has_one`
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+    // Receives the lock's principal
+    #[account(mut, token::mint = economics.token_mint, token::authority = owner)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns lock custody accounts
+    #[account(seeds = [CUSTODY_AUTHORITY_SEED, economics.key().as_ref()], bump)]
+    pub custody_authority: AccountInfo<'info>,
+    #[account(mut, seeds = [CUSTODY_SEED, lock.key().as_ref()], bump)]
+    pub lock_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub rewards_treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub protocol_treasury: Account<'info, TokenAccount>,
+    pub rewards_authority: Signer<'info>,
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[account]
+pub struct EconomicsState {
+    pub authority: Pubkey,
+    pub config: EconomicsConfig,
+    pub total_fees_collected: u64,
+    pub total_burned: u64,
+    pub period_start: i64,
+    pub period_emitted: u64,
+    pub reward_schedule: Vec<RateCheckpoint>,
+    pub fee_tokens: Vec<FeeToken>,
+    pub treasury_signers: Vec<Pubkey>,
+    pub treasury_threshold: u8,  // approvals needed to withdraw from the treasury
+    pub withdrawal_count: u64,
+    pub fee_shares: FeeShares,
+    pub governance_program: Pubkey,  // default until set; gates update_economics_params
+    pub paused: bool,  // no rewards accrue or can be claimed while set
+    pub pause_windows: Vec<PauseWindow>,
+    pub participation_boost: ParticipationBoost,
+    pub bump: u8,
+    pub token_mint: Pubkey,  // NEXUS; never rescuable from the treasury
+    pub governance: Pubkey,  // realm under `governance_program` whose proposals are trusted
+    pub payment_accounts: Vec<Pubkey>,  // service fee accounts pay_from_lock may pay into
+}
+
+impl EconomicsState {
+    pub const LEN: usize = 8 + 32 + EconomicsConfig::LEN + 8 + 8 + 8 + 8
+        + 4 + RateCheckpoint::LEN * MAX_RATE_CHECKPOINTS
+        + 4 + FeeToken::LEN * MAX_FEE_TOKENS
+        + 4 + 32 * MAX_TREASURY_SIGNERS + 1 + 8
+        + FeeShares::LEN + 32
+        + 1 + 4 + PauseWindow::LEN * MAX_PAUSE_WINDOWS
+        + ParticipationBoost::LEN + 1 + 32 + 32
+        + 4 + 32 * MAX_PAYMENT_ACCOUNTS;
+
+    pub fn fee_token(&self, mint: &Pubkey) -> Option<&FeeToken> {
+        self.fee_tokens.iter().find(|fee_token| fee_token.mint == *mint)
+    }
+
+    pub fn is_payment_account(&self, account: &Pubkey) -> bool {
+        self.payment_accounts.contains(account)
+    }
+}
+
+// A pending treasury withdrawal and the signers who have approved it
+#[account]
+pub struct WithdrawalRequest {
+    pub economics: Pubkey,
+    pub request_id: u64,
+    pub treasury: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub approvals: Vec<Pubkey>,
+    pub executed: bool,
+}
+
+impl WithdrawalRequest {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 32 + 8 + 4 + 32 * MAX_TREASURY_SIGNERS + 1;
+}
+
+// Marks a governance proposal whose parameter bundle has been applied
+#[account]
+pub struct AppliedProposal {
+    pub economics: Pubkey,
+    pub proposal: Pubkey,
+}
+
+impl AppliedProposal {
+    pub const LEN: usize = 8 + 32 + 32;
+}
+
+// Percentages of each fee routed to each destination; they must sum to 100
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FeeShares {
+    pub venexus: u8,
+    pub ainexus: u8,
+    pub treasury: u8,
+    pub burn: u8,
+}
+
+impl FeeShares {
+    pub const LEN: usize = 4;
+
+    pub fn total(&self) -> u16 {
+        self.venexus as u16 + self.ainexus as u16 + self.treasury as u16 + self.burn as u16
+    }
+}
+
+// Optional new values for update_economics_params; `None` keeps the current value
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EconomicsParams {
+    pub fee_shares: Option<FeeShares>,
+    pub max_lock_duration: Option<i64>,
+    pub reward_rate: Option<u64>,
+    pub boost_factor: Option<u64>,
+    pub min_stake: Option<u64>,
+    pub max_rewards_per_period: Option<u64>,
+    pub reward_period: Option<i64>,
+    pub relock_cooldown: Option<i64>,
+    pub min_fee_amount: Option<u64>,
+    pub max_lock_amount: Option<u64>,
+    pub min_claim_delay: Option<i64>,
+    pub reward_vesting_period: Option<i64>,
+}
+
+// An approved fee token and where its fee shares are routed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FeeToken {
+    pub mint: Pubkey,
+    pub venexus_treasury: Pubkey,
+    pub ainexus_treasury: Pubkey,
+    pub protocol_treasury: Pubkey,
+    pub nexus_rate_bps: u64,  // NEXUS value of one unit, 10_000 = 1:1
+    pub burnable: bool,       // false routes the burn share to the protocol treasury
+}
+
+impl FeeToken {
+    pub const LEN: usize = 32 * 4 + 8 + 1;
+}
+
+// Emission rate in effect from `start_ts` until the next checkpoint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RateCheckpoint {
+    pub start_ts: i64,
+    pub rate_bps: u32,  // 10_000 = base emission
+}
+
+impl RateCheckpoint {
+    pub const LEN: usize = 8 + 4;
+}
+
+// Reward boost for lock owners who vote; `boost_bps` of 0 disables it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ParticipationBoost {
+    pub governance: Pubkey,  // governance instance whose voter profiles count
+    pub min_votes: u64,
+    pub recency: i64,        // seconds since the owner's last vote
+    pub boost_bps: u16,      // at most MAX_PARTICIPATION_BOOST_BPS
+}
+
+impl ParticipationBoost {
+    pub const LEN: usize = 32 + 8 + 8 + 2;
+}
+
+// Interval during which no rewards accrue; `end_ts` is i64::MAX while the pause is open
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PauseWindow {
+    pub start_ts: i64,
+    pub end_ts: i64,
+}
+
+impl PauseWindow {
+    pub const LEN: usize = 8 + 8;
+}
+
+#[account]
+pub struct LockAccount {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub locked: bool,
+    pub rewards_claimed: u64,
+    pub version: u8,
+    pub last_claim_ts: i64,
+    pub auto_stake_rewards: bool,  // compound claimed rewards into the lock
+    pub vesting_total: u64,     // claimed rewards in the current linear release
+    pub vesting_released: u64,  // part of `vesting_total` already paid out
+    pub vesting_start: i64,
+    pub economics: Pubkey,   // deployment whose custody holds the principal
+    pub token_mint: Pubkey,  // mint of the locked principal
+}
+
+impl LockAccount {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1 + 8 + 1 + 8 + 1 + 8 + 8 + 8 + 32 + 32;
+
+    // Part of the current release unlocked at `now`, paid out or not
+    pub fn vested_rewards(&self, now: i64, period: i64) -> Result<u64> {
+        let elapsed = now.saturating_sub(self.vesting_start);
+        if period <= 0 || elapsed >= period {
+            return Ok(self.vesting_total);
+        }
+        mul_div(self.vesting_total, elapsed.max(0) as u64, period as u64, Rounding::Down)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EconomicsConfig {
+     pub max_lock_duration: i64,
+    pub reward_rate: u64,
+    pub boost_factor: u64,
+    pub min_stake: u64,
+    pub max_rewards_per_period: u64,  // 0 disables the cap
+    pub reward_period: i64,
+    pub relock_cooldown: i64,  // seconds after a claim before the lock can be extended
+    pub min_fee_amount: u64,   // smallest fee process_fee accepts
+    pub max_lock_amount: u64,  // largest principal a single lock may hold, 0 disables the cap
+    pub min_claim_delay: i64,  // seconds a lock must exist before its first claim
+    pub reward_vesting_period: i64,  // seconds claimed rewards take to unlock, 0 pays instantly
+}
+
+impl EconomicsConfig {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8;
+
+    pub fn within_lock_cap(&self, amount: u64) -> bool {
+        self.max_lock_amount == 0 || amount <= self.max_lock_amount
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum HealthStatus {
+    Healthy,
+    SharesMismatch,
+    BurnExceedsFees,
+    InvalidLockDuration,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum FeeType {
+    Stream,
+    Agent,
+    Storage,
+    Custom,
+}
+
+// Emitted once per processed fee. The four destination amounts always sum
+// to `amount`, so off-chain accounting can reconcile every base unit.
+#[event]
+pub struct FeeDistributed {
+    pub economics: Pubkey,
+    pub fee_account: Pubkey,
+    pub fee_authority: Pubkey,
+    pub mint: Pubkey,
+    pub fee_type: FeeType,
+    pub amount: u64,
+    pub venexus_amount: u64,
+    pub ainexus_amount: u64,
+    pub treasury_amount: u64,
+    pub burn_amount: u64,
+}
+
+// Constants
+pub const ECONOMICS_SEED: &[u8] = b"economics";
+pub const CUSTODY_SEED: &[u8] = b"custody";
+pub const CUSTODY_AUTHORITY_SEED: &[u8] = b"custody_authority";
+const MIN_LOCK_DURATION: i64 = 7 * 24 * 60 * 60;   // 1 week
+const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60; // 4 years
+const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+const LOCK_VERSION: u8 = 7;
+const SWEEP_GRACE_PERIOD: i64 = 90 * 24 * 60 * 60; // 90 days after expiry
+const MAX_REWARD_VESTING_PERIOD: i64 = 90 * 24 * 60 * 60;
+const MAX_RATE_CHECKPOINTS: usize = 16;
+const MAX_PAUSE_WINDOWS: usize = 16;
+const MAX_PARTICIPATION_BOOST_BPS: u16 = 2_000;  // 20%
+const MAX_FEE_TOKENS: usize = 8;
+const MAX_PAYMENT_ACCOUNTS: usize = 16;
+const RATE_BPS_DENOMINATOR: u64 = 10_000;
+const MAX_TREASURY_SIGNERS: usize = 10;
+
+#[error_code]
+pub enum EconomicsError {
+    #[msg("Math overflow")]
+    Overflow,
+    #[msg("Token account balance is too low for this transfer")]
+    InsufficientFunds,
+    #[msg("Invalid lock duration")]
+    InvalidLockDuration,
+    #[msg("Lock not active")]
+    LockNotActive,
+    #[msg("Insufficient stake")]
+    InsufficientStake,
+    #[msg("Invalid fee amount")]
+    InvalidFeeAmount,
+    #[msg("Lock is still within its sweep grace period")]
+    SweepTooEarly,
+    #[msg("Reward period must be positive when an emission cap is set")]
+    InvalidRewardPeriod,
+    #[msg("Account is not a lock account")]
+    InvalidLockAccount,
+    #[msg("Lock is already on the current version")]
+    LockAlreadyMigrated,
+    #[msg("Account is not rent exempt")]
+    NotRentExempt,
+    #[msg("Reward schedule is too long or out of order")]
+    InvalidRewardSchedule,
+    #[msg("Fee token allowlist is full")]
+    TooManyFeeTokens,
+    #[msg("Fee token is already approved")]
+    FeeTokenAlreadyApproved,
+    #[msg("Fee token is not approved")]
+    FeeTokenNotApproved,
+    #[msg("Fee accounts do not match the approved fee token")]
+    FeeTokenMismatch,
+    #[msg("Conversion rate must be positive")]
+    InvalidConversionRate,
+    #[msg("Lock cannot be extended this soon after a claim")]
+    RelockCooldown,
+    #[msg("Relock cooldown must not be negative")]
+    InvalidRelockCooldown,
+    #[msg("Split amount must be positive and below the locked amount")]
+    InvalidSplitAmount,
+    #[msg("Treasury signers must be unique and the threshold between 1 and their count")]
+    InvalidMultisig,
+    #[msg("Not a treasury signer")]
+    NotTreasurySigner,
+    #[msg("Already approved")]
+    AlreadyApproved,
+    #[msg("Not enough treasury signers have approved")]
+    InsufficientApprovals,
+    #[msg("Withdrawal has already been executed")]
+    WithdrawalAlreadyExecuted,
+    #[msg("Fee is below the configured minimum")]
+    FeeBelowMinimum,
+    #[msg("Auto-stake needs the lock's custody token account")]
+    InvalidStakeTarget,
+    #[msg("Fee shares must sum to 100")]
+    InvalidFeeShares,
+    #[msg("No governance program is configured")]
+    GovernanceNotSet,
+    #[msg("Economics parameters can only be changed by a core proposal")]
+    NotCoreProposal,
+    #[msg("Only the proposer can apply the proposal")]
+    NotProposer,
+    #[msg("Lock amount exceeds the configured maximum")]
+    InvalidLockAmount,
+    #[msg("Reward accrual is paused")]
+    RewardsPaused,
+    #[msg("Reward accrual is not paused")]
+    RewardsNotPaused,
+    #[msg("No pause windows left")]
+    TooManyPauseWindows,
+    #[msg("Rebalance needs a positive amount within the source balance and two distinct treasuries")]
+    InvalidRebalanceAmount,
+    #[msg("Participation boost is above the maximum or has a negative recency")]
+    InvalidParticipationBoost,
+    #[msg("Voter profile does not belong to the lock owner in the configured governance")]
+    InvalidVoterProfile,
+    #[msg("Reward payout is below the requested minimum")]
+    RewardsBelowMinimum,
+    #[msg("Lock has not been held long enough to claim")]
+    ClaimTooEarly,
+    #[msg("Claim delay cannot be negative")]
+    InvalidClaimDelay,
+    #[msg("Protocol and approved fee tokens cannot be rescued")]
+    ProtectedMint,
+    #[msg("Account holds no tokens to rescue")]
+    NothingToRescue,
+    #[msg("Reward vesting period must be between zero and 90 days")]
+    InvalidVestingPeriod,
+    #[msg("No vested rewards to release")]
+    NothingVested,
+    #[msg("Lock still has rewards vesting")]
+    RewardsStillVesting,
+    #[msg("Proposal belongs to a different governance realm")]
+    GovernanceRealmMismatch,
+    #[msg("Proposal does not commit to this action")]
+    ActionHashMismatch,
+    #[msg("Payment account allowlist is full")]
+    TooManyPaymentAccounts,
+    #[msg("Payment account is already approved")]
+    PaymentAccountAlreadyApproved,
+    #[msg("Lock payments can only go to an approved service fee account")]
+    PaymentAccountNotApproved,
+    #[msg("Rewards paid out cannot exceed those accrued")]
+    PaidOutExceedsAccrued,
+}
+
+/// Address of the economics state initialized by `authority`: the PDA of
+/// `[ECONOMICS_SEED, authority]` under this program.
+pub fn economics_address(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ECONOMICS_SEED, authority.as_ref()], &ID)
+}
+
+/// Address of the token account holding `lock`'s principal: the PDA of
+/// `[CUSTODY_SEED, lock]` under this program.
+pub fn custody_address(lock: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CUSTODY_SEED, lock.as_ref()], &ID)
+}
+
+/// Address of the authority owning every lock custody account of `economics`:
+/// the PDA of `[CUSTODY_AUTHORITY_SEED, economics]` under this program.
+pub fn custody_authority_address(economics: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CUSTODY_AUTHORITY_SEED, economics.as_ref()], &ID)
+}
+
+fn validate_config(config: &EconomicsConfig) -> Result<()> {
+    require!(
+        config.max_rewards_per_period == 0 || config.reward_period > 0,
+        EconomicsError::InvalidRewardPeriod
+    );
+    require!(config.relock_cooldown >= 0, EconomicsError::InvalidRelockCooldown);
+    require!(config.min_claim_delay >= 0, EconomicsError::InvalidClaimDelay);
+    require!(
+        config.reward_vesting_period >= 0 && config.reward_vesting_period <= MAX_REWARD_VESTING_PERIOD,
+        EconomicsError::InvalidVestingPeriod
+    );
+    require!(
+        config.max_lock_duration >= MIN_LOCK_DURATION && config.max_lock_duration <= MAX_LOCK_DURATION,
+        EconomicsError::InvalidLockDuration
+    );
+    Ok(())
+}
+
+/// Action hash a core proposal must carry to authorize `update_economics_params`
+/// with `params` on `economics`.
+pub fn economics_params_hash(economics: &Pubkey, params: &EconomicsParams) -> Result<[u8; 32]> {
+    let params = params.try_to_vec().map_err(|_| error!(EconomicsError::ActionHashMismatch))?;
+    Ok(hashv(&[b"update_economics_params", economics.as_ref(), &params]).to_bytes())
+}
+
+/// Action hash a core proposal must carry to authorize `rebalance_treasuries`
+/// moving `amount` from `source` to `destination` on `economics`.
+pub fn rebalance_hash(economics: &Pubkey, source: &Pubkey, destination: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[
+        b"rebalance_treasuries",
+        economics.as_ref(),
+        source.as_ref(),
+        destination.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+// A governance-enacted change must come from an executed core proposal of
+// the trusted realm, applied by its proposer. Callers still check that the
+// proposal's action hash matches what they are about to do.
+fn verify_core_proposal(
+    economics: &EconomicsState,
+    proposal: &AccountInfo,
+    proposer: &Pubkey,
+) -> Result<nexus_common::ProposalHeader> {
+    require!(
+        economics.governance_program != Pubkey::default(),
+        EconomicsError::GovernanceNotSet
+    );
+
+    let proposal = nexus_common::verify_executed_proposal(proposal, &economics.governance_program)?;
+    require_keys_eq!(
+        proposal.governance,
+        economics.governance,
+        EconomicsError::GovernanceRealmMismatch
+    );
+    require!(
+        proposal.proposal_type == nexus_common::ProposalKind::Core,
+        EconomicsError::NotCoreProposal
+    );
+    require_keys_eq!(proposal.proposer, *proposer, EconomicsError::NotProposer);
+    Ok(proposal)
+}
+
+// Boost in bps the lock owner qualifies for. A missing or stale profile
+// simply earns no boost; a profile for someone else is rejected.
+fn participation_boost_bps(
+    economics: &EconomicsState,
+    voter_profile: Option<&AccountInfo>,
+    owner: &Pubkey,
+    now: i64,
+) -> Result<u64> {
+    let boost = &economics.participation_boost;
+    let info = match voter_profile {
+        Some(info) if boost.boost_bps > 0 => info,
+        _ => return Ok(0),
+    };
+    require!(
+        economics.governance_program != Pubkey::default(),
+        EconomicsError::GovernanceNotSet
+    );
+
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"voter", boost.governance.as_ref(), owner.as_ref()],
+        &economics.governance_program,
+    );
+    require_keys_eq!(info.key(), expected, EconomicsError::InvalidVoterProfile);
+
+    let profile = nexus_common::load_voter_profile(info, &economics.governance_program)?;
+    let active = profile.proposals_voted >= boost.min_votes
+        && now.saturating_sub(profile.last_voted_at) <= boost.recency;
+    Ok(if active { boost.boost_bps as u64 } else { 0 })
+}
+
+// Adds `amount` to the lock principal. Accrual so far scales with the
+// principal, so the difference is checkpointed to keep the top-up from
+// earning rewards retroactively.
+fn top_up_lock(lock: &mut LockAccount, amount: u64, economics: &EconomicsState, now: i64) -> Result<()> {
+    let accrued_before = calculate_rewards(
+        lock.amount,
+        lock.start_time,
+        lock.end_time,
+        economics.total_fees_collected,
+        now,
+        &economics.reward_schedule,
+        &economics.pause_windows,
+    )?;
+    let new_amount = lock.amount
+        .checked_add(amount)
+        .ok_or(EconomicsError::Overflow)?;
+    let accrued_after = calculate_rewards(
+        new_amount,
+        lock.start_time,
+        lock.end_time,
+        economics.total_fees_collected,
+        now,
+        &economics.reward_schedule,
+        &economics.pause_windows,
+    )?;
+    lock.rewards_claimed += accrued_after.saturating_sub(accrued_before);
+    lock.amount = new_amount;
+    Ok(())
+}
+
+// Helper functions for reward calculations
+fn calculate_rewards(
+    amount: u64,
+    start_time: i64,
+    end_time: i64,
+    total_fees: u64,
+    now: i64,
+    schedule: &[RateCheckpoint],
+    pauses: &[PauseWindow],
+) -> Result<u64> {
+    // Rewards stop accruing once the lock expires
+    let accrual_end = now.min(end_time);
+    
+    // Nothing accrues while there are no fees to distribute
+    if accrual_end <= start_time || total_fees == 0 {
+        return Ok(0);
+    }
+
+    let mut weighted_elapsed = rate_weighted_elapsed(start_time, accrual_end, schedule)?;
+
+    // Paused time earns nothing, so take out its share of the integral
+    for pause in pauses {
+        let from = pause.start_ts.max(start_time);
+        let to = pause.end_ts.min(accrual_end);
+        if to > from {
+            weighted_elapsed -= rate_weighted_elapsed(from, to, schedule)?;
+        }
+    }
+
+    // amount * (duration / year) * (weighted_elapsed / (duration * rate_bps)).
+    // The lock duration cancels out, leaving one integer division rounded down.
+    let weighted_elapsed = u64::try_from(weighted_elapsed).map_err(|_| error!(EconomicsError::Overflow))?;
+    let year_bps = (SECONDS_PER_YEAR as u64)
+        .checked_mul(RATE_BPS_DENOMINATOR)
+        .ok_or(EconomicsError::Overflow)?;
+    mul_div(amount, weighted_elapsed, year_bps, Rounding::Down)
+}
+
+// Integrates the emission rate over [from, to), in seconds * rate_bps.
+// Time before the first checkpoint accrues at the base rate.
+fn rate_weighted_elapsed(from: i64, to: i64, schedule: &[RateCheckpoint]) -> Result<u128> {
+    let mut weighted: u128 = 0;
+    let mut cursor = from;
+    let mut rate = RATE_BPS_DENOMINATOR as u128;
+
+    for checkpoint in schedule {
+        if cursor >= to {
+            break;
+        }
+        let segment_end = checkpoint.start_ts.min(to);
+        if segment_end > cursor {
+            weighted = weighted
+                .checked_add((segment_end - cursor) as u128 * rate)
+                .ok_or(EconomicsError::Overflow)?;
+            cursor = segment_end;
+        }
+        rate = checkpoint.rate_bps as u128;
+    }
+    if to > cursor {
+        weighted = weighted
+            .checked_add((to - cursor) as u128 * rate)
+            .ok_or(EconomicsError::Overflow)?;
+    }
+
+    Ok(weighted)
+}
+
+// Save as: tests/economics.ts
+
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { NexusEconomics } from '../target/types/nexus_economics';
+import { createAccount, createMint, mintTo, transfer } from '@solana/spl-token';
+import { expect } from 'chai';
+
+describe('nexus-economics', () => {
+    const provider = anchor.AnchorProvider.env();
+    anchor.setProvider(provider);
+
+    const program = anchor.workspace.NexusEconomics as Program<NexusEconomics>;
+    
+    let economics: anchor.web3.PublicKey;
+    let feeAuthority: anchor.web3.Keypair;
+    let tokenMint: anchor.web3.PublicKey;
+    let rewardsAuthority: anchor.web3.Keypair;
+    let expiredLock: anchor.web3.PublicKey;
+    let cappedEconomics: anchor.web3.PublicKey;
+    let bigLock: anchor.web3.PublicKey;
+    let scheduledEconomics: anchor.web3.PublicKey;
+    let scheduledLock: anchor.web3.PublicKey;
+    let dailyAccrual: number;
+    let relockEconomics: anchor.web3.PublicKey;
+    let relockLock: anchor.web3.PublicKey;
+    let splittableLock: anchor.web3.PublicKey;
+    let shortLock: anchor.web3.PublicKey;
+    let longLock: anchor.web3.PublicKey;
+    let multisigTreasury: anchor.web3.PublicKey;
+    let minFeeEconomics: anchor.web3.PublicKey;
+    let autoStakeLock: anchor.web3.PublicKey;
+    let governedEconomics: anchor.web3.PublicKey;
+    let enactedProposal: anchor.web3.PublicKey;
+    let rejectedBundleProposal: anchor.web3.PublicKey;
+    let rebalanceProposal: anchor.web3.PublicKey;
+    let foreignRealmProposal: anchor.web3.PublicKey;
+    let governedVenexusTreasury: anchor.web3.PublicKey;
+    let governedAinexusTreasury: anchor.web3.PublicKey;
+    let withdrawalDestination: anchor.web3.PublicKey;
+    const treasurySignerB = anchor.web3.Keypair.generate();
+    const treasurySignerC = anchor.web3.Keypair.generate();
+    let multiTokenEconomics: anchor.web3.PublicKey;
+    let slippageEconomics: anchor.web3.PublicKey;
+    let slippageLock: anchor.web3.PublicKey;
+    let usdcMint: anchor.web3.PublicKey;
+    let usdcFeeAccount: anchor.web3.PublicKey;
+    let usdcVenexusTreasury: anchor.web3.PublicKey;
+    let usdcAinexusTreasury: anchor.web3.PublicKey;
+    let usdcProtocolTreasury: anchor.web3.PublicKey;
+
+    before(async () => {
+        // Setup test accounts and mint
+    });
+
+    const treasuryAuthorityOf = (economicsKey: anchor.web3.PublicKey) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from('treasury'), economicsKey.toBuffer()],
+            program.programId
+        )[0];
+    const custodyAuthorityOf = (economicsKey: anchor.web3.PublicKey) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from('custody_authority'), economicsKey.toBuffer()],
+            program.programId
+        )[0];
+    const custodyOf = (lockKey: anchor.web3.PublicKey) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from('custody'), lockKey.toBuffer()],
+            program.programId
+        )[0];
+
+    // Initializes an economics state configured like `economics` apart from
+    // `overrides`, under a fresh authority
+    const createEconomics = async (overrides: object) => {
+        const authority = anchor.web3.Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(authority.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+        );
+        const [newEconomics] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from('economics'), authority.publicKey.toBuffer()],
+            program.programId
+        );
+        const base = await program.account.economicsState.fetch(economics);
+
+        await program.methods
+            .initializeEconomics({ ...base.config, ...overrides })
+            .accounts({
+                economics: newEconomics,
+                tokenMint: tokenMint,
+                authority: authority.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([authority])
+            .rpc();
+
+        return { economics: newEconomics, authority };
+    };
+
+    // Routes a fee through `economicsKey` so its locks start accruing rewards
+    const collectFee = (economicsKey: anchor.web3.PublicKey) => program.methods
+        .processFee(new anchor.BN(1_000_000_000), { stream: {} }, null, null)
+        .accounts({
+            economics: economicsKey,
+            feeAccount: feeAccount,
+            venexusTreasury: venexusTreasury,
+            ainexusTreasury: ainexusTreasury,
+            protocolTreasury: protocolTreasury,
+            tokenMint: tokenMint,
+            feeAuthority: feeAuthority.publicKey,
+            tokenProgram: anchor.web3.TokenProgram.programId,
+        })
+        .signers([feeAuthority])
+        .rpc();
+
+    // Locks `amount` of the wallet's NEXUS in `economicsKey` for `duration` seconds
+    const lockIn = async (economicsKey: anchor.web3.PublicKey, amount: anchor.BN, duration: anchor.BN) => {
+        const newLock = anchor.web3.Keypair.generate();
+        await program.methods
+            .createLock(amount, duration)
+            .accounts({
+                lock: newLock.publicKey,
+                economics: economicsKey,
+                owner: provider.wallet.publicKey,
+                ownerTokenAccount: ownerTokenAccount,
+                tokenMint: tokenMint,
+                custodyAuthority: custodyAuthorityOf(economicsKey),
+                lockTokenAccount: custodyOf(newLock.publicKey),
+                tokenProgram: anchor.web3.TokenProgram.programId,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([newLock])
+            .rpc();
+
+        return newLock.publicKey;
+    };
+
+    it('Initializes economics at the authority PDA', async () => {
+        let bump: number;
+        [economics, bump] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from('economics'), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const config = {
+            minLockDuration: new anchor.BN(7 * 24 * 60 * 60),
+            maxLockDuration: new anchor.BN(4 * 365 * 24 * 60 * 60),
+            rewardRate: new anchor.BN(10),
+            boostFactor: new anchor.BN(2),
+            minStake: new anchor.BN(1000 * 10^9),
+            maxRewardsPerPeriod: new anchor.BN(0),
+            rewardPeriod: new anchor.BN(24 * 60 * 60),
+            relockCooldown: new anchor.BN(0),
+            minFeeAmount: new anchor.BN(0),
+            maxLockAmount: new anchor.BN(0),
+            minClaimDelay: new anchor.BN(0),
+            rewardVestingPeriod: new anchor.BN(0),
+        };
+
+        await program.methods
+            .initializeEconomics(config)
+            .accounts({
+                economics: economics,
+                tokenMint: tokenMint,
+                authority: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        const state = await program.account.economicsState.fetch(economics);
+        expect(state.config.minStake.toNumber()).to.equal(config.minStake.toNumber());
+        expect(state.authority.toBase58()).to.equal(provider.wallet.publicKey.toBase58());
+        expect(state.bump).to.equal(bump);
+        expect(state.tokenMint.toBase58()).to.equal(tokenMint.toBase58());
+    });
+
+    it('Refuses to initialize economics twice', async () => {
+        const state = await program.account.economicsState.fetch(economics);
+
+        try {
+            await program.methods
+                .initializeEconomics(state.config)
+                .accounts({
+                    economics: economics,
+                    tokenMint: tokenMint,
+                    authority: provider.wallet.publicKey,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .rpc();
+            expect.fail('existing economics state should not be re-initialized');
+        } catch (err) {
+            expect(err.logs.join('\n')).to.include('already in use');
+        }
+
+        const after = await program.account.economicsState.fetch(economics);
+        expect(after.totalFeesCollected.toNumber()).to.equal(state.totalFeesCollected.toNumber());
+    });
+
+    it('Processes fees', async () => {
+        const amount = new anchor.BN(100 * 10^9);
+        const feeType = { stream: {} };
+
+        await program.methods
+            .processFee(amount, feeType, null, null)
+            .accounts({
+                economics: economics,
+                feeAccount: feeAccount,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocolTreasury,
+                tokenMint: tokenMint,
+                feeAuthority: feeAuthority.publicKey,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([feeAuthority])
+            .rpc();
+
+        const state = await program.account.economicsState.fetch(economics);
+        expect(state.totalFeesCollected.toNumber()).to.equal(amount.toNumber());
+    });
+
+    it('Rejects fees outside the expected range', async () => {
+        const amount = new anchor.BN(100 * 10^9);
+        const feeType = { stream: {} };
+
+        try {
+            await program.methods
+                .processFee(amount, feeType, amount.addn(1), null)
+                .accounts({
+                    economics: economics,
+                    feeAccount: feeAccount,
+                    venexusTreasury: venexusTreasury,
+                    ainexusTreasury: ainexusTreasury,
+                    protocolTreasury: protocolTreasury,
+                    tokenMint: tokenMint,
+                    feeAuthority: feeAuthority.publicKey,
+                    tokenProgram: anchor.web3.TokenProgram.programId,
+                })
+                .signers([feeAuthority])
+                .rpc();
+            expect.fail('fee below min_amount should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidFeeAmount');
+        }
+
+        try {
+            await program.methods
+                .processFee(amount, feeType, null, amount.subn(1))
+                .accounts({
+                    economics: economics,
+                    feeAccount: feeAccount,
+                    venexusTreasury: venexusTreasury,
+                    ainexusTreasury: ainexusTreasury,
+                    protocolTreasury: protocolTreasury,
+                    tokenMint: tokenMint,
+                    feeAuthority: feeAuthority.publicKey,
+                    tokenProgram: anchor.web3.TokenProgram.programId,
+                })
+                .signers([feeAuthority])
+                .rpc();
+            expect.fail('fee above max_amount should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidFeeAmount');
+        }
+    });
+
+    it('Rejects dust fees and keeps the accumulator exact', async () => {
+        // `minFeeEconomics` only accepts fees of at least 1,000 units
+        const processFee = (amount: anchor.BN) => program.methods
+            .processFee(amount, { stream: {} }, null, null)
+            .accounts({
+                economics: minFeeEconomics,
+                feeAccount: feeAccount,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocolTreasury,
+                tokenMint: tokenMint,
+                feeAuthority: feeAuthority.publicKey,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([feeAuthority])
+            .rpc();
+        const balance = async (account: anchor.web3.PublicKey) =>
+            Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+        const before = await program.account.economicsState.fetch(minFeeEconomics);
+        for (let i = 0; i < 5; i++) {
+            try {
+                await processFee(new anchor.BN(999));
+                expect.fail('fee below the minimum should be rejected');
+            } catch (err) {
+                expect(err.error.errorCode.code).to.equal('FeeBelowMinimum');
+            }
+        }
+        const afterSpam = await program.account.economicsState.fetch(minFeeEconomics);
+        expect(afterSpam.totalFeesCollected.toString()).to.equal(before.totalFeesCollected.toString());
+
+        // An amount that does not split evenly still distributes every unit
+        const amount = 1_003;
+        const treasuries = [venexusTreasury, ainexusTreasury, protocolTreasury];
+        const balancesBefore = await Promise.all(treasuries.map(balance));
+        await processFee(new anchor.BN(amount));
+        const balancesAfter = await Promise.all(treasuries.map(balance));
+
+        const after = await program.account.economicsState.fetch(minFeeEconomics);
+        const distributed = balancesAfter.reduce((sum, value, i) => sum + value - balancesBefore[i], 0);
+        const burned = after.totalBurned.sub(before.totalBurned).toNumber();
+        expect(after.totalFeesCollected.sub(before.totalFeesCollected).toNumber()).to.equal(amount);
+        expect(distributed + burned).to.equal(amount);
+    });
+
+    it('Distributes every unit of randomly sized fees', async () => {
+        const balance = async (account: anchor.web3.PublicKey) =>
+            Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+        const treasuries = [venexusTreasury, ainexusTreasury, protocolTreasury];
+
+        for (let i = 0; i < 10; i++) {
+            // Amounts that are rarely a multiple of 10, so every share rounds
+            const amount = 1_000 + Math.floor(Math.random() * 1_000_000);
+            const before = await program.account.economicsState.fetch(minFeeEconomics);
+            const balancesBefore = await Promise.all(treasuries.map(balance));
+
+            await program.methods
+                .processFee(new anchor.BN(amount), { stream: {} }, null, null)
+                .accounts({
+                    economics: minFeeEconomics,
+                    feeAccount: feeAccount,
+                    venexusTreasury: venexusTreasury,
+                    ainexusTreasury: ainexusTreasury,
+                    protocolTreasury: protocolTreasury,
+                    tokenMint: tokenMint,
+                    feeAuthority: feeAuthority.publicKey,
+                    tokenProgram: anchor.web3.TokenProgram.programId,
+                })
+                .signers([feeAuthority])
+                .rpc();
+
+            const balancesAfter = await Promise.all(treasuries.map(balance));
+            const after = await program.account.economicsState.fetch(minFeeEconomics);
+            const received = balancesAfter.map((value, j) => value - balancesBefore[j]);
+            const burned = after.totalBurned.sub(before.totalBurned).toNumber();
+
+            // Fixed shares round down; the protocol treasury takes the remainder
+            expect(received[0]).to.equal(Math.floor(amount * 40 / 100));
+            expect(received[1]).to.equal(Math.floor(amount * 30 / 100));
+            expect(burned).to.equal(Math.floor(amount * 10 / 100));
+            expect(received[0] + received[1] + received[2] + burned).to.equal(amount);
+        }
+    });
+
+    it('Emits a fee distribution that reconciles to the input amount', async () => {
+        const balance = async (account: anchor.web3.PublicKey) =>
+            Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+        const treasuries = [venexusTreasury, ainexusTreasury, protocolTreasury];
+        const amount = 123_457;
+        const balancesBefore = await Promise.all(treasuries.map(balance));
+
+        let event;
+        const listener = program.addEventListener('FeeDistributed', (e) => { event = e; });
+
+        await program.methods
+            .processFee(new anchor.BN(amount), { agent: {} }, null, null)
+            .accounts({
+                economics: minFeeEconomics,
+                feeAccount: feeAccount,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocolTreasury,
+                tokenMint: tokenMint,
+                feeAuthority: feeAuthority.publicKey,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([feeAuthority])
+            .rpc();
+
+        await program.removeEventListener(listener);
+        const received = (await Promise.all(treasuries.map(balance))).map((value, j) => value - balancesBefore[j]);
+
+        expect(event.amount.toNumber()).to.equal(amount);
+        expect(event.feeType).to.deep.equal({ agent: {} });
+        expect(event.feeAccount.toString()).to.equal(feeAccount.toString());
+        expect(event.venexusAmount.toNumber()).to.equal(received[0]);
+        expect(event.ainexusAmount.toNumber()).to.equal(received[1]);
+        expect(event.treasuryAmount.toNumber()).to.equal(received[2]);
+        expect(
+            event.venexusAmount.toNumber() + event.ainexusAmount.toNumber()
+                + event.treasuryAmount.toNumber() + event.burnAmount.toNumber()
+        ).to.equal(amount);
+    });
+
+    it('Processes fees in two approved tokens', async () => {
+        // `multiTokenEconomics` starts with an empty allowlist. USDC is worth 2 NEXUS
+        // per unit and is not burned.
+        const approve = (mint, venexus, ainexus, protocol, nexusRateBps, burnable) => program.methods
+            .addFeeToken({
+                mint,
+                venexusTreasury: venexus,
+                ainexusTreasury: ainexus,
+                protocolTreasury: protocol,
+                nexusRateBps: new anchor.BN(nexusRateBps),
+                burnable,
+            })
+            .accounts({ economics: multiTokenEconomics, authority: provider.wallet.publicKey })
+            .rpc();
+        await approve(tokenMint, venexusTreasury, ainexusTreasury, protocolTreasury, 10_000, true);
+        await approve(usdcMint, usdcVenexusTreasury, usdcAinexusTreasury, usdcProtocolTreasury, 20_000, false);
+
+        const pay = (mint, feeAccount, venexus, ainexus, protocol, amount) => program.methods
+            .processFee(new anchor.BN(amount), { custom: {} }, null, null)
+            .accounts({
+                economics: multiTokenEconomics,
+                feeAccount: feeAccount,
+                venexusTreasury: venexus,
+                ainexusTreasury: ainexus,
+                protocolTreasury: protocol,
+                tokenMint: mint,
+                feeAuthority: feeAuthority.publicKey,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([feeAuthority])
+            .rpc();
+
+        await pay(tokenMint, feeAccount, venexusTreasury, ainexusTreasury, protocolTreasury, 1_000);
+        const usdcProtocolBefore = await provider.connection.getTokenAccountBalance(usdcProtocolTreasury);
+        await pay(usdcMint, usdcFeeAccount, usdcVenexusTreasury, usdcAinexusTreasury, usdcProtocolTreasury, 1_000);
+        const usdcProtocolAfter = await provider.connection.getTokenAccountBalance(usdcProtocolTreasury);
+
+        // 1,000 NEXUS plus 1,000 USDC counted as 2,000 NEXUS; only NEXUS is burned
+        const state = await program.account.economicsState.fetch(multiTokenEconomics);
+        expect(state.totalFeesCollected.toNumber()).to.equal(3_000);
+        expect(state.totalBurned.toNumber()).to.equal(100);
+        // USDC protocol share includes the redirected burn share: 20% + 10%
+        expect(Number(usdcProtocolAfter.value.amount) - Number(usdcProtocolBefore.value.amount)).to.equal(300);
+    });
+
+    it('Rejects fees in a token removed from the allowlist', async () => {
+        await program.methods
+            .removeFeeToken(usdcMint)
+            .accounts({ economics: multiTokenEconomics, authority: provider.wallet.publicKey })
+            .rpc();
+
+        try {
+            await program.methods
+                .processFee(new anchor.BN(1_000), { custom: {} }, null, null)
+                .accounts({
+                    economics: multiTokenEconomics,
+                    feeAccount: usdcFeeAccount,
+                    venexusTreasury: usdcVenexusTreasury,
+                    ainexusTreasury: usdcAinexusTreasury,
+                    protocolTreasury: usdcProtocolTreasury,
+                    tokenMint: usdcMint,
+                    feeAuthority: feeAuthority.publicKey,
+                    tokenProgram: anchor.web3.TokenProgram.programId,
+                })
+                .signers([feeAuthority])
+                .rpc();
+            expect.fail('removed fee token should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('FeeTokenNotApproved');
+        }
+    });
+
+    it('Rescues a foreign token from the treasury but never NEXUS', async () => {
+        // `strayTreasuryAccount` holds an unrelated mint and `nexusTreasuryAccount` holds
+        // NEXUS, both owned by the treasury PDA of `economics`
+        const payer = await provider.wallet.payer;
+        const treasuryAuthority = treasuryAuthorityOf(economics);
+        const strayMint = await createMint(provider.connection, payer, provider.wallet.publicKey, null, 0);
+        const strayTreasuryAccount = await createAccount(
+            provider.connection,
+            payer,
+            strayMint,
+            treasuryAuthority,
+            anchor.web3.Keypair.generate()
+        );
+        await mintTo(provider.connection, payer, strayMint, strayTreasuryAccount, payer, 1_000);
+        const strayDestination = await createAccount(provider.connection, payer, strayMint, provider.wallet.publicKey);
+        const nexusTreasuryAccount = await createAccount(
+            provider.connection,
+            payer,
+            tokenMint,
+            treasuryAuthority,
+            anchor.web3.Keypair.generate()
+        );
+        await transfer(provider.connection, payer, ownerTokenAccount, nexusTreasuryAccount, provider.wallet.publicKey, 1_000);
+
+        const rescue = (source: anchor.web3.PublicKey, destination: anchor.web3.PublicKey) => program.methods
+            .rescueTokens()
+            .accounts({
+                economics: economics,
+                authority: provider.wallet.publicKey,
+                treasuryAuthority: treasuryAuthority,
+                source: source,
+                destination: destination,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .rpc();
+        const balance = async (account: anchor.web3.PublicKey) =>
+            Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+        const stray = await balance(strayTreasuryAccount);
+        const destinationBefore = await balance(strayDestination);
+        await rescue(strayTreasuryAccount, strayDestination);
+        expect(await balance(strayTreasuryAccount)).to.equal(0);
+        expect(await balance(strayDestination)).to.equal(destinationBefore + stray);
+
+        const nexusBefore = await balance(nexusTreasuryAccount);
+        try {
+            await rescue(nexusTreasuryAccount, ownerTokenAccount);
+            expect.fail('the protocol token should not be rescuable');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ProtectedMint');
+        }
+        expect(await balance(nexusTreasuryAccount)).to.equal(nexusBefore);
+    });
+
+    it('Requires 2 of 3 treasury signers to withdraw', async () => {
+        // `multisigTreasury` is owned by the economics treasury PDA and holds NEXUS
+        await program.methods
+            .setTreasuryMultisig([provider.wallet.publicKey, treasurySignerB.publicKey, treasurySignerC.publicKey], 2)
+            .accounts({ economics: economics, authority: provider.wallet.publicKey })
+            .rpc();
+
+        const state = await program.account.economicsState.fetch(economics);
+        const [withdrawalRequest] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("withdrawal"), economics.toBuffer(), state.withdrawalCount.toArrayLike(Buffer, 'le', 8)],
+            program.programId
+        );
+        const [treasuryAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("treasury"), economics.toBuffer()],
+            program.programId
+        );
+        const amount = new anchor.BN(5_000);
+
+        await program.methods
+            .requestWithdrawal(amount)
+            .accounts({
+                economics: economics,
+                withdrawalRequest: withdrawalRequest,
+                treasuryAuthority: treasuryAuthority,
+                treasury: multisigTreasury,
+                destination: withdrawalDestination,
+                signer: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        const execute = () => program.methods
+            .executeWithdrawal()
+            .accounts({
+                economics: economics,
+                withdrawalRequest: withdrawalRequest,
+                treasuryAuthority: treasuryAuthority,
+                treasury: multisigTreasury,
+                destination: withdrawalDestination,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .rpc();
+
+        // The requester's approval alone is not enough
+        try {
+            await execute();
+            expect.fail('a single signer should not be able to withdraw');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InsufficientApprovals');
+        }
+
+        await program.methods
+            .approveWithdrawal()
+            .accounts({
+                economics: economics,
+                withdrawalRequest: withdrawalRequest,
+                signer: treasurySignerB.publicKey,
+            })
+            .signers([treasurySignerB])
+            .rpc();
+
+        const before = await provider.connection.getTokenAccountBalance(withdrawalDestination);
+        await execute();
+        const after = await provider.connection.getTokenAccountBalance(withdrawalDestination);
+        expect(Number(after.value.amount) - Number(before.value.amount)).to.equal(amount.toNumber());
+
+        const request = await program.account.withdrawalRequest.fetch(withdrawalRequest);
+        expect(request.executed).to.be.true;
+    });
+
+    it('Creates lock', async () => {
+        const amount = new anchor.BN(1000 * 10^9);
+        const duration = new anchor.BN(365 * 24 * 60 * 60);
+
+        await program.methods
+            .createLock(amount, duration)
+            .accounts({
+                lock: lock.publicKey,
+                economics: economics,
+                owner: provider.wallet.publicKey,
+                ownerTokenAccount: ownerTokenAccount,
+                tokenMint: tokenMint,
+                custodyAuthority: custodyAuthorityOf(economics),
+                lockTokenAccount: custodyOf(lock.publicKey),
+                tokenProgram: anchor.web3.TokenProgram.programId,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([lock])
+            .rpc();
+
+        const lockAccount = await program.account.lockAccount.fetch(lock.publicKey);
+        expect(lockAccount.amount.toNumber()).to.equal(amount.toNumber());
+    });
+
+    it('Keeps lock custody out of reach of treasury withdrawals', async () => {
+        const state = await program.account.economicsState.fetch(economics);
+        const [withdrawalRequest] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from('withdrawal'), economics.toBuffer(), state.withdrawalCount.toArrayLike(Buffer, 'le', 8)],
+            program.programId
+        );
+        try {
+            await program.methods
+                .requestWithdrawal(new anchor.BN(1))
+                .accounts({
+                    economics: economics,
+                    withdrawalRequest: withdrawalRequest,
+                    treasuryAuthority: treasuryAuthorityOf(economics),
+                    treasury: custodyOf(lock.publicKey),
+                    destination: withdrawalDestination,
+                    signer: provider.wallet.publicKey,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .rpc();
+            expect.fail('lock custody should not be withdrawable as a treasury');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ConstraintTokenOwner');
+        }
+    });
+
+    it('Caps the amount a single lock can hold', async () => {
+        const maxLockAmount = new anchor.BN(500_000_000_000);
+        const { economics: lockCapEconomics } = await createEconomics({ maxLockAmount });
+        const duration = new anchor.BN(365 * 24 * 60 * 60);
+        const create = (target: anchor.web3.Keypair, amount: anchor.BN) => program.methods
+            .createLock(amount, duration)
+            .accounts({
+                lock: target.publicKey,
+                economics: lockCapEconomics,
+                owner: provider.wallet.publicKey,
+                ownerTokenAccount: ownerTokenAccount,
+                tokenMint: tokenMint,
+                custodyAuthority: custodyAuthorityOf(lockCapEconomics),
+                lockTokenAccount: custodyOf(target.publicKey),
+                tokenProgram: anchor.web3.TokenProgram.programId,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([target])
+            .rpc();
+
+        const oversized = anchor.web3.Keypair.generate();
+        try {
+            await create(oversized, maxLockAmount.addn(1));
+            expect.fail('lock above the maximum should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidLockAmount');
+        }
+
+        const full = anchor.web3.Keypair.generate();
+        await create(full, maxLockAmount);
+        const fullLock = await program.account.lockAccount.fetch(full.publicKey);
+        expect(fullLock.amount.toString()).to.equal(maxLockAmount.toString());
+
+        // Topping up a lock already at the maximum is rejected too
+        try {
+            await program.methods
+                .extendLock(new anchor.BN(1), new anchor.BN(0))
+                .accounts({
+                    lock: full.publicKey,
+                    economics: lockCapEconomics,
+                    owner: provider.wallet.publicKey,
+                    ownerTokenAccount: ownerTokenAccount,
+                    lockTokenAccount: custodyOf(full.publicKey),
+                    tokenProgram: anchor.web3.TokenProgram.programId,
+                })
+                .rpc();
+            expect.fail('extension above the maximum should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidLockAmount');
+        }
+    });
+
+    it('Reports a healthy economics state', async () => {
+        const status = await program.methods
+            .healthCheck()
+            .accounts({ economics: economics })
+            .view();
+
+        expect(status).to.deep.equal({ healthy: {} });
+    });
+
+    const noParamChanges = {
+        feeShares: null,
+        maxLockDuration: null,
+        rewardRate: null,
+        boostFactor: null,
+        minStake: null,
+        maxRewardsPerPeriod: null,
+        rewardPeriod: null,
+        relockCooldown: null,
+        minFeeAmount: null,
+        maxLockAmount: null,
+        minClaimDelay: null,
+        rewardVestingPeriod: null,
+    };
+
+    const appliedProposalPda = (proposal: anchor.web3.PublicKey) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from('applied'), proposal.toBuffer()],
+            program.programId
+        )[0];
+
+    it('Applies a governance parameter bundle in one step', async () => {
+        // `governedEconomics` trusts one realm of the governance program; `enactedProposal`
+        // is an executed core proposal of that realm by the wallet whose action hash
+        // commits to `params`, both loaded into the test validator
+        const params = {
+            ...noParamChanges,
+            feeShares: { venexus: 45, ainexus: 25, treasury: 20, burn: 10 },
+            rewardRate: new anchor.BN(12),
+            minStake: new anchor.BN(2_000),
+        };
+        const apply = () => program.methods
+            .updateEconomicsParams(params)
+            .accounts({
+                economics: governedEconomics,
+                proposal: enactedProposal,
+                appliedProposal: appliedProposalPda(enactedProposal),
+                proposer: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        const before = await program.account.economicsState.fetch(governedEconomics);
+        await apply();
+
+        const state = await program.account.economicsState.fetch(governedEconomics);
+        expect(state.feeShares).to.deep.equal(params.feeShares);
+        expect(state.config.rewardRate.toNumber()).to.equal(12);
+        expect(state.config.minStake.toNumber()).to.equal(2_000);
+        expect(state.config.boostFactor.toNumber()).to.equal(before.config.boostFactor.toNumber());
+
+        // A proposal enacts its bundle once
+        try {
+            await apply();
+            expect.fail('an applied proposal should not be replayed');
+        } catch (err) {
+            expect(err.logs.join('\n')).to.include('already in use');
+        }
+    });
+
+    it('Only applies the bundle a proposal of the trusted realm commits to', async () => {
+        // `foreignRealmProposal` is an executed core proposal by the wallet in another realm,
+        // committing to `bundle`; `rejectedBundleProposal` commits to a different bundle
+        const bundle = { ...noParamChanges, rewardRate: new anchor.BN(50) };
+        const apply = (proposal: anchor.web3.PublicKey) => program.methods
+            .updateEconomicsParams(bundle)
+            .accounts({
+                economics: governedEconomics,
+                proposal: proposal,
+                appliedProposal: appliedProposalPda(proposal),
+                proposer: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        try {
+            await apply(foreignRealmProposal);
+            expect.fail('a proposal from another realm should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('GovernanceRealmMismatch');
+        }
+
+        try {
+            await apply(rejectedBundleProposal);
+            expect.fail('a bundle the proposal does not commit to should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ActionHashMismatch');
+        }
+    });
+
+    it('Rejects a parameter bundle whose combined shares are invalid', async () => {
+        // `rejectedBundleProposal` is a second executed core proposal by the wallet,
+        // committing to the bundle below
+        const before = await program.account.economicsState.fetch(governedEconomics);
+
+        try {
+            await program.methods
+                .updateEconomicsParams({
+                    ...noParamChanges,
+                    feeShares: { venexus: 50, ainexus: 30, treasury: 20, burn: 10 },
+                    rewardRate: new anchor.BN(99),
+                })
+                .accounts({
+                    economics: governedEconomics,
+                    proposal: rejectedBundleProposal,
+                    appliedProposal: appliedProposalPda(rejectedBundleProposal),
+                    proposer: provider.wallet.publicKey,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .rpc();
+            expect.fail('shares summing to 110 should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidFeeShares');
+        }
+
+        // The valid reward rate in the same bundle was not applied either
+        const after = await program.account.economicsState.fetch(governedEconomics);
+        expect(after.config.rewardRate.toNumber()).to.equal(before.config.rewardRate.toNumber());
+        expect(after.feeShares).to.deep.equal(before.feeShares);
+    });
+
+    it('Rebalances collected funds between treasuries via a passed proposal', async () => {
+        // `rebalanceProposal` is an executed core proposal by the wallet committing to move
+        // 1 NEXUS from `governedVenexusTreasury` to `governedAinexusTreasury`, both held by
+        // the treasury PDA of `governedEconomics`
+        const committedAmount = 1_000_000_000;
+        const [treasuryAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from('treasury'), governedEconomics.toBuffer()],
+            program.programId
+        );
+        const rebalance = (
+            amount: anchor.BN,
+            source = governedVenexusTreasury,
+            destination = governedAinexusTreasury,
+        ) => program.methods
+            .rebalanceTreasuries(amount)
+            .accounts({
+                economics: governedEconomics,
+                proposal: rebalanceProposal,
+                appliedProposal: appliedProposalPda(rebalanceProposal),
+                treasuryAuthority: treasuryAuthority,
+                source: source,
+                destination: destination,
+                proposer: provider.wallet.publicKey,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+        const balance = async (account: anchor.web3.PublicKey) =>
+            Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+        const sourceBefore = await balance(governedVenexusTreasury);
+        const destinationBefore = await balance(governedAinexusTreasury);
+
+        // Any other amount or direction than the proposal committed to is rejected
+        try {
+            await rebalance(new anchor.BN(committedAmount + 1));
+            expect.fail('an amount the proposal does not commit to should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ActionHashMismatch');
+        }
+        try {
+            await rebalance(new anchor.BN(committedAmount), governedAinexusTreasury, governedVenexusTreasury);
+            expect.fail('reversed treasuries should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ActionHashMismatch');
+        }
+
+        const amount = committedAmount;
+        await rebalance(new anchor.BN(amount));
+        expect(await balance(governedVenexusTreasury)).to.equal(sourceBefore - amount);
+        expect(await balance(governedAinexusTreasury)).to.equal(destinationBefore + amount);
+
+        // The proposal cannot move funds a second time
+        try {
+            await rebalance(new anchor.BN(amount));
+            expect.fail('an applied proposal should not be replayed');
+        } catch (err) {
+            expect(err.logs.join('\n')).to.include('already in use');
+        }
+    });
+
+    it('Caps reward emissions per period', async () => {
+        // `cappedEconomics` allows 1,000 units per day; `bigLock` has accrued more than that
+        const claim = () => program.methods
+            .claimRewards(null)
+            .accounts({
+                lock: bigLock,
+                economics: cappedEconomics,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                lockTokenAccount: null,
+                voterProfile: null,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([rewardsAuthority])
+            .rpc();
+
+        await claim();
+
+        const state = await program.account.economicsState.fetch(cappedEconomics);
+        const lockAccount = await program.account.lockAccount.fetch(bigLock);
+        expect(state.periodEmitted.toNumber()).to.equal(1000);
+        expect(lockAccount.rewardsClaimed.toNumber()).to.equal(1000);
+
+        // The cap is exhausted so a second claim in the same period pays nothing
+        await claim();
+        const after = await program.account.lockAccount.fetch(bigLock);
+        expect(after.rewardsClaimed.toNumber()).to.equal(1000);
+    });
+
+    it('Reports InsufficientFunds for unfunded locks and fees', async () => {
+        // A NEXUS account of the wallet that holds no tokens
+        const emptyTokenAccount = await createAccount(
+            provider.connection,
+            await provider.wallet.payer,
+            tokenMint,
+            provider.wallet.publicKey,
+            anchor.web3.Keypair.generate()
+        );
+        const expectInsufficient = async (attempt: Promise<string>) => {
+            try {
+                await attempt;
+                expect.fail('an unfunded transfer should be rejected');
+            } catch (err) {
+                expect(err.error.errorCode.code).to.equal('InsufficientFunds');
+            }
+        };
+        const unfunded = anchor.web3.Keypair.generate();
+
+        await expectInsufficient(program.methods
+            .createLock(new anchor.BN(1_000_000_000), new anchor.BN(365 * 24 * 60 * 60))
+            .accounts({
+                lock: unfunded.publicKey,
+                economics: economics,
+                owner: provider.wallet.publicKey,
+                ownerTokenAccount: emptyTokenAccount,
+                tokenMint: tokenMint,
+                custodyAuthority: custodyAuthorityOf(economics),
+                lockTokenAccount: custodyOf(unfunded.publicKey),
+                tokenProgram: anchor.web3.TokenProgram.programId,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([unfunded])
+            .rpc());
+
+        await expectInsufficient(program.methods
+            .extendLock(new anchor.BN(1), new anchor.BN(0))
+            .accounts({
+                lock: lock.publicKey,
+                economics: economics,
+                owner: provider.wallet.publicKey,
+                ownerTokenAccount: emptyTokenAccount,
+                lockTokenAccount: custodyOf(lock.publicKey),
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .rpc());
+
+        await expectInsufficient(program.methods
+            .processFee(new anchor.BN(1_000), { stream: {} }, null, null)
+            .accounts({
+                economics: minFeeEconomics,
+                feeAccount: emptyTokenAccount,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocolTreasury,
+                tokenMint: tokenMint,
+                feeAuthority: feeAuthority.publicKey,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([feeAuthority])
+            .rpc());
+    });
 
-#[account]
-pub struct LockAccount {
-    pub owner: Pubkey,
-    pub amount: u64,
-    pub start_time: i64,
-    pub end_time: i64,
-    pub locked: bool,
-}
+    it('Holds back the first claim until the minimum delay has passed', async () => {
+        const { economics: claimDelayEconomics } = await createEconomics({ minClaimDelay: new anchor.BN(5) });
+        const fresh = await lockIn(claimDelayEconomics, new anchor.BN(1_000_000_000), new anchor.BN(365 * 24 * 60 * 60));
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct EconomicsConfig {
-     pub max_lock_duration: i64,
-    pub reward_rate: u64,
-    pub boost_factor: u64,
-    pub min_stake: u64,
-}
+        const claim = () => program.methods
+            .claimRewards(null)
+            .accounts({
+                lock: fresh,
+                economics: claimDelayEconomics,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                lockTokenAccount: null,
+                voterProfile: null,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([rewardsAuthority])
+            .rpc();
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub enum FeeType {
-    Stream,
-    Agent,
-    Storage,
-    Custom,
-}
+        try {
+            await claim();
+            expect.fail('a claim inside the delay should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ClaimTooEarly');
+        }
 
-// Constants
-const MIN_LOCK_DURATION: i64 = 7 * 24 * 60 * 60;   // 1 week
-const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60; // 4 years
-const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+        await new Promise((resolve) => setTimeout(resolve, 6_000));
+        await claim();
 
-#[error_code]
-pub enum EconomicsError {
-    #[msg("Math overflow")]
-    Overflow,
-    #[msg("Invalid lock duration")]
-    InvalidLockDuration,
-    #[msg("Lock not active")]
-    LockNotActive,
-    #[msg("Insufficient stake")]
-    InsufficientStake,
-    #[msg("Invalid fee amount")]
-    InvalidFeeAmount,
-}
+        const lockAccount = await program.account.lockAccount.fetch(fresh);
+        expect(lockAccount.lastClaimTs.toNumber()).to.be.above(0);
+    });
 
-// Helper functions for reward calculations
-fn calculate_rewards(
-    amount: u64,
-    start_time: i64,
-    end_time: i64,
-    total_fees: u64,
-) -> Result<u64> {
-    let now = Clock::get()?.unix_timestamp;
-    let duration = end_time - start_time;
-    let elapsed = now - start_time;
-    
-    if elapsed <= 0 {
-        return Ok(0);
-    }
+    it('Unlocks claimed rewards gradually when reward vesting is enabled', async () => {
+        // Rewards vest over 10 seconds, and `vestingLock` accrues 1,000,000 a second
+        const { economics: vestingEconomics, authority: vestingAuthority } =
+            await createEconomics({ rewardVestingPeriod: new anchor.BN(10) });
+        await collectFee(vestingEconomics);
+        const vestingLock = await lockIn(vestingEconomics, new anchor.BN('31536000000000'), new anchor.BN(365 * 24 * 60 * 60));
+        await new Promise((resolve) => setTimeout(resolve, 2_000));
+        const balance = async () =>
+            (await provider.connection.getTokenAccountBalance(ownerTokenAccount)).value.amount;
+        const release = () => program.methods
+            .releaseVestedRewards()
+            .accounts({
+                lock: vestingLock,
+                economics: vestingEconomics,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([rewardsAuthority])
+            .rpc();
 
-    let lock_weight = (duration as f64) / (SECONDS_PER_YEAR as f64);
-    let time_factor = (elapsed as f64) / (duration as f64);
-    
-    let reward_base = ((amount as f64) * lock_weight * time_factor) as u64;
-    let fee_share = (total_fees * reward_base) / total_fees;
-    
-    Ok(fee_share)
-}
+        const before = await balance();
+        await program.methods
+            .claimRewards(null)
+            .accounts({
+                lock: vestingLock,
+                economics: vestingEconomics,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                lockTokenAccount: null,
+                voterProfile: null,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([rewardsAuthority])
+            .rpc();
 
-// Save as: tests/economics.ts
+        // The claim is recorded but nothing is paid yet
+        const claimed = await program.account.lockAccount.fetch(vestingLock);
+        expect(claimed.vestingTotal.toNumber()).to.be.above(0);
+        expect(claimed.vestingReleased.toNumber()).to.equal(0);
+        expect(await balance()).to.equal(before);
 
-import * as anchor from '@project-serum/anchor';
-import { Program } from '@project-serum/anchor';
-import { NexusEconomics } from '../target/types/nexus_economics';
-import { expect } from 'chai';
+        // Part way through the period only a share has unlocked
+        await new Promise((resolve) => setTimeout(resolve, 4_000));
+        await release();
+        const partial = await program.account.lockAccount.fetch(vestingLock);
+        expect(partial.vestingReleased.toNumber()).to.be.above(0);
+        expect(partial.vestingReleased.lt(claimed.vestingTotal)).to.be.true;
 
-describe('nexus-economics', () => {
-    const provider = anchor.AnchorProvider.env();
-    anchor.setProvider(provider);
+        // Nothing is released while rewards are paused
+        await program.methods
+            .pauseRewards()
+            .accounts({ economics: vestingEconomics, authority: vestingAuthority.publicKey })
+            .signers([vestingAuthority])
+            .rpc();
+        try {
+            await release();
+            expect.fail('releases should be rejected while paused');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('RewardsPaused');
+        }
+        await program.methods
+            .unpauseRewards()
+            .accounts({ economics: vestingEconomics, authority: vestingAuthority.publicKey })
+            .signers([vestingAuthority])
+            .rpc();
 
-    const program = anchor.workspace.NexusEconomics as Program<NexusEconomics>;
-    
-    let economics: anchor.web3.PublicKey;
-    let feeAuthority: anchor.web3.Keypair;
-    let tokenMint: anchor.web3.PublicKey;
+        // After the full period the remainder is released
+        await new Promise((resolve) => setTimeout(resolve, 7_000));
+        await release();
+        const done = await program.account.lockAccount.fetch(vestingLock);
+        expect(done.vestingReleased.toString()).to.equal(claimed.vestingTotal.toString());
+        const paid = BigInt(await balance()) - BigInt(before);
+        expect(paid.toString()).to.equal(claimed.vestingTotal.toString());
 
-    before(async () => {
-        // Setup test accounts and mint
+        try {
+            await release();
+            expect.fail('nothing is left to release');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('NothingVested');
+        }
     });
 
-    it('Initializes economics', async () => {
-        const config = {
-            minLockDuration: new anchor.BN(7 * 24 * 60 * 60),
-            maxLockDuration: new anchor.BN(4 * 365 * 24 * 60 * 60),
-            rewardRate: new anchor.BN(10),
-            boostFactor: new anchor.BN(2),
-            minStake: new anchor.BN(1000 * 10^9),
+    it('Reverts a claim that pays less than the requested minimum', async () => {
+        // `slippageLock` on `slippageEconomics` started a day ago and accrues 1,000,000 a second
+        const claim = (minRewards: anchor.BN) => program.methods
+            .claimRewards(minRewards)
+            .accounts({
+                lock: slippageLock,
+                economics: slippageEconomics,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                lockTokenAccount: null,
+                voterProfile: null,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([rewardsAuthority])
+            .rpc();
+
+        const oneHour = new anchor.BN(3_600 * 1_000_000);
+        await claim(oneHour);
+        const claimed = await program.account.lockAccount.fetch(slippageLock);
+        expect(claimed.rewardsClaimed.gte(oneHour)).to.be.true;
+
+        // Straight after a claim only a few seconds have accrued
+        try {
+            await claim(oneHour);
+            expect.fail('a payout below the minimum should revert');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('RewardsBelowMinimum');
+        }
+        const after = await program.account.lockAccount.fetch(slippageLock);
+        expect(after.rewardsClaimed.toString()).to.equal(claimed.rewardsClaimed.toString());
+    });
+
+    it('Rejects an out of order reward schedule', async () => {
+        try {
+            await program.methods
+                .setRewardSchedule([
+                    { startTs: new anchor.BN(2_000), rateBps: 5_000 },
+                    { startTs: new anchor.BN(1_000), rateBps: 2_500 },
+                ])
+                .accounts({ economics: economics, authority: provider.wallet.publicKey })
+                .rpc();
+            expect.fail('checkpoints must be increasing');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidRewardSchedule');
+        }
+    });
+
+    it('Splits accrual across a rate change', async () => {
+        // `scheduledLock` started 100 days ago on `scheduledEconomics`, with fees such that
+        // one full-rate day accrues `dailyAccrual`. Halve emissions from day 50 onwards.
+        const lockAccount = await program.account.lockAccount.fetch(scheduledLock);
+        const halving = lockAccount.startTime.addn(50 * 24 * 60 * 60);
+
+        await program.methods
+            .setRewardSchedule([{ startTs: halving, rateBps: 5_000 }])
+            .accounts({ economics: scheduledEconomics, authority: provider.wallet.publicKey })
+            .rpc();
+
+        await program.methods
+            .claimRewards(null)
+            .accounts({
+                lock: scheduledLock,
+                economics: scheduledEconomics,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                lockTokenAccount: null,
+                voterProfile: null,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([rewardsAuthority])
+            .rpc();
+
+        // 50 days at the full rate plus ~50 days at half rate
+        const claimed = await program.account.lockAccount.fetch(scheduledLock);
+        const expected = dailyAccrual * 50 + (dailyAccrual * 50) / 2;
+        expect(claimed.rewardsClaimed.toNumber()).to.be.closeTo(expected, dailyAccrual);
+    });
+
+    it('Refuses to boost a lock with another voter\'s profile', async () => {
+        // `boostEconomics` trusts the governance program and has collected fees; `idleLock`
+        // belongs to the wallet, which has never voted in `boostGovernance`
+        const boostBps = 1_000;
+        const governanceProgramId = anchor.workspace.NexusGovernance.programId;
+        const boostGovernance = anchor.web3.Keypair.generate().publicKey;
+        const { economics: boostEconomics, authority: boostAuthority } = await createEconomics({});
+        await program.methods
+            .setGovernanceProgram(governanceProgramId, boostGovernance)
+            .accounts({ economics: boostEconomics, authority: boostAuthority.publicKey })
+            .signers([boostAuthority])
+            .rpc();
+        await program.methods
+            .setParticipationBoost({
+                governance: boostGovernance,
+                minVotes: new anchor.BN(2),
+                recency: new anchor.BN(30 * 24 * 60 * 60),
+                boostBps,
+            })
+            .accounts({ economics: boostEconomics, authority: boostAuthority.publicKey })
+            .signers([boostAuthority])
+            .rpc();
+        await collectFee(boostEconomics);
+        const idleLock = await lockIn(boostEconomics, new anchor.BN(1_000_000_000), new anchor.BN(365 * 24 * 60 * 60));
+
+        const [votingProfile] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from('voter'), boostGovernance.toBuffer(), anchor.web3.Keypair.generate().publicKey.toBuffer()],
+            governanceProgramId
+        );
+        const claim = (voterProfile: anchor.web3.PublicKey | null) => program.methods
+            .claimRewards(null)
+            .accounts({
+                lock: idleLock,
+                economics: boostEconomics,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                lockTokenAccount: null,
+                voterProfile: voterProfile,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([rewardsAuthority])
+            .rpc();
+
+        // Someone else's profile cannot be borrowed for the boost
+        try {
+            await claim(votingProfile);
+            expect.fail("another owner's voter profile should be rejected");
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidVoterProfile');
+        }
+
+        // Without a profile the lock claims its plain accrual
+        await claim(null);
+        const idleAccount = await program.account.lockAccount.fetch(idleLock);
+        expect(idleAccount.lastClaimTs.toNumber()).to.be.above(0);
+    });
+
+    it('Accrues nothing while rewards are paused', async () => {
+        // A fresh economics state has no schedule and no emission cap; once it has collected
+        // a fee, a lock of 31,536,000,000,000 accrues 1,000,000 a second
+        const perSecond = 1_000_000;
+        const { economics: pausableEconomics, authority: pausableAuthority } = await createEconomics({});
+        await collectFee(pausableEconomics);
+        const pausableLock = await lockIn(pausableEconomics, new anchor.BN('31536000000000'), new anchor.BN(365 * 24 * 60 * 60));
+        const claim = () => program.methods
+            .claimRewards(null)
+            .accounts({
+                lock: pausableLock,
+                economics: pausableEconomics,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                lockTokenAccount: null,
+                voterProfile: null,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([rewardsAuthority])
+            .rpc();
+        const setPaused = (paused: boolean) => {
+            const method = paused ? program.methods.pauseRewards() : program.methods.unpauseRewards();
+            return method
+                .accounts({ economics: pausableEconomics, authority: pausableAuthority.publicKey })
+                .signers([pausableAuthority])
+                .rpc();
         };
 
+        await claim();
+        const beforePause = await program.account.lockAccount.fetch(pausableLock);
+
+        await setPaused(true);
+        try {
+            await claim();
+            expect.fail('claims should be rejected while paused');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('RewardsPaused');
+        }
+        try {
+            await setPaused(true);
+            expect.fail('pausing twice should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('RewardsPaused');
+        }
+
+        await new Promise((resolve) => setTimeout(resolve, 5_000));
+        await setPaused(false);
+        await claim();
+
+        // Only the time between the claims outside the pause window accrued
+        const afterPause = await program.account.lockAccount.fetch(pausableLock);
+        const state = await program.account.economicsState.fetch(pausableEconomics);
+        const window = state.pauseWindows[0];
+        const pausedFor = window.endTs.toNumber() - window.startTs.toNumber();
+        const activeFor = afterPause.lastClaimTs.toNumber() - beforePause.lastClaimTs.toNumber() - pausedFor;
+        expect(pausedFor).to.be.at.least(5);
+        expect(afterPause.rewardsClaimed.sub(beforePause.rewardsClaimed).toNumber())
+            .to.be.closeTo(activeFor * perSecond, 1);
+
+        try {
+            await setPaused(false);
+            expect.fail('unpausing while not paused should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('RewardsNotPaused');
+        }
+    });
+
+    it('Holds back relocking until the cooldown after a claim', async () => {
+        // `relockEconomics` has a 2 second relock cooldown; `relockLock` has accrued rewards
         await program.methods
-            .initializeEconomics(config)
+            .claimRewards(null)
+            .accounts({
+                lock: relockLock,
+                economics: relockEconomics,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                lockTokenAccount: null,
+                voterProfile: null,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .signers([rewardsAuthority])
+            .rpc();
+
+        const claimed = await program.account.lockAccount.fetch(relockLock);
+        expect(claimed.lastClaimTs.toNumber()).to.be.above(0);
+
+        const extend = () => program.methods
+            .extendLock(claimed.rewardsClaimed, new anchor.BN(0))
+            .accounts({
+                lock: relockLock,
+                economics: relockEconomics,
+                owner: provider.wallet.publicKey,
+                ownerTokenAccount: ownerTokenAccount,
+                lockTokenAccount: custodyOf(relockLock),
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .rpc();
+
+        try {
+            await extend();
+            expect.fail('relock inside the cooldown should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('RelockCooldown');
+        }
+
+        await new Promise((resolve) => setTimeout(resolve, 3_000));
+        await extend();
+
+        const relocked = await program.account.lockAccount.fetch(relockLock);
+        expect(relocked.amount.toString()).to.equal(claimed.amount.add(claimed.rewardsClaimed).toString());
+    });
+
+    it('Compounds claimed rewards into the lock when auto-stake is on', async () => {
+        // `autoStakeLock` on `economics` has accrued unclaimed rewards
+        await program.methods
+            .setAutoStake(true)
+            .accounts({ lock: autoStakeLock, owner: provider.wallet.publicKey })
+            .rpc();
+
+        const before = await program.account.lockAccount.fetch(autoStakeLock);
+        const walletBefore = await provider.connection.getTokenAccountBalance(ownerTokenAccount);
+        const custodyBefore = await provider.connection.getTokenAccountBalance(custodyOf(autoStakeLock));
+
+        await program.methods
+            .claimRewards(null)
             .accounts({
+                lock: autoStakeLock,
                 economics: economics,
-                authority: provider.wallet.publicKey,
-                systemProgram: anchor.web3.SystemProgram.programId,
+                ownerTokenAccount: ownerTokenAccount,
+                rewardsTreasury: rewardsTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                lockTokenAccount: custodyOf(autoStakeLock),
+                voterProfile: null,
+                tokenProgram: anchor.web3.TokenProgram.programId,
             })
+            .signers([rewardsAuthority])
             .rpc();
 
-        const state = await program.account.economicsState.fetch(economics);
-        expect(state.config.minStake.toNumber()).to.equal(config.minStake.toNumber());
+        const after = await program.account.lockAccount.fetch(autoStakeLock);
+        const walletAfter = await provider.connection.getTokenAccountBalance(ownerTokenAccount);
+        const custodyAfter = await provider.connection.getTokenAccountBalance(custodyOf(autoStakeLock));
+        const staked = Number(custodyAfter.value.amount) - Number(custodyBefore.value.amount);
+
+        // The voting stake grows by the claimed rewards instead of the wallet balance
+        expect(staked).to.be.above(0);
+        expect(after.amount.sub(before.amount).toNumber()).to.equal(staked);
+        expect(walletAfter.value.amount).to.equal(walletBefore.value.amount);
     });
 
-    it('Processes fees', async () => {
-        const amount = new anchor.BN(100 * 10^9);
-        const feeType = { stream: {} };
+    it('Splits a lock and claims from both halves', async () => {
+        // `splittableLock` on `economics` has claimed part of its accrued rewards
+        const original = await program.account.lockAccount.fetch(splittableLock);
+        const half = original.amount.divn(2);
+        const newLock = anchor.web3.Keypair.generate();
+
+        try {
+            await program.methods
+                .splitLock(original.amount)
+                .accounts({
+                    lock: splittableLock,
+                    newLock: newLock.publicKey,
+                    economics: economics,
+                    tokenMint: tokenMint,
+                    custodyAuthority: custodyAuthorityOf(economics),
+                    lockTokenAccount: custodyOf(splittableLock),
+                    newLockTokenAccount: custodyOf(newLock.publicKey),
+                    owner: provider.wallet.publicKey,
+                    tokenProgram: anchor.web3.TokenProgram.programId,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([newLock])
+                .rpc();
+            expect.fail('splitting off the whole lock should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidSplitAmount');
+        }
 
         await program.methods
-            .processFee(amount, feeType)
+            .splitLock(half)
             .accounts({
+                lock: splittableLock,
+                newLock: newLock.publicKey,
                 economics: economics,
-                feeAccount: feeAccount,
-                venexusTreasury: venexusTreasury,
-                ainexusTreasury: ainexusTreasury,
-                protocolTreasury: protocolTreasury,
                 tokenMint: tokenMint,
-                feeAuthority: feeAuthority.publicKey,
+                custodyAuthority: custodyAuthorityOf(economics),
+                lockTokenAccount: custodyOf(splittableLock),
+                newLockTokenAccount: custodyOf(newLock.publicKey),
+                owner: provider.wallet.publicKey,
                 tokenProgram: anchor.web3.TokenProgram.programId,
+                systemProgram: anchor.web3.SystemProgram.programId,
             })
-            .signers([feeAuthority])
+            .signers([newLock])
             .rpc();
 
-        const state = await program.account.economicsState.fetch(economics);
-        expect(state.totalFeesCollected.toNumber()).to.equal(amount.toNumber());
+        const kept = await program.account.lockAccount.fetch(splittableLock);
+        const split = await program.account.lockAccount.fetch(newLock.publicKey);
+        expect(kept.amount.add(split.amount).toString()).to.equal(original.amount.toString());
+        expect(kept.rewardsClaimed.add(split.rewardsClaimed).toString()).to.equal(original.rewardsClaimed.toString());
+        expect(split.startTime.toString()).to.equal(original.startTime.toString());
+        expect(split.endTime.toString()).to.equal(original.endTime.toString());
+
+        for (const part of [splittableLock, newLock.publicKey]) {
+            await program.methods
+                .claimRewards(null)
+                .accounts({
+                    lock: part,
+                    economics: economics,
+                    ownerTokenAccount: ownerTokenAccount,
+                    rewardsTreasury: rewardsTreasury,
+                    rewardsAuthority: rewardsAuthority.publicKey,
+                    lockTokenAccount: null,
+                    voterProfile: null,
+                    tokenProgram: anchor.web3.TokenProgram.programId,
+                })
+                .signers([rewardsAuthority])
+                .rpc();
+        }
+
+        const keptAfter = await program.account.lockAccount.fetch(splittableLock);
+        const splitAfter = await program.account.lockAccount.fetch(newLock.publicKey);
+        expect(keptAfter.rewardsClaimed.gt(kept.rewardsClaimed)).to.be.true;
+        expect(splitAfter.rewardsClaimed.gt(split.rewardsClaimed)).to.be.true;
     });
 
-    it('Creates lock', async () => {
-        const amount = new anchor.BN(1000 * 10^9);
-        const duration = new anchor.BN(365 * 24 * 60 * 60);
+    it('Merges two locks of different durations', async () => {
+        // `shortLock` (30 days) and `longLock` (1 year) belong to the provider wallet
+        const short = await program.account.lockAccount.fetch(shortLock);
+        const long = await program.account.lockAccount.fetch(longLock);
+        const ownerBalance = await provider.connection.getBalance(provider.wallet.publicKey);
 
         await program.methods
-            .createLock(amount, duration)
+            .mergeLocks()
+            .accounts({
+                lock: shortLock,
+                absorbed: longLock,
+                economics: economics,
+                custodyAuthority: custodyAuthorityOf(economics),
+                lockTokenAccount: custodyOf(shortLock),
+                absorbedTokenAccount: custodyOf(longLock),
+                owner: provider.wallet.publicKey,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .rpc();
+
+        const merged = await program.account.lockAccount.fetch(shortLock);
+        expect(merged.amount.toString()).to.equal(short.amount.add(long.amount).toString());
+        expect(merged.endTime.toString()).to.equal(long.endTime.toString());
+        expect(merged.startTime.toNumber()).to.equal(Math.min(short.startTime.toNumber(), long.startTime.toNumber()));
+
+        // The absorbed lock is closed and its rent returned to the owner
+        expect(await provider.connection.getAccountInfo(longLock)).to.be.null;
+        expect(await provider.connection.getBalance(provider.wallet.publicKey)).to.be.above(ownerBalance);
+    });
+
+    it('Only pays lock principal into approved service fee accounts', async () => {
+        const payFromLock = () => program.methods
+            .payFromLock(new anchor.BN(1_000))
             .accounts({
                 lock: lock.publicKey,
+                economics: economics,
+                owner: provider.wallet.publicKey,
+                custodyAuthority: custodyAuthorityOf(economics),
+                lockTokenAccount: custodyOf(lock.publicKey),
+                destination: ownerTokenAccount,
+                tokenProgram: anchor.web3.TokenProgram.programId,
+            })
+            .rpc();
+
+        try {
+            await payFromLock();
+            expect.fail('paying into an unapproved account should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('PaymentAccountNotApproved');
+        }
+
+        await program.methods
+            .addPaymentAccount(ownerTokenAccount)
+            .accounts({ economics: economics, authority: provider.wallet.publicKey })
+            .rpc();
+
+        const before = await program.account.lockAccount.fetch(lock.publicKey);
+        await payFromLock();
+        const after = await program.account.lockAccount.fetch(lock.publicKey);
+        expect(before.amount.sub(after.amount).toNumber()).to.equal(1_000);
+
+        await program.methods
+            .removePaymentAccount(ownerTokenAccount)
+            .accounts({ economics: economics, authority: provider.wallet.publicKey })
+            .rpc();
+        const state = await program.account.economicsState.fetch(economics);
+        expect(state.paymentAccounts.map((key) => key.toBase58())).to.not.include(ownerTokenAccount.toBase58());
+    });
+
+    it('Refuses to migrate an account that is not a lock', async () => {
+        // A stand-in for the custody account locks shared before v6
+        const legacyCustody = await createAccount(
+            provider.connection,
+            await provider.wallet.payer,
+            tokenMint,
+            treasuryAuthorityOf(economics),
+            anchor.web3.Keypair.generate()
+        );
+        try {
+            await program.methods
+                .migrateLock(new anchor.BN(0))
+                .accounts({
+                    economics: economics,
+                    lock: economics,
+                    tokenMint: tokenMint,
+                    treasuryAuthority: treasuryAuthorityOf(economics),
+                    custodyAuthority: custodyAuthorityOf(economics),
+                    legacyCustody: legacyCustody,
+                    lockTokenAccount: custodyOf(economics),
+                    authority: provider.wallet.publicKey,
+                    tokenProgram: anchor.web3.TokenProgram.programId,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .rpc();
+            expect.fail('economics state should not pass as a lock');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidLockAccount');
+        }
+    });
+
+    it('Refuses to sweep a lock inside the grace period', async () => {
+        try {
+            await program.methods
+                .sweepUnclaimed()
+                .accounts({
+                    economics: economics,
+                    lock: lock.publicKey,
+                    owner: provider.wallet.publicKey,
+                    ownerTokenAccount: ownerTokenAccount,
+                    custodyAuthority: custodyAuthorityOf(economics),
+                    lockTokenAccount: custodyOf(lock.publicKey),
+                    rewardsTreasury: rewardsTreasury,
+                    protocolTreasury: protocolTreasury,
+                    rewardsAuthority: rewardsAuthority.publicKey,
+                    authority: provider.wallet.publicKey,
+                    tokenProgram: anchor.web3.TokenProgram.programId,
+                })
+                .signers([rewardsAuthority])
+                .rpc();
+            expect.fail('active lock should not be swept');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('SweepTooEarly');
+        }
+    });
+
+    it('Sweeps unclaimed rewards from a lock past the grace period', async () => {
+        // `expiredLock` ended more than 90 days before the test validator clock
+        const before = await provider.connection.getTokenAccountBalance(protocolTreasury);
+        const walletBefore = await provider.connection.getTokenAccountBalance(ownerTokenAccount);
+        const principal = await provider.connection.getTokenAccountBalance(custodyOf(expiredLock));
+
+        await program.methods
+            .sweepUnclaimed()
+            .accounts({
+                economics: economics,
+                lock: expiredLock,
                 owner: provider.wallet.publicKey,
                 ownerTokenAccount: ownerTokenAccount,
-                lockTokenAccount: lockTokenAccount,
+                custodyAuthority: custodyAuthorityOf(economics),
+                lockTokenAccount: custodyOf(expiredLock),
+                rewardsTreasury: rewardsTreasury,
+                protocolTreasury: protocolTreasury,
+                rewardsAuthority: rewardsAuthority.publicKey,
+                authority: provider.wallet.publicKey,
                 tokenProgram: anchor.web3.TokenProgram.programId,
-                systemProgram: anchor.web3.SystemProgram.programId,
             })
-            .signers([lock])
+            .signers([rewardsAuthority])
             .rpc();
 
-        const lockAccount = await program.account.lockAccount.fetch(lock.publicKey);
-        expect(lockAccount.amount.toNumber()).to.equal(amount.toNumber());
+        const after = await provider.connection.getTokenAccountBalance(protocolTreasury);
+        expect(Number(after.value.amount)).to.be.at.least(Number(before.value.amount));
+        expect(await provider.connection.getAccountInfo(expiredLock)).to.be.null;
+
+        // The principal goes back to the owner and the custody is closed
+        const walletAfter = await provider.connection.getTokenAccountBalance(ownerTokenAccount);
+        expect(Number(walletAfter.value.amount) - Number(walletBefore.value.amount))
+            .to.equal(Number(principal.value.amount));
+        expect(await provider.connection.getAccountInfo(custodyOf(expiredLock))).to.be.null;
     });
 });
 
@@ -404,22 +3862,33 @@ async function main() {
         rewardRate: new anchor.BN(10),
         boostFactor: new anchor.BN(2),
         minStake: new anchor.BN(1000 * 10^9),
+        maxRewardsPerPeriod: new anchor.BN(0),
+        rewardPeriod: new anchor.BN(24 * 60 * 60),
+        relockCooldown: new anchor.BN(24 * 60 * 60),
+        minFeeAmount: new anchor.BN(1_000),
+        maxLockAmount: new anchor.BN(0),
+        minClaimDelay: new anchor.BN(60 * 60),
+        rewardVestingPeriod: new anchor.BN(0),
     };
 
-    const economics = anchor.web3.Keypair.generate();
+    const [economics] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("economics"), provider.wallet.publicKey.toBuffer()],
+        program.programId
+    );
+    const tokenMint = new anchor.web3.PublicKey(process.env.NEXUS_TOKEN_MINT);
 
     try {
         const tx = await program.methods
             .initializeEconomics(config)
             .accounts({
-                economics: economics.publicKey,
+                economics: economics,
+                tokenMint: tokenMint,
                 authority: provider.wallet.publicKey,
                 systemProgram: anchor.web3.SystemProgram.programId,
             })
-            .signers([economics])
             .rpc();
 
-        console.log("Economics system deployed at:", economics.publicKey.toString());
+        console.log("Economics system deployed at:", economics.toString());
         console.log("Transaction signature:", tx);
     } catch (error) {
         console.error("Deployment failed:", error);
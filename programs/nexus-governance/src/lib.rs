@@ -2,6 +2,9 @@
 
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount};
+use nexus_economics::program::NexusEconomics;
+use nexus_common::{apply_bps, integer_sqrt, is_arweave_tx_id, mul_div, Rounding, BPS_DENOMINATOR};
+use nexus_economics::{EconomicsState, FeeType};
 
 declare_id!("NEXUSGOVxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
@@ -16,35 +19,73 @@ pub mod nexus_governance {
     const MAX_VOTING_DELAY: i64 = 5 * 24 * 60 * 60;  // 5 days
     const MIN_QUORUM: u8 = 4;  // 4%
     const MAX_QUORUM: u8 = 75; // 75%
+    const MAX_MANUAL_EXTENSION: i64 = 3 * 24 * 60 * 60;     // 3 days per extend_voting call
+    const MAX_TOTAL_VOTING_PERIOD: i64 = 14 * 24 * 60 * 60; // 14 days including extensions
+    const MAX_EXECUTION_DELAY: i64 = 30 * 24 * 60 * 60;     // 30 days of timelock for any type
+    const MAX_LOCK_DURATION_BOOST_BPS: u16 = 10_000;        // a full-length lock counts at most double
 
     pub fn create_governance(
         ctx: Context<CreateGovernance>,
         config: GovernanceConfig,
+        emergency_council: Vec<Pubkey>,
+        realm_id: u64,
     ) -> Result<()> {
         let governance = &mut ctx.accounts.governance;
-        
-        // Validate configuration
-        require!(
-            config.voting_period >= MIN_VOTING_PERIOD 
-            && config.voting_period <= MAX_VOTING_PERIOD,
-            GovernanceError::InvalidVotingPeriod
-        );
 
-        require!(
-            config.voting_delay >= MIN_VOTING_DELAY 
-            && config.voting_delay <= MAX_VOTING_DELAY,
-            GovernanceError::InvalidVotingDelay
-        );
+        validate_config(&config)?;
 
         require!(
-            config.quorum_percentage >= MIN_QUORUM 
-            && config.quorum_percentage <= MAX_QUORUM,
-            GovernanceError::InvalidQuorum
+            emergency_council.len() <= MAX_EMERGENCY_COUNCIL,
+            GovernanceError::EmergencyCouncilFull
         );
+        for (i, member) in emergency_council.iter().enumerate() {
+            require!(
+                !emergency_council[..i].contains(member),
+                GovernanceError::AlreadyCouncilMember
+            );
+        }
 
-        governance.config = config;
+        governance.authority = ctx.accounts.authority.key();
+        governance.token_mint = ctx.accounts.token_mint.key();
+        governance.config = config.clone();
         governance.proposal_count = 0;
+        governance.type_proposal_counts = [0; PROPOSAL_TYPE_SLOTS];
         governance.total_locked_tokens = 0;
+        governance.emergency_council = emergency_council;
+        governance.realm_id = realm_id;
+        governance.bump = ctx.bumps.governance;
+        governance.economics = ctx.accounts.economics.key();
+        governance.venexus_treasury = ctx.accounts.venexus_treasury.key();
+        governance.ainexus_treasury = ctx.accounts.ainexus_treasury.key();
+        governance.protocol_treasury = ctx.accounts.protocol_treasury.key();
+
+        emit!(ConfigChanged {
+            governance: governance.key(),
+            authority: ctx.accounts.authority.key(),
+            old_config: None,
+            new_config: config,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_governance_config(
+        ctx: Context<UpdateGovernanceConfig>,
+        config: GovernanceConfig,
+    ) -> Result<()> {
+        let governance = &mut ctx.accounts.governance;
+
+        validate_config(&config)?;
+
+        let old_config = governance.config.clone();
+        governance.config = config.clone();
+
+        emit!(ConfigChanged {
+            governance: governance.key(),
+            authority: ctx.accounts.authority.key(),
+            old_config: Some(old_config),
+            new_config: config,
+        });
 
         Ok(())
     }
@@ -55,6 +96,9 @@ pub mod nexus_governance {
         title: String,
         description: String,
         link: String,
+        quorum_override: Option<u8>,
+        description_hash: Option<[u8; 32]>,
+        action_hash: Option<[u8; 32]>,
     ) -> Result<()> {
         let governance = &mut ctx.accounts.governance;
         let proposal = &mut ctx.accounts.proposal;
@@ -65,6 +109,9 @@ pub mod nexus_governance {
             ProposalType::Core => 100_000,
             ProposalType::Technical => 50_000,
             ProposalType::Operational => 10_000,
+            ProposalType::Custom(index) => {
+                governance.config.custom_proposal_type(index)?.required_tokens
+            }
         };
 
         let proposer_tokens = ctx.accounts.proposer_token_account.amount;
@@ -73,7 +120,97 @@ pub mod nexus_governance {
             GovernanceError::InsufficientTokens
         );
 
+        // String fields must fit the space reserved by Proposal::LEN
+        require!(title.len() <= MAX_TITLE_LEN, GovernanceError::TitleTooLong);
+        require!(description.len() <= MAX_DESCRIPTION_LEN, GovernanceError::DescriptionTooLong);
+        require!(link.len() <= MAX_LINK_LEN, GovernanceError::LinkTooLong);
+        require!(
+            link.is_empty() || governance.config.link_allowed(&link),
+            GovernanceError::LinkNotAllowed
+        );
+
+        // Off-chain descriptions are committed to by hash and located via `link`
+        match description_hash {
+            Some(hash) => {
+                require!(hash != [0u8; 32], GovernanceError::InvalidDescriptionHash);
+                require!(!link.is_empty(), GovernanceError::InvalidDescriptionHash);
+            }
+            None => require!(
+                description.len() >= governance.config.min_description_len as usize,
+                GovernanceError::DescriptionTooShort
+            ),
+        }
+        // Programs enacting the proposal match this against the action they perform
+        if let Some(hash) = action_hash {
+            require!(hash != [0u8; 32], GovernanceError::InvalidActionHash);
+        }
+
+        // A sensitive proposal may ask for a higher quorum, never below the configured one
+        let base_quorum = governance.config.type_quorums
+            .get(&proposal_type)
+            .unwrap_or(governance.config.quorum_percentage);
+        let quorum = match quorum_override {
+            Some(quorum) => {
+                require!(
+                    quorum >= base_quorum && quorum <= MAX_QUORUM,
+                    GovernanceError::InvalidQuorum
+                );
+                quorum
+            }
+            None => base_quorum,
+        };
+
+        // Enforce the per-proposer cooldown
+        let proposer_record = &mut ctx.accounts.proposer_record;
+        if proposer_record.last_proposal_ts != 0 {
+            require!(
+                clock.unix_timestamp - proposer_record.last_proposal_ts >= governance.config.proposal_cooldown,
+                GovernanceError::ProposalCooldown
+            );
+        }
+        proposer_record.proposer = ctx.accounts.proposer.key();
+        proposer_record.last_proposal_ts = clock.unix_timestamp;
+
+        // Escrow the anti-spam deposit until the proposal is settled
+        let deposit = governance.config.type_deposits
+            .get(&proposal_type)
+            .unwrap_or(governance.config.proposal_deposit);
+        // Optionally hold the threshold tokens too until voting closes, so the
+        // balance that qualified the proposal cannot then swing its vote
+        let threshold_locked = if governance.config.lock_proposer_threshold { required_tokens } else { 0 };
+        if threshold_locked > 0 {
+            require!(
+                proposer_tokens >= threshold_locked.saturating_add(deposit),
+                GovernanceError::InsufficientTokens
+            );
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.proposer_token_account.to_account_info(),
+                        to: ctx.accounts.deposit_vault.to_account_info(),
+                        authority: ctx.accounts.proposer.to_account_info(),
+                    },
+                ),
+                threshold_locked,
+            )?;
+        }
+        if deposit > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.proposer_token_account.to_account_info(),
+                        to: ctx.accounts.deposit_vault.to_account_info(),
+                        authority: ctx.accounts.proposer.to_account_info(),
+                    },
+                ),
+                deposit,
+            )?;
+        }
+
         proposal.proposal_id = governance.proposal_count;
+        proposal.governance = governance.key();
         proposal.proposer = ctx.accounts.proposer.key();
         proposal.proposal_type = proposal_type;
         proposal.title = title;
@@ -88,8 +225,21 @@ pub mod nexus_governance {
         proposal.no_votes = 0;
         proposal.veto_votes = 0;
         proposal.abstain_votes = 0;
-        proposal.quorum = governance.config.quorum_percentage;
+        proposal.quorum = quorum;
+        proposal.quorum_floor = governance.config.quorum_floor;
+        proposal.deposit = deposit;
+        proposal.deposit_settled = false;
+        proposal.total_extension = 0;
+        proposal.description_hash = description_hash.unwrap_or_default();
+        proposal.eta = 0;
+        proposal.voter_count = 0;
+        proposal.threshold_locked = threshold_locked;
+        proposal.action_hash = action_hash.unwrap_or_default();
 
+        // `proposal_id` stays the global sequence; `type_index` numbers proposals within their type
+        let slot = proposal.proposal_type.counter_slot();
+        proposal.type_index = governance.type_proposal_counts[slot];
+        governance.type_proposal_counts[slot] += 1;
         governance.proposal_count += 1;
 
         Ok(())
@@ -99,34 +249,223 @@ pub mod nexus_governance {
         ctx: Context<CastVote>,
         vote: Vote,
     ) -> Result<()> {
-        let proposal = &mut ctx.accounts.proposal;
-        let voter_weight = ctx.accounts.voter_token_account.amount;
-        let clock = Clock::get()?;
+        let raw_weight = ctx.accounts.voter_token_account.amount;
+        let supply = quorum_supply(&ctx.accounts.governance, &ctx.accounts.token_mint);
+        // Liquid tokens carry no lock, so they never earn the duration boost
+        let voter_weight = tally_vote(
+            &ctx.accounts.governance,
+            supply,
+            &mut ctx.accounts.proposal,
+            &vote,
+            raw_weight,
+            BPS_DENOMINATOR,
+        )?;
 
-        require!(
-            clock.unix_timestamp >= proposal.voting_starts_at,
-            GovernanceError::VotingNotStarted
-        );
+        // Record that this voter has voted
+        let vote_record = &mut ctx.accounts.vote_record;
+        vote_record.proposal = ctx.accounts.proposal.key();
+        vote_record.voter = ctx.accounts.voter.key();
+        vote_record.vote = vote;
+        vote_record.weight = voter_weight;
+        vote_record.raw_weight = raw_weight;
+        vote_record.lock_duration = 0;
+        vote_record.duration_factor_bps = BPS_DENOMINATOR as u16;
+
+        // The vote record's `init` already rules out a second vote on this
+        // proposal, so the profile only needs a single increment
+        let voter_profile = &mut ctx.accounts.voter_profile;
+        voter_profile.voter = ctx.accounts.voter.key();
+        voter_profile.proposals_voted += 1;
+        voter_profile.last_voted_at = Clock::get()?.unix_timestamp;
+        if raw_weight >= ctx.accounts.governance.config.voter_count_min_weight {
+            voter_profile.qualifying_votes += 1;
+        }
 
+        Ok(())
+    }
+
+    // Votes with the principal of an economics lock instead of a token
+    // account balance. The vote record seeds are shared with cast_vote, so
+    // a voter still gets one vote per proposal whichever path they use.
+    pub fn cast_vote_with_proof(
+        ctx: Context<CastVoteWithProof>,
+        vote: Vote,
+    ) -> Result<()> {
+        let lock = nexus_common::verify_lock_owner(
+            &ctx.accounts.lock.to_account_info(),
+            &nexus_economics::ID,
+            &ctx.accounts.voter.key(),
+        )?;
+        require_lock_in_realm(&ctx.accounts.governance, &lock)?;
+        let now = Clock::get()?.unix_timestamp;
         require!(
-            clock.unix_timestamp <= proposal.voting_ends_at,
-            GovernanceError::VotingEnded
+            lock.locked && lock.end_time > now,
+            GovernanceError::InactiveLock
         );
 
-        // Record vote
-        match vote {
-            Vote::Yes => proposal.yes_votes += voter_weight,
-            Vote::No => proposal.no_votes += voter_weight,
-            Vote::Veto => proposal.veto_votes += voter_weight,
-            Vote::Abstain => proposal.abstain_votes += voter_weight,
-        }
+        let raw_weight = lock.amount;
+        let supply = quorum_supply(&ctx.accounts.governance, &ctx.accounts.token_mint);
+        // Time left on the lock decides the boost, so an expiring lock counts
+        // little more than liquid tokens
+        let lock_duration = lock.end_time - now;
+        let factor_bps = duration_factor_bps(&ctx.accounts.governance.config, lock_duration);
+        let voter_weight = tally_vote(
+            &ctx.accounts.governance,
+            supply,
+            &mut ctx.accounts.proposal,
+            &vote,
+            raw_weight,
+            factor_bps,
+        )?;
 
-        // Record that this voter has voted
         let vote_record = &mut ctx.accounts.vote_record;
-        vote_record.proposal = proposal.key();
+        vote_record.proposal = ctx.accounts.proposal.key();
         vote_record.voter = ctx.accounts.voter.key();
         vote_record.vote = vote;
         vote_record.weight = voter_weight;
+        vote_record.raw_weight = raw_weight;
+        vote_record.lock_duration = lock_duration;
+        vote_record.duration_factor_bps = factor_bps as u16;
+
+        let voter_profile = &mut ctx.accounts.voter_profile;
+        voter_profile.voter = ctx.accounts.voter.key();
+        voter_profile.proposals_voted += 1;
+        voter_profile.last_voted_at = Clock::get()?.unix_timestamp;
+        if raw_weight >= ctx.accounts.governance.config.voter_count_min_weight {
+            voter_profile.qualifying_votes += 1;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_participation(ctx: Context<GetParticipation>) -> Result<u64> {
+        let voter_profile_info = ctx.accounts.voter_profile.to_account_info();
+
+        // A missing profile means the wallet has never voted
+        if voter_profile_info.data_is_empty() {
+            return Ok(0);
+        }
+
+        let voter_profile = Account::<VoterProfile>::try_from(&voter_profile_info)?;
+        Ok(voter_profile.proposals_voted)
+    }
+
+    pub fn configure_participation_rewards(
+        ctx: Context<ConfigureParticipationRewards>,
+        reward_per_vote: u64,
+        period_length: i64,
+        period_budget: u64,
+    ) -> Result<()> {
+        require!(
+            reward_per_vote > 0 && period_length > 0 && period_budget >= reward_per_vote,
+            GovernanceError::InvalidParticipationRewards
+        );
+
+        let rewards = &mut ctx.accounts.participation_rewards;
+        if rewards.governance == Pubkey::default() {
+            rewards.governance = ctx.accounts.governance.key();
+            rewards.period_start = Clock::get()?.unix_timestamp;
+            rewards.period_paid = 0;
+        }
+        rewards.reward_per_vote = reward_per_vote;
+        rewards.period_length = period_length;
+        rewards.period_budget = period_budget;
+        Ok(())
+    }
+
+    // Pays `reward_per_vote` for every vote cast since the voter's last claim
+    // that was heavy enough to count towards the voter count, so dust votes
+    // from split wallets earn nothing. Payouts across all voters are bounded by `period_budget` per period; a
+    // claim that exceeds what is left is paid in part and the rest carries over.
+    pub fn claim_participation_reward(ctx: Context<ClaimParticipationReward>) -> Result<()> {
+        let rewards = &mut ctx.accounts.participation_rewards;
+        let voter_profile = &mut ctx.accounts.voter_profile;
+        let now = Clock::get()?.unix_timestamp;
+
+        if now >= rewards.period_start + rewards.period_length {
+            let elapsed_periods = (now - rewards.period_start) / rewards.period_length;
+            rewards.period_start += elapsed_periods * rewards.period_length;
+            rewards.period_paid = 0;
+        }
+
+        let unrewarded = voter_profile.qualifying_votes.saturating_sub(voter_profile.rewarded_votes);
+        require!(unrewarded > 0, GovernanceError::NoParticipationToReward);
+
+        let remaining_budget = rewards.period_budget.saturating_sub(rewards.period_paid);
+        let votes = unrewarded.min(remaining_budget / rewards.reward_per_vote);
+        require!(votes > 0, GovernanceError::ParticipationBudgetExhausted);
+
+        let amount = votes
+            .checked_mul(rewards.reward_per_vote)
+            .ok_or(GovernanceError::Overflow)?;
+        rewards.period_paid += amount;
+        voter_profile.rewarded_votes += votes;
+
+        let governance_key = ctx.accounts.governance.key();
+        let seeds: &[&[u8]] = &[
+            b"participation",
+            governance_key.as_ref(),
+            &[ctx.bumps.participation_rewards],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.reward_vault.to_account_info(),
+                    to: ctx.accounts.voter_token_account.to_account_info(),
+                    authority: ctx.accounts.participation_rewards.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+
+        emit!(ParticipationRewardClaimed {
+            governance: governance_key,
+            voter: ctx.accounts.voter.key(),
+            votes,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn get_vote_receipt(ctx: Context<GetVoteReceipt>) -> Result<VoteReceipt> {
+        let vote_record_info = ctx.accounts.vote_record.to_account_info();
+
+        // A missing record means the wallet has not voted, not an error
+        if vote_record_info.data_is_empty() {
+            return Ok(VoteReceipt { voted: false, vote: None, weight: 0 });
+        }
+
+        let vote_record = Account::<VoteRecord>::try_from(&vote_record_info)?;
+        Ok(VoteReceipt {
+            voted: true,
+            vote: Some(vote_record.vote.clone()),
+            weight: vote_record.weight,
+        })
+    }
+
+    // Starts a passed proposal's timelock. `eta` depends on the proposal type,
+    // so high-impact changes wait longer before they can execute.
+    pub fn queue_proposal(ctx: Context<QueueProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let config = &ctx.accounts.governance.config;
+        let now = Clock::get()?.unix_timestamp;
+
+        check_passed(
+            proposal,
+            config,
+            quorum_supply(&ctx.accounts.governance, &ctx.accounts.token_mint),
+            now,
+        )?;
+        require!(proposal.eta == 0, GovernanceError::AlreadyQueued);
+
+        proposal.eta = now + config.execution_delays.get(&proposal.proposal_type);
+
+        emit!(ProposalQueued {
+            proposal: proposal.key(),
+            eta: proposal.eta,
+        });
 
         Ok(())
     }
@@ -135,274 +474,3729 @@ pub mod nexus_governance {
         let proposal = &mut ctx.accounts.proposal;
         let clock = Clock::get()?;
 
+        check_executable(
+            proposal,
+            &ctx.accounts.governance.config,
+            quorum_supply(&ctx.accounts.governance, &ctx.accounts.token_mint),
+            clock.unix_timestamp,
+        )?;
+
+        proposal.executed = true;
+
+        Ok(())
+    }
+
+    // Returns threshold tokens held by create_proposal once the proposal can
+    // no longer be voted on
+    pub fn release_threshold_lock(ctx: Context<ReleaseThresholdLock>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let clock = Clock::get()?;
+
+        require!(proposal.threshold_locked > 0, GovernanceError::NoThresholdLock);
+        require!(
+            proposal.cancelled || clock.unix_timestamp > proposal.voting_ends_at,
+            GovernanceError::VotingNotEnded
+        );
+
+        let amount = proposal.threshold_locked;
+        proposal.threshold_locked = 0;
+
+        let governance_key = ctx.accounts.governance.key();
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            governance_key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.deposit_vault.to_account_info(),
+                    to: ctx.accounts.proposer_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn settle_deposit(ctx: Context<SettleDeposit>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let clock = Clock::get()?;
+
         require!(
             clock.unix_timestamp > proposal.voting_ends_at,
             GovernanceError::VotingNotEnded
         );
+        require!(!proposal.deposit_settled, GovernanceError::DepositAlreadySettled);
 
-        require!(!proposal.executed, GovernanceError::AlreadyExecuted);
-        require!(!proposal.cancelled, GovernanceError::ProposalCancelled);
+        let deposit = proposal.deposit;
+        let forfeited = proposal.veto_votes > 0
+            || !quorum_reached(
+                proposal,
+                quorum_supply(&ctx.accounts.governance, &ctx.accounts.token_mint),
+            );
 
-        // Check quorum and vote outcome
-        let total_votes = proposal.yes_votes + proposal.no_votes + proposal.veto_votes + proposal.abstain_votes;
-        let quorum_threshold = (ctx.accounts.governance.total_locked_tokens * proposal.quorum as u64) / 100;
+        proposal.deposit_settled = true;
+
+        if deposit == 0 {
+            return Ok(());
+        }
+
+        let governance_key = ctx.accounts.governance.key();
+        let seeds: &[&[u8]] = &[
+            b"vault_authority",
+            governance_key.as_ref(),
+            &[ctx.bumps.vault_authority],
+        ];
+
+        // process_fee refuses amounts under its minimum and tokens it does not
+        // route, so those forfeitures go straight to the protocol treasury
+        // rather than stranding the deposit in the vault
+        let economics = &ctx.accounts.economics;
+        let fee_routable = deposit >= economics.config.min_fee_amount
+            && (economics.fee_tokens.is_empty()
+                || economics.fee_token(&ctx.accounts.token_mint.key()).map_or(false, |fee_token| {
+                    fee_token.venexus_treasury == ctx.accounts.venexus_treasury.key()
+                        && fee_token.ainexus_treasury == ctx.accounts.ainexus_treasury.key()
+                        && fee_token.protocol_treasury == ctx.accounts.protocol_treasury.key()
+                }));
+
+        if forfeited && fee_routable {
+            // Vetoed or quorum-less proposals feed their deposit into the fee distribution
+            nexus_economics::cpi::process_fee(
+                CpiContext::new_with_signer(
+                    ctx.accounts.economics_program.to_account_info(),
+                    nexus_economics::cpi::accounts::ProcessFee {
+                        economics: ctx.accounts.economics.to_account_info(),
+                        fee_account: ctx.accounts.deposit_vault.to_account_info(),
+                        venexus_treasury: ctx.accounts.venexus_treasury.to_account_info(),
+                        ainexus_treasury: ctx.accounts.ainexus_treasury.to_account_info(),
+                        protocol_treasury: ctx.accounts.protocol_treasury.to_account_info(),
+                        token_mint: ctx.accounts.token_mint.to_account_info(),
+                        fee_authority: ctx.accounts.vault_authority.to_account_info(),
+                        token_program: ctx.accounts.token_program.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                deposit,
+                FeeType::Custom,
+                None,
+                None,
+            )?;
+        } else if forfeited {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.deposit_vault.to_account_info(),
+                        to: ctx.accounts.protocol_treasury.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                deposit,
+            )?;
+        } else {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.deposit_vault.to_account_info(),
+                        to: ctx.accounts.proposer_token_account.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                deposit,
+            )?;
+        }
+
+        Ok(())
+    }
 
+    pub fn execute_proposals_batch<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ExecuteProposalsBatch<'info>>,
+    ) -> Result<()> {
+        let supply = quorum_supply(&ctx.accounts.governance, &ctx.accounts.token_mint);
+        let clock = Clock::get()?;
+        let mut executed = Vec::new();
         require!(
-            total_votes >= quorum_threshold,
-            GovernanceError::QuorumNotReached
+            ctx.remaining_accounts.len() <= MAX_EXECUTE_BATCH,
+            GovernanceError::BatchTooLarge
         );
 
-        // Check if proposal passed based on type
-        let passed = match proposal.proposal_type {
-            ProposalType::Core => {
-                proposal.yes_votes as f64 / total_votes as f64 >= 0.75 // 75% required
-            }
-            ProposalType::Technical => {
-                proposal.yes_votes as f64 / total_votes as f64 >= 0.66 // 66% required
-            }
-            ProposalType::Operational => {
-                proposal.yes_votes > proposal.no_votes // Simple majority
+        for account_info in ctx.remaining_accounts.iter() {
+            require!(account_info.is_writable, GovernanceError::AccountNotWritable);
+            let mut proposal = Account::<Proposal>::try_from(account_info)?;
+            require_keys_eq!(
+                proposal.governance,
+                ctx.accounts.governance.key(),
+                GovernanceError::RealmMismatch
+            );
+
+            // Skip proposals that are not yet eligible instead of failing the batch
+            if check_executable(&proposal, &ctx.accounts.governance.config, supply, clock.unix_timestamp).is_err() {
+                continue;
             }
-        };
 
-        require!(passed, GovernanceError::ProposalNotPassed);
-        require!(proposal.veto_votes == 0, GovernanceError::ProposalVetoed);
+            proposal.executed = true;
+            proposal.exit(&crate::ID)?;
+            executed.push(proposal.proposal_id);
+        }
 
-        proposal.executed = true;
+        emit!(ProposalsBatchExecuted {
+            governance: ctx.accounts.governance.key(),
+            executed,
+        });
 
         Ok(())
     }
 
-    pub fn emergency_action(ctx: Context<EmergencyAction>) -> Result<()> {
-        // Implement emergency action logic
+    // Keeper cleanup after a governance cycle. `remaining_accounts` holds
+    // (account, rent recipient) pairs, each account a proposal or a vote
+    // record. A vote record closes once its proposal is terminal and past its
+    // voting window in this batch, or already gone, so one proposal's votes
+    // can span several calls before the proposal itself is passed. Anything
+    // else is skipped.
+    pub fn close_terminal_accounts<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, CloseTerminalAccounts<'info>>,
+    ) -> Result<()> {
+        let accounts = ctx.remaining_accounts;
+        require!(accounts.len() % 2 == 0, GovernanceError::InvalidCloseBatch);
+        require!(accounts.len() <= MAX_CLOSE_BATCH * 2, GovernanceError::BatchTooLarge);
+
+        let governance = &ctx.accounts.governance;
+        let supply = quorum_supply(governance, &ctx.accounts.token_mint);
+        let now = Clock::get()?.unix_timestamp;
+
+        // Proposals whose vote records may be closed: terminal ones from this
+        // realm whose voting has ended, so a closed record can never be
+        // re-cast, and addresses that no longer hold an account
+        let mut settled: Vec<Pubkey> = Vec::new();
+        for pair in accounts.chunks(2) {
+            let info = &pair[0];
+            if info.data_is_empty() {
+                settled.push(info.key());
+            } else if let Ok(proposal) = Account::<Proposal>::try_from(info) {
+                if proposal.governance == governance.key()
+                    && now > proposal.voting_ends_at
+                    && is_terminal(&proposal, &governance.config, supply, now)
+                {
+                    settled.push(info.key());
+                }
+            }
+        }
+
+        let mut closed_proposals = Vec::new();
+        let mut closed_votes: u32 = 0;
+        // Vote records first, so a proposal closed below never strands them
+        for pair in accounts.chunks(2) {
+            let (info, recipient) = (&pair[0], &pair[1]);
+            if let Ok(vote_record) = Account::<VoteRecord>::try_from(info) {
+                if settled.contains(&vote_record.proposal) && recipient.key() == vote_record.voter {
+                    vote_record.close(recipient.clone())?;
+                    closed_votes += 1;
+                }
+            }
+        }
+        for pair in accounts.chunks(2) {
+            let (info, recipient) = (&pair[0], &pair[1]);
+            if !settled.contains(info.key) || info.data_is_empty() {
+                continue;
+            }
+            let proposal = Account::<Proposal>::try_from(info)?;
+            if recipient.key() == proposal.proposer {
+                closed_proposals.push(proposal.proposal_id);
+                proposal.close(recipient.clone())?;
+            }
+        }
+
+        emit!(TerminalAccountsClosed {
+            governance: governance.key(),
+            proposals: closed_proposals,
+            vote_records: closed_votes,
+        });
+
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct CreateGovernance<'info> {
-    #[account(init, payer = authority, space = 8 + size_of::<GovernanceState>())]
-    pub governance: Account<'info, GovernanceState>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    pub fn reconcile_locked<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ReconcileLocked<'info>>,
+        expected_count: u32,
+    ) -> Result<()> {
+        require!(
+            ctx.remaining_accounts.len() <= MAX_RECONCILE_LOCKS,
+            GovernanceError::BatchTooLarge
+        );
+        // The caller commits to the size of the set so a truncated list is rejected
+        require!(
+            ctx.remaining_accounts.len() == expected_count as usize,
+            GovernanceError::LockCountMismatch
+        );
 
-#[derive(Accounts)]
-pub struct CreateProposal<'info> {
-    #[account(mut)]
-    pub governance: Account<'info, GovernanceState>,
-    #[account(init, payer = proposer, space = 8 + size_of::<Proposal>())]
-    pub proposal: Account<'info, Proposal>,
-    #[account(mut)]
-    pub proposer: Signer<'info>,
-    pub proposer_token_account: Account<'info, TokenAccount>,
-    pub system_program: Program<'info, System>,
-}
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut total_locked: u64 = 0;
 
-#[derive(Accounts)]
-pub struct CastVote<'info> {
-    pub governance: Account<'info, GovernanceState>,
-    #[account(mut)]
-    pub proposal: Account<'info, Proposal>,
-    #[account(
-        init,
-        payer = voter,
-        space = 8 + size_of::<VoteRecord>(),
-        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
-        bump
-    )]
-    pub vote_record: Account<'info, VoteRecord>,
-    #[account(mut)]
-    pub voter: Signer<'info>,
-    pub voter_token_account: Account<'info, TokenAccount>,
-    pub system_program: Program<'info, System>,
-}
+        for account_info in ctx.remaining_accounts.iter() {
+            require!(!seen.contains(account_info.key), GovernanceError::DuplicateLock);
+            seen.push(account_info.key());
 
-#[derive(Accounts)]
-pub struct ExecuteProposal<'info> {
-    pub governance: Account<'info, GovernanceState>,
-    #[account(mut)]
-    pub proposal: Account<'info, Proposal>,
-    pub executor: Signer<'info>,
-}
+            let lock = nexus_common::load_lock(account_info, &nexus_economics::ID)?;
+            require_lock_in_realm(&ctx.accounts.governance, &lock)?;
+            if lock.locked {
+                total_locked = total_locked
+                    .checked_add(lock.amount)
+                    .ok_or(GovernanceError::Overflow)?;
+            }
+        }
 
-#[derive(Accounts)]
-pub struct EmergencyAction<'info> {
-    #[account(mut)]
-    pub governance: Account<'info, GovernanceState>,
-    pub emergency_council_member: Signer<'info>,
-}
+        ctx.accounts.governance.total_locked_tokens = total_locked;
 
-#[account]
-pub struct GovernanceState {
-    pub config: GovernanceConfig,
-    pub proposal_count: u64,
-    pub total_locked_tokens: u64,
-    pub emergency_council: Vec<Pubkey>,
-}
+        Ok(())
+    }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct GovernanceConfig {
-    pub voting_delay: i64,
-    pub voting_period: i64,
-    pub quorum_percentage: u8,
-    pub proposal_threshold: u64,
-    pub emergency_threshold: u8,
-}
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<HealthStatus> {
+        let governance = &ctx.accounts.governance;
 
-#[account]
-pub struct Proposal {
-    pub proposal_id: u64,
-    pub proposer: Pubkey,
-    pub proposal_type: ProposalType,
-    pub title: String,
-    pub description: String,
-    pub link: String,
-    pub created_at: i64,
-    pub voting_starts_at: i64,
-    pub voting_ends_at: i64,
-    pub executed: bool,
-    pub cancelled: bool,
-    pub yes_votes: u64,
-    pub no_votes: u64,
-    pub veto_votes: u64,
-    pub abstain_votes: u64,
-    pub quorum: u8,
-}
+        if validate_config(&governance.config).is_err() {
+            return Ok(HealthStatus::ConfigOutOfBounds);
+        }
+        if governance.emergency_council.len() > MAX_EMERGENCY_COUNCIL {
+            return Ok(HealthStatus::CouncilOverCapacity);
+        }
 
-#[account]
-pub struct VoteRecord {
-    pub proposal: Pubkey,
-    pub voter: Pubkey,
-    pub vote: Vote,
-    pub weight: u64,
-}
+        Ok(HealthStatus::Healthy)
+    }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
-pub enum ProposalType {
-    Core,        // 75% approval required
-    Technical,   // 66% approval required
-    Operational, // 51% approval required
+    fn validate_config(config: &GovernanceConfig) -> Result<()> {
+        require!(
+            config.voting_period >= MIN_VOTING_PERIOD 
+            && config.voting_period <= MAX_VOTING_PERIOD,
+            GovernanceError::InvalidVotingPeriod
+        );
+
+        require!(
+            config.voting_delay >= MIN_VOTING_DELAY 
+            && config.voting_delay <= MAX_VOTING_DELAY,
+            GovernanceError::InvalidVotingDelay
+        );
+
+        require!(
+            config.quorum_percentage >= MIN_QUORUM 
+            && config.quorum_percentage <= MAX_QUORUM,
+            GovernanceError::InvalidQuorum
+        );
+
+        require!(
+            config.emergency_threshold > 0 && config.emergency_threshold <= 100,
+            GovernanceError::InvalidEmergencyThreshold
+        );
+
+        require!(config.proposal_cooldown >= 0, GovernanceError::InvalidProposalCooldown);
+
+        require!(
+            config.custom_proposal_types.len() <= MAX_CUSTOM_PROPOSAL_TYPES,
+            GovernanceError::TooManyProposalTypes
+        );
+        require!(
+            config.vote_extension.window >= 0
+                && config.vote_extension.extension >= 0
+                && config.vote_extension.max_total_extension >= 0,
+            GovernanceError::InvalidVoteExtension
+        );
+
+        require!(
+            config.veto_window >= 0 && config.veto_window <= config.voting_period,
+            GovernanceError::InvalidVetoWindow
+        );
+
+        require!(
+            config.lock_duration_boost_bps <= MAX_LOCK_DURATION_BOOST_BPS,
+            GovernanceError::InvalidLockDurationBoost
+        );
+
+        let delays = &config.execution_delays;
+        for delay in [delays.core, delays.technical, delays.operational, delays.custom] {
+            require!(
+                delay >= 0 && delay <= MAX_EXECUTION_DELAY,
+                GovernanceError::InvalidExecutionDelay
+            );
+        }
+
+        for quorum in [
+            config.type_quorums.core,
+            config.type_quorums.technical,
+            config.type_quorums.operational,
+        ].into_iter().flatten() {
+            require!(
+                quorum >= MIN_QUORUM && quorum <= MAX_QUORUM,
+                GovernanceError::InvalidQuorum
+            );
+        }
+
+        require!(
+            config.allowed_link_prefixes.len() <= MAX_LINK_PREFIXES
+                && config.allowed_link_prefixes
+                    .iter()
+                    .all(|prefix| !prefix.is_empty() && prefix.len() <= MAX_LINK_PREFIX_LEN),
+            GovernanceError::InvalidLinkPrefix
+        );
+
+        require!(
+            config.min_description_len as usize <= MAX_DESCRIPTION_LEN,
+            GovernanceError::InvalidMinDescriptionLen
+        );
+
+        if let VoteWeightCap::Percentage(bps) = config.vote_weight_cap {
+            require!(bps > 0 && bps <= 10_000, GovernanceError::InvalidVoteWeightCap);
+        }
+
+        for custom in config.custom_proposal_types.iter() {
+            require!(
+                custom.approval_threshold > 50 && custom.approval_threshold <= 100,
+                GovernanceError::InvalidApprovalThreshold
+            );
+            require!(
+                custom.name.len() <= MAX_PROPOSAL_TYPE_NAME_LEN,
+                GovernanceError::InvalidProposalTypeName
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn emergency_action(ctx: Context<EmergencyAction>) -> Result<()> {
+        let governance = &ctx.accounts.governance;
+        let proposal = &mut ctx.accounts.proposal;
+        let approval = &mut ctx.accounts.emergency_approval;
+        let member = ctx.accounts.emergency_council_member.key();
+
+        require!(
+            governance.emergency_council.contains(&member),
+            GovernanceError::NotEmergencyCouncil
+        );
+        require!(
+            !proposal.executed && !proposal.cancelled,
+            GovernanceError::InvalidEmergencyAction
+        );
+        require!(!approval.approvals.contains(&member), GovernanceError::AlreadyApproved);
+
+        // Approvals from members removed since they approved no longer count.
+        // Pruning them also keeps the list within its allocated space.
+        approval.approvals.retain(|approver| governance.emergency_council.contains(approver));
+
+        approval.proposal = proposal.key();
+        approval.approvals.push(member);
+
+        // The action takes effect once emergency_threshold percent of the council approves
+        let approvals = approval.approvals.len() as u64;
+        let council_size = governance.emergency_council.len() as u64;
+        if approvals * 100 >= council_size * governance.config.emergency_threshold as u64 {
+            proposal.cancelled = true;
+        }
+
+        Ok(())
+    }
+
+    pub fn add_council_member(ctx: Context<UpdateCouncil>, member: Pubkey) -> Result<()> {
+        let council = &mut ctx.accounts.governance.emergency_council;
+
+        require!(!council.contains(&member), GovernanceError::AlreadyCouncilMember);
+        require!(
+            council.len() < MAX_EMERGENCY_COUNCIL,
+            GovernanceError::EmergencyCouncilFull
+        );
+
+        council.push(member);
+        Ok(())
+    }
+
+    pub fn remove_council_member(ctx: Context<UpdateCouncil>, member: Pubkey) -> Result<()> {
+        let council = &mut ctx.accounts.governance.emergency_council;
+
+        let index = council
+            .iter()
+            .position(|existing| *existing == member)
+            .ok_or(GovernanceError::NotEmergencyCouncil)?;
+        // Order-preserving removal, so the council never has gaps. Approvals are
+        // tracked by key rather than index and are pruned in emergency_action.
+        council.remove(index);
+        Ok(())
+    }
+
+    pub fn extend_voting(ctx: Context<ExtendVoting>, extension: i64) -> Result<()> {
+        let governance = &ctx.accounts.governance;
+        let proposal = &mut ctx.accounts.proposal;
+        let signer = ctx.accounts.signer.key();
+        let clock = Clock::get()?;
+
+        require!(
+            signer == governance.authority || governance.emergency_council.contains(&signer),
+            GovernanceError::NotEmergencyCouncil
+        );
+        require!(
+            !proposal.executed && !proposal.cancelled,
+            GovernanceError::InvalidEmergencyAction
+        );
+        // Voting that has already closed cannot be reopened
+        require!(
+            clock.unix_timestamp <= proposal.voting_ends_at,
+            GovernanceError::VotingEnded
+        );
+
+        let voting_ends_at = proposal.voting_ends_at + extension;
+        require!(
+            extension > 0
+                && extension <= MAX_MANUAL_EXTENSION
+                && voting_ends_at - proposal.voting_starts_at <= MAX_TOTAL_VOTING_PERIOD,
+            GovernanceError::ExtensionTooLong
+        );
+
+        proposal.voting_ends_at = voting_ends_at;
+        emit!(VotingPeriodExtended {
+            proposal: proposal.key(),
+            extended_by: signer,
+            extension,
+            voting_ends_at,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ExtendVoting<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(mut, has_one = governance @ GovernanceError::RealmMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(config: GovernanceConfig, emergency_council: Vec<Pubkey>, realm_id: u64)]
+pub struct CreateGovernance<'info> {
+    // `init` fails on an existing account, so a live governance can never be re-created
+    #[account(
+        init,
+        payer = authority,
+        space = GovernanceState::LEN,
+        seeds = [GOVERNANCE_SEED, authority.key().as_ref(), realm_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub governance: Account<'info, GovernanceState>,
+    pub token_mint: Account<'info, token::Mint>,
+    // Economics deployment whose locks may back votes in this realm
+    #[account(constraint = economics.token_mint == token_mint.key() @ GovernanceError::InvalidTokenMint)]
+    pub economics: Account<'info, EconomicsState>,
+    // Fee treasuries of that deployment, which receive forfeited deposits
+    #[account(token::mint = token_mint)]
+    pub venexus_treasury: Account<'info, TokenAccount>,
+    #[account(token::mint = token_mint)]
+    pub ainexus_treasury: Account<'info, TokenAccount>,
+    #[account(token::mint = token_mint)]
+    pub protocol_treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGovernanceConfig<'info> {
+    #[account(mut, has_one = authority)]
+    pub governance: Account<'info, GovernanceState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCouncil<'info> {
+    #[account(mut, has_one = authority)]
+    pub governance: Account<'info, GovernanceState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_type: ProposalType, title: String, description: String)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub governance: Account<'info, GovernanceState>,
+    // Sized to the actual description so hash-backed proposals stay small
+    #[account(init, payer = proposer, space = Proposal::space(description.len()))]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        init_if_needed,
+        payer = proposer,
+        space = ProposerRecord::LEN,
+        seeds = [b"proposer", governance.key().as_ref(), proposer.key().as_ref()],
+        bump
+    )]
+    pub proposer_record: Account<'info, ProposerRecord>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    // Pays the deposit and holds the threshold tokens, so it must be the proposer's own
+    #[account(mut, token::mint = governance.token_mint, token::authority = proposer)]
+    pub proposer_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns the deposit vault
+    #[account(seeds = [b"vault_authority", governance.key().as_ref()], bump)]
+    pub vault_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = vault_authority, token::mint = governance.token_mint)]
+    pub deposit_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(address = governance.token_mint @ GovernanceError::InvalidTokenMint)]
+    pub token_mint: Account<'info, token::Mint>,
+    #[account(mut, has_one = governance @ GovernanceError::RealmMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        init,
+        payer = voter,
+        space = VoteRecord::LEN,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = VoterProfile::LEN,
+        seeds = [b"voter", governance.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub voter_profile: Account<'info, VoterProfile>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    // Weight is the balance, so only the voter's own realm tokens count
+    #[account(token::mint = governance.token_mint, token::authority = voter)]
+    pub voter_token_account: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CastVoteWithProof<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(address = governance.token_mint @ GovernanceError::InvalidTokenMint)]
+    pub token_mint: Account<'info, token::Mint>,
+    #[account(mut, has_one = governance @ GovernanceError::RealmMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        init,
+        payer = voter,
+        space = VoteRecord::LEN,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = VoterProfile::LEN,
+        seeds = [b"voter", governance.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub voter_profile: Account<'info, VoterProfile>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    /// CHECK: economics lock owned by the voter, verified in the handler
+    pub lock: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureParticipationRewards<'info> {
+    #[account(has_one = authority)]
+    pub governance: Account<'info, GovernanceState>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = ParticipationRewards::LEN,
+        seeds = [b"participation", governance.key().as_ref()],
+        bump
+    )]
+    pub participation_rewards: Account<'info, ParticipationRewards>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimParticipationReward<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(mut, seeds = [b"participation", governance.key().as_ref()], bump)]
+    pub participation_rewards: Account<'info, ParticipationRewards>,
+    #[account(
+        mut,
+        seeds = [b"voter", governance.key().as_ref(), voter.key().as_ref()],
+        bump,
+        has_one = voter
+    )]
+    pub voter_profile: Account<'info, VoterProfile>,
+    pub voter: Signer<'info>,
+    #[account(mut, token::authority = participation_rewards, token::mint = governance.token_mint)]
+    pub reward_vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = governance.token_mint, token::authority = voter)]
+    pub voter_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct GetParticipation<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    /// CHECK: wallet being queried
+    pub voter: UncheckedAccount<'info>,
+    /// CHECK: may not exist yet; the address is pinned by the seeds
+    #[account(seeds = [b"voter", governance.key().as_ref(), voter.key().as_ref()], bump)]
+    pub voter_profile: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetVoteReceipt<'info> {
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: wallet being queried
+    pub voter: UncheckedAccount<'info>,
+    /// CHECK: may not exist yet; the address is pinned by the seeds
+    #[account(seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()], bump)]
+    pub vote_record: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueProposal<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(address = governance.token_mint @ GovernanceError::InvalidTokenMint)]
+    pub token_mint: Account<'info, token::Mint>,
+    #[account(mut, has_one = governance @ GovernanceError::RealmMismatch)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(address = governance.token_mint @ GovernanceError::InvalidTokenMint)]
+    pub token_mint: Account<'info, token::Mint>,
+    #[account(mut, has_one = governance @ GovernanceError::RealmMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleDeposit<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(mut, has_one = proposer, has_one = governance @ GovernanceError::RealmMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: refund recipient, validated by `has_one`
+    pub proposer: AccountInfo<'info>,
+    #[account(mut, token::authority = proposer)]
+    pub proposer_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns the deposit vault
+    #[account(seeds = [b"vault_authority", governance.key().as_ref()], bump)]
+    pub vault_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = vault_authority)]
+    pub deposit_vault: Account<'info, TokenAccount>,
+    // Economics accounts used when the deposit is forfeited, pinned to the realm's
+    #[account(mut, address = governance.economics @ GovernanceError::EconomicsMismatch)]
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut, address = governance.venexus_treasury @ GovernanceError::EconomicsMismatch)]
+    pub venexus_treasury: Account<'info, TokenAccount>,
+    #[account(mut, address = governance.ainexus_treasury @ GovernanceError::EconomicsMismatch)]
+    pub ainexus_treasury: Account<'info, TokenAccount>,
+    #[account(mut, address = governance.protocol_treasury @ GovernanceError::EconomicsMismatch)]
+    pub protocol_treasury: Account<'info, TokenAccount>,
+    #[account(mut, address = governance.token_mint @ GovernanceError::InvalidTokenMint)]
+    pub token_mint: Account<'info, token::Mint>,
+    pub economics_program: Program<'info, NexusEconomics>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseThresholdLock<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(mut, has_one = proposer, has_one = governance @ GovernanceError::RealmMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: refund recipient, validated by `has_one`
+    pub proposer: AccountInfo<'info>,
+    #[account(mut, token::authority = proposer)]
+    pub proposer_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns the deposit vault
+    #[account(seeds = [b"vault_authority", governance.key().as_ref()], bump)]
+    pub vault_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = vault_authority)]
+    pub deposit_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposalsBatch<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(address = governance.token_mint @ GovernanceError::InvalidTokenMint)]
+    pub token_mint: Account<'info, token::Mint>,
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTerminalAccounts<'info> {
+    pub governance: Account<'info, GovernanceState>,
+    #[account(address = governance.token_mint @ GovernanceError::InvalidTokenMint)]
+    pub token_mint: Account<'info, token::Mint>,
+    pub keeper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileLocked<'info> {
+    #[account(mut, has_one = authority)]
+    pub governance: Account<'info, GovernanceState>,
+    pub authority: Signer<'info>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
-pub enum Vote {
-    Yes,
-    No,
-    Veto,
-    Abstain,
-}
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    pub governance: Account<'info, GovernanceState>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyAction<'info> {
+    #[account(mut)]
+    pub governance: Account<'info, GovernanceState>,
+    #[account(mut, has_one = governance @ GovernanceError::RealmMismatch)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        init_if_needed,
+        payer = emergency_council_member,
+        space = EmergencyApproval::LEN,
+        seeds = [b"emergency", proposal.key().as_ref()],
+        bump
+    )]
+    pub emergency_approval: Account<'info, EmergencyApproval>,
+    #[account(mut)]
+    pub emergency_council_member: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct GovernanceState {
+    pub authority: Pubkey,
+    pub token_mint: Pubkey,
+    pub config: GovernanceConfig,
+    pub proposal_count: u64,
+    pub total_locked_tokens: u64,
+    pub emergency_council: Vec<Pubkey>,
+    pub type_proposal_counts: [u64; PROPOSAL_TYPE_SLOTS],  // indexed by ProposalType::counter_slot
+    pub bump: u8,
+    pub realm_id: u64,  // distinguishes the realms one authority runs
+    pub economics: Pubkey,  // economics deployment whose locks count as voting power
+    pub venexus_treasury: Pubkey,  // that deployment's fee treasuries, paid forfeited deposits
+    pub ainexus_treasury: Pubkey,
+    pub protocol_treasury: Pubkey,
+}
+
+impl GovernanceState {
+    pub const LEN: usize = 8 + 32 + 32 + GovernanceConfig::LEN + 8 + 8 + 4 + 32 * MAX_EMERGENCY_COUNCIL
+        + 8 * PROPOSAL_TYPE_SLOTS + 1 + 8 + 32 + 32 * 3;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GovernanceConfig {
+    pub voting_delay: i64,
+    pub voting_period: i64,
+    pub quorum_percentage: u8,
+    pub proposal_threshold: u64,
+    pub emergency_threshold: u8,
+    pub proposal_cooldown: i64,
+    pub proposal_deposit: u64,
+    pub quorum_basis: QuorumBasis,
+    pub quorum_floor: u64,  // turnout in tokens that must also be met, 0 disables
+    pub min_vote_weight: u64,
+    pub vote_weight_cap: VoteWeightCap,
+    pub vote_extension: VoteExtension,
+    pub veto_window: i64,  // final seconds of voting that only accept vetoes
+    pub min_description_len: u16,  // ignored for hash-committed descriptions
+    pub voting_mode: VotingMode,
+    pub type_quorums: TypeQuorums,
+    pub type_deposits: TypeDeposits,
+    pub allowed_link_prefixes: Vec<String>,  // empty accepts any link
+    pub custom_proposal_types: Vec<CustomProposalType>,
+    pub execution_delays: ExecutionDelays,
+    pub lock_duration_boost_bps: u16,  // extra weight for a vote backed by a full-length lock, 0 disables
+    pub voter_count_min_weight: u64,  // raw weight a vote needs to count towards voter_count
+    pub lock_proposer_threshold: bool,  // escrow the proposer's required tokens while voting runs
+}
+
+impl GovernanceConfig {
+    pub const LEN: usize = 8 + 8 + 1 + 8 + 1 + 8 + 8 + 1 + 8 + 8 + 9 + VoteExtension::LEN + 8 + 2 + 1 + TypeQuorums::LEN + TypeDeposits::LEN
+        + 4 + (4 + MAX_LINK_PREFIX_LEN) * MAX_LINK_PREFIXES
+        + 4 + CustomProposalType::LEN * MAX_CUSTOM_PROPOSAL_TYPES
+        + ExecutionDelays::LEN + 2 + 8 + 1;
+
+    // Links must start with an allowed prefix or be a bare Arweave/Irys transaction id
+    pub fn link_allowed(&self, link: &str) -> bool {
+        self.allowed_link_prefixes.is_empty()
+            || self.allowed_link_prefixes.iter().any(|prefix| link.starts_with(prefix.as_str()))
+            || is_arweave_tx_id(link)
+    }
+
+    pub fn custom_proposal_type(&self, index: u8) -> Result<&CustomProposalType> {
+        self.custom_proposal_types
+            .get(index as usize)
+            .ok_or(error!(GovernanceError::UnknownProposalType))
+    }
+}
+
+// DAO-defined proposal type, referenced by index from ProposalType::Custom
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CustomProposalType {
+    pub name: String,
+    pub approval_threshold: u8, // Percentage of votes cast that must be yes
+    pub required_tokens: u64,
+}
+
+impl CustomProposalType {
+    pub const LEN: usize = 4 + MAX_PROPOSAL_TYPE_NAME_LEN + 1 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum QuorumBasis {
+    LockedSupply,
+    TotalSupply,
+}
+
+// Outcome-flipping votes within `window` seconds of the end extend voting by
+// `extension`, up to `max_total_extension` per proposal. Zero extension disables it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct VoteExtension {
+    pub window: i64,
+    pub extension: i64,
+    pub max_total_extension: i64,
+}
+
+impl VoteExtension {
+    pub const LEN: usize = 8 + 8 + 8;
+}
+
+// Upper bound on the weight a single voter can contribute
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum VoteWeightCap {
+    None,
+    Absolute(u64),
+    Percentage(u64),  // basis points of the quorum supply
+}
+
+// Quorum overrides for the built-in proposal types. Unset types, and
+// custom types, use `quorum_percentage`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct TypeQuorums {
+    pub core: Option<u8>,
+    pub technical: Option<u8>,
+    pub operational: Option<u8>,
+}
+
+impl TypeQuorums {
+    pub const LEN: usize = 2 + 2 + 2;
+
+    pub fn get(&self, proposal_type: &ProposalType) -> Option<u8> {
+        match proposal_type {
+            ProposalType::Core => self.core,
+            ProposalType::Technical => self.technical,
+            ProposalType::Operational => self.operational,
+            ProposalType::Custom(_) => None,
+        }
+    }
+}
+
+// Deposit overrides for the built-in proposal types. Unset types, and
+// custom types, use `proposal_deposit`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct TypeDeposits {
+    pub core: Option<u64>,
+    pub technical: Option<u64>,
+    pub operational: Option<u64>,
+}
+
+impl TypeDeposits {
+    pub const LEN: usize = 9 + 9 + 9;
+
+    pub fn get(&self, proposal_type: &ProposalType) -> Option<u64> {
+        match proposal_type {
+            ProposalType::Core => self.core,
+            ProposalType::Technical => self.technical,
+            ProposalType::Operational => self.operational,
+            ProposalType::Custom(_) => None,
+        }
+    }
+}
+
+// Timelock between queueing a passed proposal and executing it, per type.
+// All custom types share `custom`; a zero delay needs no queueing.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct ExecutionDelays {
+    pub core: i64,
+    pub technical: i64,
+    pub operational: i64,
+    pub custom: i64,
+}
+
+impl ExecutionDelays {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+
+    pub fn get(&self, proposal_type: &ProposalType) -> i64 {
+        match proposal_type {
+            ProposalType::Core => self.core,
+            ProposalType::Technical => self.technical,
+            ProposalType::Operational => self.operational,
+            ProposalType::Custom(_) => self.custom,
+        }
+    }
+}
+
+// How token holdings translate into vote weight
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum VotingMode {
+    Linear,
+    Quadratic,  // weight is the integer square root of the (capped) holding
+}
+
+#[account]
+pub struct Proposal {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub proposal_type: ProposalType,
+    pub title: String,
+    pub description: String,
+    pub link: String,
+    pub created_at: i64,
+    pub voting_starts_at: i64,
+    pub voting_ends_at: i64,
+    pub executed: bool,
+    pub cancelled: bool,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub veto_votes: u64,
+    pub abstain_votes: u64,
+    pub quorum: u8,
+    pub deposit: u64,
+    pub deposit_settled: bool,
+    pub total_extension: i64,
+    pub description_hash: [u8; 32],  // all zeros when the description is on-chain only
+    pub type_index: u64,  // position among proposals of the same type, so `(proposal_type, type_index)` is unique
+    pub quorum_floor: u64,  // absolute turnout snapshotted from the config alongside `quorum`
+    pub governance: Pubkey,  // realm the proposal belongs to
+    pub eta: i64,  // earliest execution time once queued, 0 until then
+    pub voter_count: u64,  // voters at or above the config's voter_count_min_weight
+    pub threshold_locked: u64,  // proposer tokens escrowed in the deposit vault until voting closes
+    pub action_hash: [u8; 32],  // on-chain action the proposal enacts, all zeros when it enacts none
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + 8 + 32 + 2
+        + 4 + MAX_TITLE_LEN
+        + 4 + MAX_DESCRIPTION_LEN
+        + 4 + MAX_LINK_LEN
+        + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 8 + 8 + 1 + 8 + 1 + 8 + 32 + 8 + 8 + 32 + 8 + 8 + 8 + 32;
+
+    // Space for a proposal whose description is `description_len` bytes
+    pub fn space(description_len: usize) -> usize {
+        Self::LEN - MAX_DESCRIPTION_LEN + description_len.min(MAX_DESCRIPTION_LEN)
+    }
+}
+
+// Council approvals collected towards cancelling a proposal
+#[account]
+pub struct EmergencyApproval {
+    pub proposal: Pubkey,
+    pub approvals: Vec<Pubkey>,
+}
+
+impl EmergencyApproval {
+    pub const LEN: usize = 8 + 32 + 4 + 32 * MAX_EMERGENCY_COUNCIL;
+}
+
+#[account]
+pub struct ProposerRecord {
+    pub proposer: Pubkey,
+    pub last_proposal_ts: i64,
+}
+
+impl ProposerRecord {
+    pub const LEN: usize = 8 + 32 + 8;
+}
+
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub vote: Vote,
+    pub weight: u64,      // counted weight after the cap
+    pub raw_weight: u64,  // token balance at vote time
+    pub lock_duration: i64,  // seconds left on the backing lock at vote time, 0 for liquid tokens
+    pub duration_factor_bps: u16,  // lock duration multiplier included in `weight`
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 + 8 + 8 + 2;
+}
+
+// Per-governance participation counter for one wallet
+#[account]
+pub struct VoterProfile {
+    pub voter: Pubkey,
+    pub proposals_voted: u64,
+    pub rewarded_votes: u64,  // qualifying votes already paid a participation reward
+    pub last_voted_at: i64,
+    pub qualifying_votes: u64,  // votes at or above the config's voter_count_min_weight
+}
+
+impl VoterProfile {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8;
+}
+
+// Turnout reward pool. Owns the reward vault, which the authority funds.
+#[account]
+pub struct ParticipationRewards {
+    pub governance: Pubkey,
+    pub reward_per_vote: u64,
+    pub period_length: i64,
+    pub period_budget: u64,  // most that can be paid out per period
+    pub period_start: i64,
+    pub period_paid: u64,
+}
+
+impl ParticipationRewards {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8;
+}
+
+/// Derives the GovernanceState address of realm `realm_id` created by `authority`.
+/// Seeds: `[b"governance", authority, realm_id as little-endian u64]`.
+pub fn governance_address(authority: &Pubkey, realm_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[GOVERNANCE_SEED, authority.as_ref(), &realm_id.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+/// Derives the VoteRecord address for `voter` on `proposal`.
+/// Seeds: `[b"vote", proposal, voter]`.
+pub fn vote_record_address(proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vote", proposal.as_ref(), voter.as_ref()], &crate::ID)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VoteReceipt {
+    pub voted: bool,
+    pub vote: Option<Vote>,
+    pub weight: u64,
+}
+
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+const MAX_EMERGENCY_COUNCIL: usize = 10;
+// Batch sizes that stay within compute and transaction size limits: each
+// proposal is a full deserialize-and-write, while locks are only read
+const MAX_EXECUTE_BATCH: usize = 10;
+const MAX_RECONCILE_LOCKS: usize = 24;
+const MAX_CLOSE_BATCH: usize = 12;  // (account, recipient) pairs per close_terminal_accounts
+const FULL_BOOST_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60; // Matches the economics max lock
+const MAX_CUSTOM_PROPOSAL_TYPES: usize = 8;
+const PROPOSAL_TYPE_SLOTS: usize = 3 + MAX_CUSTOM_PROPOSAL_TYPES;
+const MAX_PROPOSAL_TYPE_NAME_LEN: usize = 32;
+const MAX_TITLE_LEN: usize = 100;
+const MAX_DESCRIPTION_LEN: usize = 1000;
+const MAX_LINK_LEN: usize = 200;
+const MAX_LINK_PREFIXES: usize = 4;
+const MAX_LINK_PREFIX_LEN: usize = 64;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum ProposalType {
+    Core,        // 75% approval required
+    Technical,   // 66% approval required
+    Operational, // 51% approval required
+    Custom(u8),  // Index into GovernanceConfig.custom_proposal_types
+}
+
+impl ProposalType {
+    // Slot in GovernanceState.type_proposal_counts; custom types follow the built-in ones
+    pub fn counter_slot(&self) -> usize {
+        match self {
+            ProposalType::Core => 0,
+            ProposalType::Technical => 1,
+            ProposalType::Operational => 2,
+            ProposalType::Custom(index) => 3 + *index as usize,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum HealthStatus {
+    Healthy,
+    ConfigOutOfBounds,
+    CouncilOverCapacity,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum Vote {
+    Yes,
+    No,
+    Veto,
+    Abstain,
+}
+
+// Emitted by every instruction that writes GovernanceState.config
+#[event]
+pub struct ConfigChanged {
+    pub governance: Pubkey,
+    pub authority: Pubkey,
+    pub old_config: Option<GovernanceConfig>,
+    pub new_config: GovernanceConfig,
+}
+
+#[event]
+pub struct VotingExtended {
+    pub proposal: Pubkey,
+    pub voting_ends_at: i64,
+    pub total_extension: i64,
+}
+
+// Emitted when the authority or a council member extends voting by hand
+#[event]
+pub struct VotingPeriodExtended {
+    pub proposal: Pubkey,
+    pub extended_by: Pubkey,
+    pub extension: i64,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct ProposalsBatchExecuted {
+    pub governance: Pubkey,
+    pub executed: Vec<u64>,
+}
+
+#[event]
+pub struct TerminalAccountsClosed {
+    pub governance: Pubkey,
+    pub proposals: Vec<u64>,
+    pub vote_records: u32,
+}
+
+#[event]
+pub struct ProposalQueued {
+    pub proposal: Pubkey,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ParticipationRewardClaimed {
+    pub governance: Pubkey,
+    pub voter: Pubkey,
+    pub votes: u64,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum GovernanceError {
+    #[msg("Invalid voting period")]
+    InvalidVotingPeriod,
+    #[msg("Invalid voting delay")]
+    InvalidVotingDelay,
+    #[msg("Invalid quorum percentage")]
+    InvalidQuorum,
+    #[msg("Insufficient tokens to create proposal")]
+    InsufficientTokens,
+    #[msg("Voting has not started yet")]
+    VotingNotStarted,
+    #[msg("Voting has already ended")]
+    VotingEnded,
+    #[msg("Voting period has not ended yet")]
+    VotingNotEnded,
+    #[msg("Proposal has already been executed")]
+    AlreadyExecuted,
+    #[msg("Proposal has been cancelled")]
+    ProposalCancelled,
+    #[msg("Quorum has not been reached")]
+    QuorumNotReached,
+    #[msg("Proposal did not pass")]
+    ProposalNotPassed,
+    #[msg("Proposal was vetoed")]
+    ProposalVetoed,
+    #[msg("Invalid emergency action")]
+    InvalidEmergencyAction,
+    #[msg("Account must be writable")]
+    AccountNotWritable,
+    #[msg("Proposer must wait for the cooldown before creating another proposal")]
+    ProposalCooldown,
+    #[msg("Proposal was defeated by an exact tie")]
+    ProposalTied,
+    #[msg("Emergency threshold must be between 1 and 100")]
+    InvalidEmergencyThreshold,
+    #[msg("Emergency council is full")]
+    EmergencyCouncilFull,
+    #[msg("Signer is not on the emergency council")]
+    NotEmergencyCouncil,
+    #[msg("Council member has already approved this action")]
+    AlreadyApproved,
+    #[msg("Proposal deposit has already been settled")]
+    DepositAlreadySettled,
+    #[msg("Mint does not match the governance token")]
+    InvalidTokenMint,
+    #[msg("Number of lock accounts does not match the expected count")]
+    LockCountMismatch,
+    #[msg("Lock account passed more than once")]
+    DuplicateLock,
+    #[msg("Lock is unlocked or has expired")]
+    InactiveLock,
+    #[msg("Math overflow")]
+    Overflow,
+    #[msg("Too many custom proposal types")]
+    TooManyProposalTypes,
+    #[msg("Approval threshold must be above 50 and at most 100")]
+    InvalidApprovalThreshold,
+    #[msg("Proposal type name is too long")]
+    InvalidProposalTypeName,
+    #[msg("Unknown proposal type")]
+    UnknownProposalType,
+    #[msg("Title is too long")]
+    TitleTooLong,
+    #[msg("Description is too long")]
+    DescriptionTooLong,
+    #[msg("Description is too short")]
+    DescriptionTooShort,
+    #[msg("Link is too long")]
+    LinkTooLong,
+    #[msg("Voting weight is below the minimum")]
+    VoteWeightTooLow,
+    #[msg("Vote weight cap must be between 1 and 10000 basis points")]
+    InvalidVoteWeightCap,
+    #[msg("Vote extension parameters must not be negative")]
+    InvalidVoteExtension,
+    #[msg("Already an emergency council member")]
+    AlreadyCouncilMember,
+    #[msg("Description hash must be non-zero and accompanied by a link")]
+    InvalidDescriptionHash,
+    #[msg("Veto window must be within the voting period")]
+    InvalidVetoWindow,
+    #[msg("Only vetoes are accepted in the final veto window")]
+    VetoWindowOnly,
+    #[msg("There is no voting supply to measure quorum against")]
+    NoVotingSupply,
+    #[msg("No votes were cast")]
+    NoVotesCast,
+    #[msg("Minimum description length exceeds the maximum")]
+    InvalidMinDescriptionLen,
+    #[msg("Link is not on the allowlist")]
+    LinkNotAllowed,
+    #[msg("Link prefixes must be non-empty, short and few")]
+    InvalidLinkPrefix,
+    #[msg("Voting extension is not positive or exceeds the allowed maximum")]
+    ExtensionTooLong,
+    #[msg("Participation rewards need a positive reward, period and budget")]
+    InvalidParticipationRewards,
+    #[msg("No votes since the last participation reward")]
+    NoParticipationToReward,
+    #[msg("Participation reward budget for this period is spent")]
+    ParticipationBudgetExhausted,
+    #[msg("Proposal belongs to a different governance realm")]
+    RealmMismatch,
+    #[msg("Batch holds more accounts than the instruction accepts")]
+    BatchTooLarge,
+    #[msg("Execution delays must be between zero and the maximum")]
+    InvalidExecutionDelay,
+    #[msg("Proposal has already been queued")]
+    AlreadyQueued,
+    #[msg("Proposal must be queued before it can execute")]
+    ProposalNotQueued,
+    #[msg("Proposal timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Close batch must hold (account, rent recipient) pairs")]
+    InvalidCloseBatch,
+    #[msg("Lock duration boost is above the maximum")]
+    InvalidLockDurationBoost,
+    #[msg("Proposal holds no threshold tokens")]
+    NoThresholdLock,
+    #[msg("Lock belongs to a different economics deployment or mint")]
+    LockRealmMismatch,
+    #[msg("Action hash must be non-zero")]
+    InvalidActionHash,
+    #[msg("Economics or treasury account is not the one registered with the realm")]
+    EconomicsMismatch,
+    #[msg("Proposal cooldown cannot be negative")]
+    InvalidProposalCooldown,
+}
+
+// Only locks of the realm's economics deployment and mint carry voting power
+fn require_lock_in_realm(governance: &GovernanceState, lock: &nexus_common::LockHeader) -> Result<()> {
+    require_keys_eq!(lock.economics, governance.economics, GovernanceError::LockRealmMismatch);
+    require_keys_eq!(lock.token_mint, governance.token_mint, GovernanceError::LockRealmMismatch);
+    Ok(())
+}
+
+// Checks the voting window and weight floor, then adds the vote to the
+// proposal tally. Returns the weight counted once the cap and voting mode apply.
+fn tally_vote(
+    governance: &GovernanceState,
+    supply: u64,
+    proposal: &mut Account<Proposal>,
+    vote: &Vote,
+    raw_weight: u64,
+    factor_bps: u64,
+) -> Result<u64> {
+    let clock = Clock::get()?;
+
+    require!(!proposal.cancelled, GovernanceError::ProposalCancelled);
+
+    require!(
+        clock.unix_timestamp >= proposal.voting_starts_at,
+        GovernanceError::VotingNotStarted
+    );
+
+    require!(
+        clock.unix_timestamp <= proposal.voting_ends_at,
+        GovernanceError::VotingEnded
+    );
+
+    // The final veto window only accepts vetoes
+    require!(
+        clock.unix_timestamp <= proposal.voting_ends_at - governance.config.veto_window
+            || matches!(vote, Vote::Veto),
+        GovernanceError::VetoWindowOnly
+    );
+
+    // Dust votes would each pay for a VoteRecord without moving the outcome
+    require!(
+        raw_weight >= governance.config.min_vote_weight,
+        GovernanceError::VoteWeightTooLow
+    );
+
+    // Clamp large holders to the configured share of possible weight
+    let capped_weight = capped_vote_weight(
+        raw_weight,
+        &governance.config.vote_weight_cap,
+        supply,
+    );
+    let voter_weight = match governance.config.voting_mode {
+        VotingMode::Linear => capped_weight,
+        VotingMode::Quadratic => integer_sqrt(capped_weight),
+    };
+    let voter_weight = apply_bps(voter_weight, factor_bps, Rounding::Down)?;
+
+    // Record vote. Only holdings above the threshold count as a distinct
+    // voter, so splitting a balance into dust wallets does not inflate it.
+    if raw_weight >= governance.config.voter_count_min_weight {
+        proposal.voter_count += 1;
+    }
+    let was_passing = proposal.yes_votes > proposal.no_votes;
+    match vote {
+        Vote::Yes => proposal.yes_votes += voter_weight,
+        Vote::No => proposal.no_votes += voter_weight,
+        Vote::Veto => proposal.veto_votes += voter_weight,
+        Vote::Abstain => proposal.abstain_votes += voter_weight,
+    }
+
+    // A late vote that flips the outcome buys everyone else time to respond
+    let extension = &governance.config.vote_extension;
+    let flipped = was_passing != (proposal.yes_votes > proposal.no_votes);
+    if flipped
+        && extension.extension > 0
+        && clock.unix_timestamp >= proposal.voting_ends_at - extension.window
+    {
+        let added = extension.extension
+            .min(extension.max_total_extension - proposal.total_extension);
+        if added > 0 {
+            proposal.voting_ends_at += added;
+            proposal.total_extension += added;
+            emit!(VotingExtended {
+                proposal: proposal.key(),
+                voting_ends_at: proposal.voting_ends_at,
+                total_extension: proposal.total_extension,
+            });
+        }
+    }
+
+    Ok(voter_weight)
+}
+
+// Multiplier in bps on a lock-backed vote: 1x plus the configured boost,
+// scaled by the time left on the lock up to FULL_BOOST_LOCK_DURATION
+fn duration_factor_bps(config: &GovernanceConfig, lock_duration: i64) -> u64 {
+    let boost = config.lock_duration_boost_bps as u64;
+    let remaining = lock_duration.clamp(0, FULL_BOOST_LOCK_DURATION) as u64;
+    BPS_DENOMINATOR + boost * remaining / FULL_BOOST_LOCK_DURATION as u64
+}
+
+// Supply that the quorum percentage is measured against
+fn quorum_supply(governance: &GovernanceState, token_mint: &token::Mint) -> u64 {
+    match governance.config.quorum_basis {
+        QuorumBasis::LockedSupply => governance.total_locked_tokens,
+        QuorumBasis::TotalSupply => token_mint.supply,
+    }
+}
+
+fn capped_vote_weight(raw_weight: u64, cap: &VoteWeightCap, supply: u64) -> u64 {
+    match cap {
+        VoteWeightCap::None => raw_weight,
+        VoteWeightCap::Absolute(max) => raw_weight.min(*max),
+        VoteWeightCap::Percentage(bps) => {
+            // bps <= 10_000 is enforced by the config, so this cannot fail
+            let max = apply_bps(supply, *bps, Rounding::Down).unwrap_or(supply);
+            raw_weight.min(max)
+        }
+    }
+}
+
+// Turnout must meet both the percentage of supply and the absolute floor,
+// so a shrinking supply cannot make quorum trivially small
+fn quorum_reached(proposal: &Proposal, supply: u64) -> bool {
+    let total_votes = proposal.yes_votes + proposal.no_votes + proposal.veto_votes + proposal.abstain_votes;
+    // Round up so a fractional threshold is never met by the vote below it
+    match mul_div(supply, proposal.quorum as u64, 100, Rounding::Up) {
+        Ok(quorum_threshold) => total_votes >= quorum_threshold.max(proposal.quorum_floor),
+        Err(_) => false,
+    }
+}
+
+// Nothing further can happen to the proposal: it was executed, cancelled or
+// defeated, and its deposit and any threshold lock have been settled
+fn is_terminal(proposal: &Proposal, config: &GovernanceConfig, supply: u64, now: i64) -> bool {
+    let finished = proposal.executed
+        || proposal.cancelled
+        || (now > proposal.voting_ends_at && check_passed(proposal, config, supply, now).is_err());
+    finished && (proposal.deposit_settled || proposal.deposit == 0) && proposal.threshold_locked == 0
+}
+
+// Shared eligibility checks for single and batch execution. A passed
+// proposal may execute once its type's timelock has run out
+fn check_executable(
+    proposal: &Proposal,
+    config: &GovernanceConfig,
+    supply: u64,
+    now: i64,
+) -> Result<()> {
+    check_passed(proposal, config, supply, now)?;
+
+    if proposal.eta == 0 {
+        require!(
+            config.execution_delays.get(&proposal.proposal_type) == 0,
+            GovernanceError::ProposalNotQueued
+        );
+    } else {
+        require!(now >= proposal.eta, GovernanceError::TimelockNotElapsed);
+    }
+
+    Ok(())
+}
+
+fn check_passed(
+    proposal: &Proposal,
+    config: &GovernanceConfig,
+    supply: u64,
+    now: i64,
+) -> Result<()> {
+    require!(
+        now > proposal.voting_ends_at,
+        GovernanceError::VotingNotEnded
+    );
+
+    require!(!proposal.executed, GovernanceError::AlreadyExecuted);
+    require!(!proposal.cancelled, GovernanceError::ProposalCancelled);
+
+    // Check quorum and vote outcome
+    let total_votes = proposal.yes_votes + proposal.no_votes + proposal.veto_votes + proposal.abstain_votes;
+
+    // With no supply the quorum threshold is zero and would trivially pass,
+    // and with no votes the approval ratios below divide by zero
+    require!(supply > 0, GovernanceError::NoVotingSupply);
+    require!(total_votes > 0, GovernanceError::NoVotesCast);
+
+    require!(
+        quorum_reached(proposal, supply),
+        GovernanceError::QuorumNotReached
+    );
+
+    // An exact yes/no tie is always defeated. It is reported separately from
+    // ProposalNotPassed so clients can display the outcome as a tie.
+    require!(proposal.yes_votes != proposal.no_votes, GovernanceError::ProposalTied);
+
+    // Check if proposal passed based on type
+    let passed = match proposal.proposal_type {
+        ProposalType::Core => {
+            proposal.yes_votes as u128 * 100 >= total_votes as u128 * 75 // 75% required
+        }
+        ProposalType::Technical => {
+            proposal.yes_votes as u128 * 100 >= total_votes as u128 * 66 // 66% required
+        }
+        ProposalType::Operational => {
+            proposal.yes_votes > proposal.no_votes // Simple majority
+        }
+        ProposalType::Custom(index) => {
+            let threshold = config.custom_proposal_type(index)?.approval_threshold;
+            proposal.yes_votes as u128 * 100 >= total_votes as u128 * threshold as u128
+        }
+    };
+
+    require!(passed, GovernanceError::ProposalNotPassed);
+    require!(proposal.veto_votes == 0, GovernanceError::ProposalVetoed);
+
+    Ok(())
+}
+
+// Save as: tests/governance.ts
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { NexusGovernance } from '../target/types/nexus_governance';
+import { NexusEconomics } from '../target/types/nexus_economics';
+import { TOKEN_PROGRAM_ID, createAccount, getAccount, transfer } from '@solana/spl-token';
+import { expect } from 'chai';
+import * as crypto from 'crypto';
+
+describe('nexus-governance', () => {
+    const provider = anchor.AnchorProvider.env();
+    anchor.setProvider(provider);
+
+    const program = anchor.workspace.NexusGovernance as Program<NexusGovernance>;
+    let governance: anchor.web3.PublicKey;
+    let proposal: anchor.web3.PublicKey;
+    let openProposal: anchor.web3.PublicKey;
+    let emergencyProposal: anchor.web3.PublicKey;
+    let vetoedProposal: anchor.web3.PublicKey;
+    let tokenMint: anchor.web3.PublicKey;
+    let lockedGovernance: anchor.web3.PublicKey;
+    let supplyGovernance: anchor.web3.PublicKey;
+    let basisProposal: anchor.web3.PublicKey;
+    let supplyBasisProposal: anchor.web3.PublicKey;
+    let driftedGovernance: anchor.web3.PublicKey;
+    let lockA: anchor.web3.PublicKey;
+    let lockB: anchor.web3.PublicKey;
+    let treasuryGovernance: anchor.web3.PublicKey;
+    let treasuryProposal: anchor.web3.PublicKey;
+    let maxProposer: anchor.web3.Keypair;
+    let secondVoterTokenAccount: anchor.web3.PublicKey;
+    let quorumGovernance: anchor.web3.PublicKey;
+    let quorumProposerRecord: anchor.web3.PublicKey;
+    let elevatedQuorumProposal: anchor.web3.PublicKey;
+    let floorGovernance: anchor.web3.PublicKey;
+    let flooredProposal: anchor.web3.PublicKey;
+    let dustGovernance: anchor.web3.PublicKey;
+    let dustProposal: anchor.web3.PublicKey;
+    let dustVoter: anchor.web3.Keypair;
+    let dustVoterTokenAccount: anchor.web3.PublicKey;
+    let thresholdVoter: anchor.web3.Keypair;
+    let thresholdVoterTokenAccount: anchor.web3.PublicKey;
+    let cappedGovernance: anchor.web3.PublicKey;
+    let cappedProposal: anchor.web3.PublicKey;
+    let whale: anchor.web3.Keypair;
+    let whaleTokenAccount: anchor.web3.PublicKey;
+    let extensionGovernance: anchor.web3.PublicKey;
+    let lateProposal: anchor.web3.PublicKey;
+    let maxedProposal: anchor.web3.PublicKey;
+    let lateWhale: anchor.web3.Keypair;
+    let lateWhaleTokenAccount: anchor.web3.PublicKey;
+    let emptyGovernance: anchor.web3.PublicKey;
+    let emptySupplyProposal: anchor.web3.PublicKey;
+    let silentProposal: anchor.web3.PublicKey;
+    let councilGovernance: anchor.web3.PublicKey;
+    let hashProposer: anchor.web3.Keypair;
+    let hashProposerRecord: anchor.web3.PublicKey;
+    let hashProposerTokenAccount: anchor.web3.PublicKey;
+    let vetoWindowGovernance: anchor.web3.PublicKey;
+    let vetoWindowProposal: anchor.web3.PublicKey;
+    let descriptionGovernance: anchor.web3.PublicKey;
+    let quadraticGovernance: anchor.web3.PublicKey;
+    let quadraticProposal: anchor.web3.PublicKey;
+    let linearProposal: anchor.web3.PublicKey;
+    let typedQuorumGovernance: anchor.web3.PublicKey;
+    let typedDepositGovernance: anchor.web3.PublicKey;
+    let linkGovernance: anchor.web3.PublicKey;
+    let extendableProposal: anchor.web3.PublicKey;
+    let longProposal: anchor.web3.PublicKey;
+    let maxProposerRecord: anchor.web3.PublicKey;
+    let maxProposerTokenAccount: anchor.web3.PublicKey;
+    let vaultAuthority: anchor.web3.PublicKey;
+    let depositVault: anchor.web3.PublicKey;
+    let economics: anchor.web3.PublicKey;
+    let venexusTreasury: anchor.web3.PublicKey;
+    let ainexusTreasury: anchor.web3.PublicKey;
+    let protocolTreasury: anchor.web3.PublicKey;
+    let tiedProposal: anchor.web3.PublicKey;
+    const councilA = anchor.web3.Keypair.generate();
+    const councilB = anchor.web3.Keypair.generate();
+    const councilC = anchor.web3.Keypair.generate();
+    let proposerRecord: anchor.web3.PublicKey;
+
+    const voterProfileFor = (gov: anchor.web3.PublicKey, voter: anchor.web3.PublicKey) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("voter"), gov.toBuffer(), voter.toBuffer()],
+            program.programId
+        )[0];
+
+    const realmAddress = (realmId: number) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+            [
+                Buffer.from('governance'),
+                provider.wallet.publicKey.toBuffer(),
+                new anchor.BN(realmId).toArrayLike(Buffer, 'le', 8),
+            ],
+            program.programId
+        );
+
+    // Creates realm `realmId` of the wallet, configured like `governance` apart from
+    // `overrides`, with a deposit vault owned by its vault authority PDA
+    const newRealm = async (realmId: number, overrides: object, council: anchor.web3.PublicKey[] = []) => {
+        const existing = await program.account.governanceState.fetch(governance);
+        const [realm] = realmAddress(realmId);
+        await program.methods
+            .createGovernance({ ...existing.config, ...overrides }, council, new anchor.BN(realmId))
+            .accounts({
+                governance: realm,
+                tokenMint: tokenMint,
+                economics: economics,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocolTreasury,
+                authority: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+        const [realmVaultAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vault_authority"), realm.toBuffer()],
+            program.programId
+        );
+        const realmVault = await createAccount(
+            provider.connection,
+            await provider.wallet.payer,
+            tokenMint,
+            realmVaultAuthority,
+            anchor.web3.Keypair.generate()
+        );
+
+        return { governance: realm, vaultAuthority: realmVaultAuthority, depositVault: realmVault };
+    };
+
+    // Funds `owner`, a fresh wallet by default, from `voterTokenAccount` and locks
+    // `amount` of it in `economicsKey` for `duration` seconds
+    const lockFor = async (
+        economicsKey: anchor.web3.PublicKey,
+        amount: number,
+        duration: number,
+        owner = anchor.web3.Keypair.generate(),
+    ) => {
+        const economicsProgram = anchor.workspace.NexusEconomics as Program<NexusEconomics>;
+        const payer = await provider.wallet.payer;
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(owner.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+        );
+        const ownerTokenAccount = await createAccount(
+            provider.connection,
+            payer,
+            tokenMint,
+            owner.publicKey,
+            anchor.web3.Keypair.generate()
+        );
+        await transfer(provider.connection, payer, voterTokenAccount, ownerTokenAccount, provider.wallet.publicKey, amount);
+
+        const lock = anchor.web3.Keypair.generate();
+        await economicsProgram.methods
+            .createLock(new anchor.BN(amount), new anchor.BN(duration))
+            .accounts({
+                lock: lock.publicKey,
+                economics: economicsKey,
+                owner: owner.publicKey,
+                ownerTokenAccount: ownerTokenAccount,
+                tokenMint: tokenMint,
+                custodyAuthority: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("custody_authority"), economicsKey.toBuffer()],
+                    economicsProgram.programId
+                )[0],
+                lockTokenAccount: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("custody"), lock.publicKey.toBuffer()],
+                    economicsProgram.programId
+                )[0],
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([lock, owner])
+            .rpc();
+
+        return { owner, lock: lock.publicKey };
+    };
+
+    it('Creates governance', async () => {
+        const config = {
+            votingDelay: new anchor.BN(24 * 60 * 60),    // 1 day
+            votingPeriod: new anchor.BN(5 * 24 * 60 * 60), // 5 days
+            quorumPercentage: 10,                        // 10%
+            proposalThreshold: new anchor.BN(100000),    // 100,000 tokens
+            emergencyThreshold: 80,                      // 80%
+            proposalCooldown: new anchor.BN(24 * 60 * 60), // 1 day
+            proposalDeposit: new anchor.BN(1000),         // 1,000 tokens
+            quorumBasis: { lockedSupply: {} },
+            quorumFloor: new anchor.BN(0),
+            minVoteWeight: new anchor.BN(0),
+            voteWeightCap: { none: {} },
+            voteExtension: { window: new anchor.BN(0), extension: new anchor.BN(0), maxTotalExtension: new anchor.BN(0) },
+            vetoWindow: new anchor.BN(0),
+            minDescriptionLen: 0,
+            votingMode: { linear: {} },
+            typeQuorums: { core: null, technical: null, operational: null },
+            typeDeposits: { core: null, technical: null, operational: null },
+            allowedLinkPrefixes: [],
+            customProposalTypes: [],
+            executionDelays: { core: new anchor.BN(0), technical: new anchor.BN(0), operational: new anchor.BN(0), custom: new anchor.BN(0) },
+            lockDurationBoostBps: 0,
+            voterCountMinWeight: new anchor.BN(0),
+            lockProposerThreshold: false,
+        };
+
+        let bump: number;
+        [governance, bump] = realmAddress(0);
+
+        let event;
+        const listener = program.addEventListener('ConfigChanged', (e) => { event = e; });
+
+        const tx = await program.methods
+            .createGovernance(config, [councilA.publicKey, councilB.publicKey, councilC.publicKey], new anchor.BN(0))
+            .accounts({
+                governance: governance,
+                tokenMint: tokenMint,
+                economics: economics,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocolTreasury,
+                authority: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        await program.removeEventListener(listener);
+
+        const governanceAccount = await program.account.governanceState.fetch(governance);
+        expect(governanceAccount.config.quorumPercentage).to.equal(config.quorumPercentage);
+        expect(governanceAccount.authority.toString()).to.equal(provider.wallet.publicKey.toString());
+        expect(governanceAccount.bump).to.equal(bump);
+
+        expect(event.authority.toString()).to.equal(provider.wallet.publicKey.toString());
+        expect(event.oldConfig).to.be.null;
+        expect(event.newConfig.quorumPercentage).to.equal(config.quorumPercentage);
+    });
+
+    it('Refuses to create governance twice at the same PDA', async () => {
+        const existing = await program.account.governanceState.fetch(governance);
+
+        try {
+            await program.methods
+                .createGovernance(existing.config, [provider.wallet.publicKey], new anchor.BN(0))
+                .accounts({
+                    governance: governance,
+                    tokenMint: tokenMint,
+                    economics: economics,
+                    venexusTreasury: venexusTreasury,
+                    ainexusTreasury: ainexusTreasury,
+                    protocolTreasury: protocolTreasury,
+                    authority: provider.wallet.publicKey,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .rpc();
+            expect.fail('existing governance should not be re-created');
+        } catch (err) {
+            expect(err.logs.join('\n')).to.include('already in use');
+        }
+
+        const after = await program.account.governanceState.fetch(governance);
+        expect(after.emergencyCouncil.length).to.equal(existing.emergencyCouncil.length);
+    });
+
+    it('Updates voting parameters within bounds', async () => {
+        const config = {
+            votingDelay: new anchor.BN(2 * 24 * 60 * 60),  // 2 days
+            votingPeriod: new anchor.BN(6 * 24 * 60 * 60), // 6 days
+            quorumPercentage: 15,                          // 15%
+            proposalThreshold: new anchor.BN(100000),
+            emergencyThreshold: 80,
+            proposalCooldown: new anchor.BN(24 * 60 * 60),
+            proposalDeposit: new anchor.BN(1000),
+            quorumBasis: { lockedSupply: {} },
+            quorumFloor: new anchor.BN(0),
+            minVoteWeight: new anchor.BN(0),
+            voteWeightCap: { none: {} },
+            voteExtension: { window: new anchor.BN(0), extension: new anchor.BN(0), maxTotalExtension: new anchor.BN(0) },
+            vetoWindow: new anchor.BN(0),
+            minDescriptionLen: 0,
+            votingMode: { linear: {} },
+            typeQuorums: { core: null, technical: null, operational: null },
+            typeDeposits: { core: null, technical: null, operational: null },
+            allowedLinkPrefixes: [],
+            customProposalTypes: [],
+            executionDelays: { core: new anchor.BN(0), technical: new anchor.BN(0), operational: new anchor.BN(0), custom: new anchor.BN(0) },
+            lockDurationBoostBps: 0,
+            voterCountMinWeight: new anchor.BN(0),
+            lockProposerThreshold: false,
+        };
+
+        let event;
+        const listener = program.addEventListener('ConfigChanged', (e) => { event = e; });
+
+        await program.methods
+            .updateGovernanceConfig(config)
+            .accounts({
+                governance: governance,
+                authority: provider.wallet.publicKey,
+            })
+            .rpc();
+
+        await program.removeEventListener(listener);
+
+        const governanceAccount = await program.account.governanceState.fetch(governance);
+        expect(governanceAccount.config.quorumPercentage).to.equal(15);
+        expect(event.oldConfig.quorumPercentage).to.equal(10);
+        expect(event.newConfig.quorumPercentage).to.equal(15);
+        expect(event.authority.toString()).to.equal(provider.wallet.publicKey.toString());
+    });
+
+    it('Rejects voting parameters outside bounds', async () => {
+        const config = {
+            votingDelay: new anchor.BN(24 * 60 * 60),
+            votingPeriod: new anchor.BN(30 * 24 * 60 * 60), // above the 7 day maximum
+            quorumPercentage: 10,
+            proposalThreshold: new anchor.BN(100000),
+            emergencyThreshold: 80,
+            proposalCooldown: new anchor.BN(24 * 60 * 60),
+            proposalDeposit: new anchor.BN(1000),
+            quorumBasis: { lockedSupply: {} },
+            quorumFloor: new anchor.BN(0),
+            minVoteWeight: new anchor.BN(0),
+            voteWeightCap: { none: {} },
+            voteExtension: { window: new anchor.BN(0), extension: new anchor.BN(0), maxTotalExtension: new anchor.BN(0) },
+            vetoWindow: new anchor.BN(0),
+            minDescriptionLen: 0,
+            votingMode: { linear: {} },
+            typeQuorums: { core: null, technical: null, operational: null },
+            typeDeposits: { core: null, technical: null, operational: null },
+            allowedLinkPrefixes: [],
+            customProposalTypes: [],
+            executionDelays: { core: new anchor.BN(0), technical: new anchor.BN(0), operational: new anchor.BN(0), custom: new anchor.BN(0) },
+            lockDurationBoostBps: 0,
+            voterCountMinWeight: new anchor.BN(0),
+            lockProposerThreshold: false,
+        };
+
+        try {
+            await program.methods
+                .updateGovernanceConfig(config)
+                .accounts({
+                    governance: governance,
+                    authority: provider.wallet.publicKey,
+                })
+                .rpc();
+            expect.fail('out of bounds config should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidVotingPeriod');
+        }
+    });
+
+    it('Reports a healthy governance state', async () => {
+        const status = await program.methods
+            .healthCheck()
+            .accounts({ governance: governance })
+            .view();
+
+        expect(status).to.deep.equal({ healthy: {} });
+    });
+
+    it('Reconciles total locked tokens from lock accounts', async () => {
+        // `driftedGovernance` tracks a total that disagrees with `lockA` + `lockB`
+        const economicsProgram = anchor.workspace.NexusEconomics as Program<NexusEconomics>;
+        const a = await economicsProgram.account.lockAccount.fetch(lockA);
+        const b = await economicsProgram.account.lockAccount.fetch(lockB);
+        const locks = [lockA, lockB].map((pubkey) => ({ pubkey, isWritable: false, isSigner: false }));
+
+        try {
+            await program.methods
+                .reconcileLocked(3)
+                .accounts({ governance: driftedGovernance, authority: provider.wallet.publicKey })
+                .remainingAccounts(locks)
+                .rpc();
+            expect.fail('count mismatch should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('LockCountMismatch');
+        }
+
+        await program.methods
+            .reconcileLocked(2)
+            .accounts({ governance: driftedGovernance, authority: provider.wallet.publicKey })
+            .remainingAccounts(locks)
+            .rpc();
+
+        const governanceAccount = await program.account.governanceState.fetch(driftedGovernance);
+        expect(governanceAccount.totalLockedTokens.toNumber()).to.equal(a.amount.add(b.amount).toNumber());
+    });
+
+    it('Caps how many locks one reconciliation reads', async () => {
+        const locks = Array(25).fill({ pubkey: lockA, isWritable: false, isSigner: false });
+        try {
+            await program.methods
+                .reconcileLocked(25)
+                .accounts({ governance: driftedGovernance, authority: provider.wallet.publicKey })
+                .remainingAccounts(locks)
+                .rpc();
+            expect.fail('a reconciliation over the cap should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('BatchTooLarge');
+        }
+    });
+
+    it('Rejects reconciliation against an account that is not an economics lock', async () => {
+        // A governance-owned account fails the shared owner check before it is decoded
+        try {
+            await program.methods
+                .reconcileLocked(1)
+                .accounts({ governance: driftedGovernance, authority: provider.wallet.publicKey })
+                .remainingAccounts([{ pubkey: driftedGovernance, isWritable: false, isSigner: false }])
+                .rpc();
+            expect.fail('non-lock account should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidAccountOwner');
+        }
+    });
+
+    it('Creates proposal', async () => {
+        const proposalType = { core: {} };
+        const title = "Test Proposal";
+        const description = "This is a test proposal";
+        const link = "https://docs.nexus.ai/proposals/1";
+
+        const tx = await program.methods
+            .createProposal(
+                proposalType,
+                title,
+                description,
+                link,
+                null,
+                null,
+                null
+            )
+            .accounts({
+                governance: governance,
+                proposal: proposal,
+                proposerRecord: proposerRecord,
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: vaultAuthority,
+                depositVault: depositVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        const proposalAccount = await program.account.proposal.fetch(proposal);
+        expect(proposalAccount.title).to.equal(title);
+        expect(proposalAccount.governance.toString()).to.equal(governance.toString());
+    });
+
+    it('Keeps proposals inside their own realm', async () => {
+        const existing = await program.account.governanceState.fetch(governance);
+        const [realmA] = realmAddress(1);
+        const [realmB] = realmAddress(2);
+
+        for (const [realm, realmId] of [[realmA, 1], [realmB, 2]] as const) {
+            await program.methods
+                .createGovernance(existing.config, [], new anchor.BN(realmId))
+                .accounts({
+                    governance: realm,
+                    tokenMint: tokenMint,
+                    economics: economics,
+                    venexusTreasury: venexusTreasury,
+                    ainexusTreasury: ainexusTreasury,
+                    protocolTreasury: protocolTreasury,
+                    authority: provider.wallet.publicKey,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .rpc();
+            const realmAccount = await program.account.governanceState.fetch(realm);
+            expect(realmAccount.realmId.toNumber()).to.equal(realmId);
+            expect(realmAccount.proposalCount.toNumber()).to.equal(0);
+        }
+        expect(realmA.toString()).to.not.equal(realmB.toString());
+
+        // `proposal` was created in the realm-0 governance
+        try {
+            await program.methods
+                .castVote({ yes: {} })
+                .accounts({
+                    governance: realmA,
+                    tokenMint: tokenMint,
+                    proposal: proposal,
+                    voteRecord: voteRecord,
+                    voterProfile: voterProfileFor(realmA, provider.wallet.publicKey),
+                    voter: provider.wallet.publicKey,
+                    voterTokenAccount: voterTokenAccount,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .rpc();
+            expect.fail('a proposal should not take votes through another realm');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('RealmMismatch');
+        }
+    });
+
+    it('Creates a proposal with max-size fields', async () => {
+        // `maxProposer` has no prior proposals so the cooldown does not apply
+        const maxProposal = anchor.web3.Keypair.generate();
+        const title = "t".repeat(100);
+        const description = "d".repeat(1000);
+        const link = "https://docs.nexus.ai/" + "l".repeat(178);
+
+        await program.methods
+            .createProposal({ operational: {} }, title, description, link, null, null, null)
+            .accounts({
+                governance: governance,
+                proposal: maxProposal.publicKey,
+                proposerRecord: maxProposerRecord,
+                proposer: maxProposer.publicKey,
+                proposerTokenAccount: maxProposerTokenAccount,
+                vaultAuthority: vaultAuthority,
+                depositVault: depositVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([maxProposal, maxProposer])
+            .rpc();
+
+        const proposalAccount = await program.account.proposal.fetch(maxProposal.publicKey);
+        expect(proposalAccount.description.length).to.equal(1000);
+        expect(proposalAccount.link.length).to.equal(200);
+    });
+
+    it('Locks the proposer threshold tokens while voting runs', async () => {
+        // A fresh realm that escrows the proposer's threshold tokens and takes no deposit
+        const { governance: thresholdRealm, vaultAuthority: thresholdVaultAuthority, depositVault: thresholdVault } =
+            await newRealm(14, {
+                proposalCooldown: new anchor.BN(0),
+                proposalDeposit: new anchor.BN(0),
+                lockProposerThreshold: true,
+            });
+        const balance = async () =>
+            Number((await provider.connection.getTokenAccountBalance(proposerTokenAccount)).value.amount);
+
+        const before = await balance();
+        const locked = anchor.web3.Keypair.generate();
+        await program.methods
+            .createProposal({ operational: {} }, "Locked threshold", "d".repeat(100), "", null, null, null)
+            .accounts({
+                governance: thresholdRealm,
+                proposal: locked.publicKey,
+                proposerRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("proposer"), thresholdRealm.toBuffer(), provider.wallet.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: thresholdVaultAuthority,
+                depositVault: thresholdVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([locked])
+            .rpc();
+
+        // The operational threshold left the proposer's wallet for the vault
+        const proposalAccount = await program.account.proposal.fetch(locked.publicKey);
+        expect(proposalAccount.thresholdLocked.toNumber()).to.equal(10_000);
+        expect(before - (await balance())).to.equal(10_000);
+        expect((await getAccount(provider.connection, thresholdVault)).amount.toString()).to.equal('10000');
+
+        try {
+            await program.methods
+                .releaseThresholdLock()
+                .accounts({
+                    governance: thresholdRealm,
+                    proposal: locked.publicKey,
+                    proposer: provider.wallet.publicKey,
+                    proposerTokenAccount: proposerTokenAccount,
+                    vaultAuthority: thresholdVaultAuthority,
+                    depositVault: thresholdVault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .rpc();
+            expect.fail('threshold tokens stay locked while voting runs');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('VotingNotEnded');
+        }
+        expect((await program.account.proposal.fetch(locked.publicKey)).thresholdLocked.toNumber()).to.equal(10_000);
+    });
+
+    it('Creates a proposal with an off-chain description hash', async () => {
+        // `hashProposer` has no prior proposals so the cooldown does not apply
+        const hashed = anchor.web3.Keypair.generate();
+        const document = Buffer.from("Full proposal text stored on Irys");
+        const descriptionHash = Array.from(crypto.createHash('sha256').update(document).digest());
+
+        await program.methods
+            .createProposal({ operational: {} }, "Hashed", "", "https://gateway.irys.xyz/proposal-doc", null, descriptionHash, null)
+            .accounts({
+                governance: governance,
+                proposal: hashed.publicKey,
+                proposerRecord: hashProposerRecord,
+                proposer: hashProposer.publicKey,
+                proposerTokenAccount: hashProposerTokenAccount,
+                vaultAuthority: vaultAuthority,
+                depositVault: depositVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([hashed, hashProposer])
+            .rpc();
+
+        const proposalAccount = await program.account.proposal.fetch(hashed.publicKey);
+        expect(proposalAccount.descriptionHash).to.deep.equal(descriptionHash);
+        expect(proposalAccount.description).to.equal("");
+
+        // Without the on-chain description the account is ~1KB smaller
+        const info = await provider.connection.getAccountInfo(hashed.publicKey);
+        const full = await provider.connection.getAccountInfo(proposal);
+        expect(info.data.length).to.be.below(full.data.length);
+    });
+
+    it('Enforces the configured minimum description length', async () => {
+        // `descriptionGovernance` requires descriptions of at least 20 characters
+        const [record] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("proposer"), descriptionGovernance.toBuffer(), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const accounts = {
+            governance: descriptionGovernance,
+            proposerRecord: record,
+            proposer: provider.wallet.publicKey,
+            proposerTokenAccount: proposerTokenAccount,
+            vaultAuthority: vaultAuthority,
+            depositVault: depositVault,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+        };
+
+        const short = anchor.web3.Keypair.generate();
+        try {
+            await program.methods
+                .createProposal({ operational: {} }, "Short", "tbd", "https://docs.nexus.ai/proposals/4", null, null, null)
+                .accounts({ ...accounts, proposal: short.publicKey })
+                .signers([short])
+                .rpc();
+            expect.fail('too short description should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('DescriptionTooShort');
+        }
+
+        const accepted = anchor.web3.Keypair.generate();
+        const description = "Fund the Irys storage grant programme";
+        await program.methods
+            .createProposal({ operational: {} }, "Grants", description, "https://docs.nexus.ai/proposals/4", null, null, null)
+            .accounts({ ...accounts, proposal: accepted.publicKey })
+            .signers([accepted])
+            .rpc();
+
+        const proposalAccount = await program.account.proposal.fetch(accepted.publicKey);
+        expect(proposalAccount.description).to.equal(description);
+    });
+
+    it('Only accepts allowlisted links', async () => {
+        // `linkGovernance` allows links under "https://docs.nexus.ai/" and has no cooldown
+        const [record] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("proposer"), linkGovernance.toBuffer(), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const create = (link: string) => {
+            const next = anchor.web3.Keypair.generate();
+            return program.methods
+                .createProposal({ operational: {} }, "Linked", "Proposal with an external link", link, null, null, null)
+                .accounts({
+                    governance: linkGovernance,
+                    proposal: next.publicKey,
+                    proposerRecord: record,
+                    proposer: provider.wallet.publicKey,
+                    proposerTokenAccount: proposerTokenAccount,
+                    vaultAuthority: vaultAuthority,
+                    depositVault: depositVault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([next])
+                .rpc();
+        };
+
+        await create("https://docs.nexus.ai/proposals/6");
+        await create("bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U");
+
+        try {
+            await create("https://docs.nexus.ai.evil.example/proposals/6");
+            expect.fail('link outside the allowlist should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('LinkNotAllowed');
+        }
+    });
+
+    it('Numbers proposals independently per type', async () => {
+        // A fresh realm with no proposals and no cooldown
+        const { governance: numberedGovernance, vaultAuthority: numberedVaultAuthority, depositVault: numberedVault } =
+            await newRealm(6, { proposalCooldown: new anchor.BN(0) });
+        const [record] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("proposer"), numberedGovernance.toBuffer(), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const create = async (proposalType: object) => {
+            const next = anchor.web3.Keypair.generate();
+            await program.methods
+                .createProposal(proposalType, "Numbered", "Proposal numbered within its type", "https://docs.nexus.ai/proposals/7", null, null, null)
+                .accounts({
+                    governance: numberedGovernance,
+                    proposal: next.publicKey,
+                    proposerRecord: record,
+                    proposer: provider.wallet.publicKey,
+                    proposerTokenAccount: proposerTokenAccount,
+                    vaultAuthority: numberedVaultAuthority,
+                    depositVault: numberedVault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([next])
+                .rpc();
+            return program.account.proposal.fetch(next.publicKey);
+        };
+
+        const firstCore = await create({ core: {} });
+        const firstOperational = await create({ operational: {} });
+        const secondCore = await create({ core: {} });
+
+        // Per-type indices restart for each type
+        expect(firstCore.typeIndex.toNumber()).to.equal(0);
+        expect(firstOperational.typeIndex.toNumber()).to.equal(0);
+        expect(secondCore.typeIndex.toNumber()).to.equal(1);
+
+        // The global id keeps counting across types
+        expect(firstCore.proposalId.toNumber()).to.equal(0);
+        expect(firstOperational.proposalId.toNumber()).to.equal(1);
+        expect(secondCore.proposalId.toNumber()).to.equal(2);
+
+        const governanceAccount = await program.account.governanceState.fetch(numberedGovernance);
+        expect(governanceAccount.proposalCount.toNumber()).to.equal(3);
+        expect(governanceAccount.typeProposalCounts.map((count) => count.toNumber()).slice(0, 3)).to.deep.equal([2, 0, 1]);
+    });
+
+    it('Rejects a second proposal inside the cooldown', async () => {
+        const next = anchor.web3.Keypair.generate();
+
+        try {
+            await program.methods
+                .createProposal({ operational: {} }, "Follow-up", "Too soon", "https://docs.nexus.ai/proposals/2", null, null, null)
+                .accounts({
+                    governance: governance,
+                    proposal: next.publicKey,
+                    proposerRecord: proposerRecord,
+                    proposer: provider.wallet.publicKey,
+                    proposerTokenAccount: proposerTokenAccount,
+                    vaultAuthority: vaultAuthority,
+                    depositVault: depositVault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([next])
+                .rpc();
+            expect.fail('second proposal inside the cooldown should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ProposalCooldown');
+        }
+    });
+
+    it('Allows another proposal once the cooldown has elapsed', async () => {
+        // A realm like `governance` but with a 2 second cooldown
+        const existing = await program.account.governanceState.fetch(governance);
+        const createRealm = (realmId: number, proposalCooldown: anchor.BN) => program.methods
+            .createGovernance({ ...existing.config, proposalCooldown }, [], new anchor.BN(realmId))
+            .accounts({
+                governance: realmAddress(realmId)[0],
+                tokenMint: tokenMint,
+                economics: economics,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocolTreasury,
+                authority: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        try {
+            await createRealm(4, new anchor.BN(-1));
+            expect.fail('a negative cooldown should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidProposalCooldown');
+        }
+
+        const [cooldownGovernance] = realmAddress(3);
+        await createRealm(3, new anchor.BN(2));
+
+        const [cooldownVaultAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vault_authority"), cooldownGovernance.toBuffer()],
+            program.programId
+        );
+        const cooldownVault = await createAccount(
+            provider.connection,
+            await provider.wallet.payer,
+            tokenMint,
+            cooldownVaultAuthority,
+            anchor.web3.Keypair.generate()
+        );
+        const [cooldownRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("proposer"), cooldownGovernance.toBuffer(), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const create = () => {
+            const next = anchor.web3.Keypair.generate();
+            return program.methods
+                .createProposal({ operational: {} }, "Cooldown", "Spaced out proposal", "https://docs.nexus.ai/proposals/3", null, null, null)
+                .accounts({
+                    governance: cooldownGovernance,
+                    proposal: next.publicKey,
+                    proposerRecord: cooldownRecord,
+                    proposer: provider.wallet.publicKey,
+                    proposerTokenAccount: proposerTokenAccount,
+                    vaultAuthority: cooldownVaultAuthority,
+                    depositVault: cooldownVault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([next])
+                .rpc();
+        };
+
+        await create();
+        const first = await program.account.proposerRecord.fetch(cooldownRecord);
+
+        try {
+            await create();
+            expect.fail('second proposal inside the cooldown should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ProposalCooldown');
+        }
+
+        await new Promise((resolve) => setTimeout(resolve, 3_000));
+        await create();
+
+        const record = await program.account.proposerRecord.fetch(cooldownRecord);
+        expect(record.lastProposalTs.toNumber()).to.be.at.least(first.lastProposalTs.toNumber() + 2);
+    });
+
+    it('Casts vote', async () => {
+        const vote = { yes: {} };
+
+        const tx = await program.methods
+            .castVote(vote)
+            .accounts({
+                governance: governance,
+                tokenMint: tokenMint,
+                proposal: proposal,
+                voteRecord: voteRecord,
+                voterProfile: voterProfileFor(governance, provider.wallet.publicKey),
+                voter: provider.wallet.publicKey,
+                voterTokenAccount: voterTokenAccount,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        const proposalAccount = await program.account.proposal.fetch(proposal);
+        expect(proposalAccount.yesVotes.toNumber()).to.be.above(0);
+    });
+
+    it('Enforces the minimum vote weight', async () => {
+        // `dustGovernance` requires 1,000 tokens to vote; `dustVoter` holds 999
+        // and `thresholdVoter` holds exactly 1,000
+        const voteRecordFor = (voter: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vote"), dustProposal.toBuffer(), voter.toBuffer()],
+            program.programId
+        )[0];
+
+        try {
+            await program.methods
+                .castVote({ yes: {} })
+                .accounts({
+                    governance: dustGovernance,
+                    tokenMint: tokenMint,
+                    proposal: dustProposal,
+                    voteRecord: voteRecordFor(dustVoter.publicKey),
+                    voterProfile: voterProfileFor(dustGovernance, dustVoter.publicKey),
+                    voter: dustVoter.publicKey,
+                    voterTokenAccount: dustVoterTokenAccount,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([dustVoter])
+                .rpc();
+            expect.fail('vote below the minimum weight should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('VoteWeightTooLow');
+        }
+
+        // Nor can it borrow another holder's balance to clear the minimum
+        try {
+            await program.methods
+                .castVote({ yes: {} })
+                .accounts({
+                    governance: dustGovernance,
+                    tokenMint: tokenMint,
+                    proposal: dustProposal,
+                    voteRecord: voteRecordFor(dustVoter.publicKey),
+                    voterProfile: voterProfileFor(dustGovernance, dustVoter.publicKey),
+                    voter: dustVoter.publicKey,
+                    voterTokenAccount: thresholdVoterTokenAccount,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([dustVoter])
+                .rpc();
+            expect.fail('voting with another holder\'s tokens should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ConstraintTokenOwner');
+        }
+
+        await program.methods
+            .castVote({ yes: {} })
+            .accounts({
+                governance: dustGovernance,
+                tokenMint: tokenMint,
+                proposal: dustProposal,
+                voteRecord: voteRecordFor(thresholdVoter.publicKey),
+                voterProfile: voterProfileFor(dustGovernance, thresholdVoter.publicKey),
+                voter: thresholdVoter.publicKey,
+                voterTokenAccount: thresholdVoterTokenAccount,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([thresholdVoter])
+            .rpc();
+
+        const proposalAccount = await program.account.proposal.fetch(dustProposal);
+        expect(proposalAccount.yesVotes.toNumber()).to.equal(1000);
+    });
+
+    it('Leaves dust votes out of the voter count', async () => {
+        // A fresh realm where any holding may vote but only 1,000 or more counts as a voter
+        const { governance: sybilRealm, vaultAuthority: sybilVaultAuthority, depositVault: sybilDepositVault } =
+            await newRealm(13, {
+                proposalCooldown: new anchor.BN(0),
+                minVoteWeight: new anchor.BN(1),
+                voterCountMinWeight: new anchor.BN(1_000),
+            });
+        expect((await program.account.governanceState.fetch(sybilRealm)).config.voterCountMinWeight.toNumber()).to.equal(1_000);
+
+        const sybilProposal = anchor.web3.Keypair.generate();
+        await program.methods
+            .createProposal({ operational: {} }, "Sybil", "Proposal voted on with dust", "", null, null, null)
+            .accounts({
+                governance: sybilRealm,
+                proposal: sybilProposal.publicKey,
+                proposerRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("proposer"), sybilRealm.toBuffer(), provider.wallet.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: sybilVaultAuthority,
+                depositVault: sybilDepositVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([sybilProposal])
+            .rpc();
+
+        const dust = anchor.web3.Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(dust.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+        );
+        const dustAccount = await createAccount(
+            provider.connection,
+            await provider.wallet.payer,
+            tokenMint,
+            dust.publicKey,
+            anchor.web3.Keypair.generate()
+        );
+        await transfer(provider.connection, await provider.wallet.payer, voterTokenAccount, dustAccount, provider.wallet.publicKey, 999);
+
+        // Voting opens a day after creation at the earliest, so the dust vote is
+        // refused before it can reach the tally
+        try {
+            await program.methods
+                .castVote({ yes: {} })
+                .accounts({
+                    governance: sybilRealm,
+                    tokenMint: tokenMint,
+                    proposal: sybilProposal.publicKey,
+                    voteRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                        [Buffer.from("vote"), sybilProposal.publicKey.toBuffer(), dust.publicKey.toBuffer()],
+                        program.programId
+                    )[0],
+                    voterProfile: voterProfileFor(sybilRealm, dust.publicKey),
+                    voter: dust.publicKey,
+                    voterTokenAccount: dustAccount,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([dust])
+                .rpc();
+            expect.fail('a vote before the window opens should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('VotingNotStarted');
+        }
+        const proposal = await program.account.proposal.fetch(sybilProposal.publicKey);
+        expect(proposal.voterCount.toNumber()).to.equal(0);
+        expect(await provider.connection.getAccountInfo(voterProfileFor(sybilRealm, dust.publicKey))).to.be.null;
+    });
+
+    it('Clamps a whale vote to the configured cap', async () => {
+        // `cappedGovernance` caps each voter at 20% of locked supply; `whale` holds far more
+        const governanceAccount = await program.account.governanceState.fetch(cappedGovernance);
+        const cap = governanceAccount.totalLockedTokens.muln(2_000).divn(10_000);
+        const [whaleRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vote"), cappedProposal.toBuffer(), whale.publicKey.toBuffer()],
+            program.programId
+        );
+
+        await program.methods
+            .castVote({ yes: {} })
+            .accounts({
+                governance: cappedGovernance,
+                tokenMint: tokenMint,
+                proposal: cappedProposal,
+                voteRecord: whaleRecord,
+                voterProfile: voterProfileFor(cappedGovernance, whale.publicKey),
+                voter: whale.publicKey,
+                voterTokenAccount: whaleTokenAccount,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([whale])
+            .rpc();
+
+        const record = await program.account.voteRecord.fetch(whaleRecord);
+        expect(record.rawWeight.gt(cap)).to.be.true;
+        expect(record.weight.toString()).to.equal(cap.toString());
+
+        const proposalAccount = await program.account.proposal.fetch(cappedProposal);
+        expect(proposalAccount.yesVotes.toString()).to.equal(cap.toString());
+    });
+
+    it('Weighs the same holding linearly and quadratically', async () => {
+        // `quadraticGovernance` matches `governance` except for its quadratic voting mode;
+        // `linearProposal` and `quadraticProposal` are open proposals under each
+        const holding = (await getAccount(provider.connection, whaleTokenAccount)).amount;
+        const records = {};
+
+        for (const [gov, prop] of [[governance, linearProposal], [quadraticGovernance, quadraticProposal]]) {
+            const [record] = anchor.web3.PublicKey.findProgramAddressSync(
+                [Buffer.from("vote"), prop.toBuffer(), whale.publicKey.toBuffer()],
+                program.programId
+            );
+            await program.methods
+                .castVote({ yes: {} })
+                .accounts({
+                    governance: gov,
+                    tokenMint: tokenMint,
+                    proposal: prop,
+                    voteRecord: record,
+                    voterProfile: voterProfileFor(gov, whale.publicKey),
+                    voter: whale.publicKey,
+                    voterTokenAccount: whaleTokenAccount,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([whale])
+                .rpc();
+            records[prop.toString()] = await program.account.voteRecord.fetch(record);
+        }
+
+        const linear = records[linearProposal.toString()];
+        const quadratic = records[quadraticProposal.toString()];
+        const root = BigInt(Math.floor(Math.sqrt(Number(holding))));
+        expect(linear.weight.toString()).to.equal(holding.toString());
+        expect(quadratic.rawWeight.toString()).to.equal(holding.toString());
+        expect(BigInt(quadratic.weight.toString())).to.equal(root);
+    });
+
+    it('Extends voting when a late vote flips the outcome', async () => {
+        // `extensionGovernance` extends by 1 hour for flips in the final hour, up to 2 hours.
+        // `lateProposal` is inside its final hour with no ahead of yes; `lateWhale` flips it.
+        const before = await program.account.proposal.fetch(lateProposal);
+        const [lateRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vote"), lateProposal.toBuffer(), lateWhale.publicKey.toBuffer()],
+            program.programId
+        );
+
+        await program.methods
+            .castVote({ yes: {} })
+            .accounts({
+                governance: extensionGovernance,
+                tokenMint: tokenMint,
+                proposal: lateProposal,
+                voteRecord: lateRecord,
+                voterProfile: voterProfileFor(extensionGovernance, lateWhale.publicKey),
+                voter: lateWhale.publicKey,
+                voterTokenAccount: lateWhaleTokenAccount,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([lateWhale])
+            .rpc();
+
+        const after = await program.account.proposal.fetch(lateProposal);
+        expect(after.votingEndsAt.sub(before.votingEndsAt).toNumber()).to.equal(60 * 60);
+        expect(after.totalExtension.toNumber()).to.equal(60 * 60);
+    });
+
+    it('Stops extending once the maximum extension is used', async () => {
+        // `maxedProposal` has already been extended by the full 2 hours
+        const before = await program.account.proposal.fetch(maxedProposal);
+        const [maxedRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vote"), maxedProposal.toBuffer(), lateWhale.publicKey.toBuffer()],
+            program.programId
+        );
+
+        await program.methods
+            .castVote({ yes: {} })
+            .accounts({
+                governance: extensionGovernance,
+                tokenMint: tokenMint,
+                proposal: maxedProposal,
+                voteRecord: maxedRecord,
+                voterProfile: voterProfileFor(extensionGovernance, lateWhale.publicKey),
+                voter: lateWhale.publicKey,
+                voterTokenAccount: lateWhaleTokenAccount,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([lateWhale])
+            .rpc();
+
+        const after = await program.account.proposal.fetch(maxedProposal);
+        expect(after.votingEndsAt.toNumber()).to.equal(before.votingEndsAt.toNumber());
+        expect(after.totalExtension.toNumber()).to.equal(2 * 60 * 60);
+    });
+
+    it('Accepts only vetoes inside the veto window', async () => {
+        // `vetoWindowGovernance` has a 1 day veto window and `vetoWindowProposal` is inside it
+        const voteRecordFor = (voter: anchor.web3.PublicKey) => anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vote"), vetoWindowProposal.toBuffer(), voter.toBuffer()],
+            program.programId
+        )[0];
+        const vote = (choice: object, voter: anchor.web3.Keypair, voterTokenAccount: anchor.web3.PublicKey) => program.methods
+            .castVote(choice)
+            .accounts({
+                governance: vetoWindowGovernance,
+                tokenMint: tokenMint,
+                proposal: vetoWindowProposal,
+                voteRecord: voteRecordFor(voter.publicKey),
+                voterProfile: voterProfileFor(vetoWindowGovernance, voter.publicKey),
+                voter: voter.publicKey,
+                voterTokenAccount: voterTokenAccount,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([voter])
+            .rpc();
+
+        for (const choice of [{ yes: {} }, { no: {} }]) {
+            try {
+                await vote(choice, dustVoter, dustVoterTokenAccount);
+                expect.fail('yes/no votes should be rejected in the veto window');
+            } catch (err) {
+                expect(err.error.errorCode.code).to.equal('VetoWindowOnly');
+            }
+        }
+
+        await vote({ veto: {} }, thresholdVoter, thresholdVoterTokenAccount);
+
+        const proposalAccount = await program.account.proposal.fetch(vetoWindowProposal);
+        expect(proposalAccount.vetoVotes.toNumber()).to.be.above(0);
+        expect(proposalAccount.yesVotes.toNumber()).to.equal(0);
+    });
+
+    it('Extends voting by hand within the allowed maximum', async () => {
+        // `extendableProposal` is mid-voting; `longProposal` already spans 13 days of voting
+        const day = 24 * 60 * 60;
+        const extend = (target: anchor.web3.PublicKey, seconds: number) => program.methods
+            .extendVoting(new anchor.BN(seconds))
+            .accounts({
+                governance: governance,
+                proposal: target,
+                signer: provider.wallet.publicKey,
+            })
+            .rpc();
+
+        const before = await program.account.proposal.fetch(extendableProposal);
+        let event;
+        const listener = program.addEventListener('VotingPeriodExtended', (e) => { event = e; });
+        await extend(extendableProposal, day);
+        await program.removeEventListener(listener);
+
+        const after = await program.account.proposal.fetch(extendableProposal);
+        expect(after.votingEndsAt.sub(before.votingEndsAt).toNumber()).to.equal(day);
+        expect(event.votingEndsAt.toString()).to.equal(after.votingEndsAt.toString());
+        expect(event.extendedBy.toString()).to.equal(provider.wallet.publicKey.toString());
+
+        for (const [target, seconds] of [[extendableProposal, 4 * day], [longProposal, 2 * day]] as const) {
+            try {
+                await extend(target, seconds);
+                expect.fail('extension beyond the maximum should be rejected');
+            } catch (err) {
+                expect(err.error.errorCode.code).to.equal('ExtensionTooLong');
+            }
+        }
+    });
+
+    it('Waits for the emergency threshold before cancelling', async () => {
+        // Threshold is 80% of a three member council, so all three must approve
+        const [emergencyApproval] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("emergency"), emergencyProposal.toBuffer()],
+            program.programId
+        );
+
+        for (const member of [councilA, councilB]) {
+            await program.methods
+                .emergencyAction()
+                .accounts({
+                    governance: governance,
+                    proposal: emergencyProposal,
+                    emergencyApproval: emergencyApproval,
+                    emergencyCouncilMember: member.publicKey,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([member])
+                .rpc();
+        }
+
+        let proposalAccount = await program.account.proposal.fetch(emergencyProposal);
+        expect(proposalAccount.cancelled).to.be.false;
+
+        await program.methods
+            .emergencyAction()
+            .accounts({
+                governance: governance,
+                proposal: emergencyProposal,
+                emergencyApproval: emergencyApproval,
+                emergencyCouncilMember: councilC.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([councilC])
+            .rpc();
+
+        proposalAccount = await program.account.proposal.fetch(emergencyProposal);
+        expect(proposalAccount.cancelled).to.be.true;
+    });
+
+    it('Keeps votes on a cancelled proposal closed until its voting ends', async () => {
+        // A deposit-free realm whose only council member is the wallet, so one
+        // approval cancels and the cancelled proposal is terminal straight away
+        const existing = await program.account.governanceState.fetch(governance);
+        const [cancelGovernance] = realmAddress(5);
+        await program.methods
+            .createGovernance({ ...existing.config, proposalDeposit: new anchor.BN(0), proposalCooldown: new anchor.BN(0) }, [provider.wallet.publicKey], new anchor.BN(5))
+            .accounts({
+                governance: cancelGovernance,
+                tokenMint: tokenMint,
+                economics: economics,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocolTreasury,
+                authority: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+        const [cancelVaultAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vault_authority"), cancelGovernance.toBuffer()],
+            program.programId
+        );
+        const cancelVault = await createAccount(
+            provider.connection,
+            await provider.wallet.payer,
+            tokenMint,
+            cancelVaultAuthority,
+            anchor.web3.Keypair.generate()
+        );
+
+        const cancelled = anchor.web3.Keypair.generate();
+        await program.methods
+            .createProposal({ operational: {} }, "Cancelled", "Proposal cancelled before voting ends", "", null, null, null)
+            .accounts({
+                governance: cancelGovernance,
+                proposal: cancelled.publicKey,
+                proposerRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("proposer"), cancelGovernance.toBuffer(), provider.wallet.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: cancelVaultAuthority,
+                depositVault: cancelVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([cancelled])
+            .rpc();
+        await program.methods
+            .emergencyAction()
+            .accounts({
+                governance: cancelGovernance,
+                proposal: cancelled.publicKey,
+                emergencyApproval: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("emergency"), cancelled.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                emergencyCouncilMember: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        // Still inside its voting window, so the keeper leaves it alone
+        await program.methods
+            .closeTerminalAccounts()
+            .accounts({ governance: cancelGovernance, tokenMint: tokenMint, keeper: provider.wallet.publicKey })
+            .remainingAccounts([
+                { pubkey: cancelled.publicKey, isWritable: true, isSigner: false },
+                { pubkey: provider.wallet.publicKey, isWritable: true, isSigner: false },
+            ])
+            .rpc();
+        expect(await provider.connection.getAccountInfo(cancelled.publicKey)).to.not.be.null;
+
+        // And no vote can be cast, so a closed record could never be re-cast either
+        try {
+            await program.methods
+                .castVote({ yes: {} })
+                .accounts({
+                    governance: cancelGovernance,
+                    tokenMint: tokenMint,
+                    proposal: cancelled.publicKey,
+                    voteRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                        [Buffer.from("vote"), cancelled.publicKey.toBuffer(), provider.wallet.publicKey.toBuffer()],
+                        program.programId
+                    )[0],
+                    voterProfile: voterProfileFor(cancelGovernance, provider.wallet.publicKey),
+                    voter: provider.wallet.publicKey,
+                    voterTokenAccount: voterTokenAccount,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .rpc();
+            expect.fail('a cancelled proposal should not take votes');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ProposalCancelled');
+        }
+    });
+
+    it('Adds and removes council members without gaps', async () => {
+        // `councilGovernance` starts with an empty council
+        const members = [0, 1, 2, 3].map(() => anchor.web3.Keypair.generate().publicKey);
+        const update = (method: 'addCouncilMember' | 'removeCouncilMember', member: anchor.web3.PublicKey) =>
+            program.methods[method](member)
+                .accounts({ governance: councilGovernance, authority: provider.wallet.publicKey })
+                .rpc();
+
+        for (const member of members) {
+            await update('addCouncilMember', member);
+        }
+        await update('removeCouncilMember', members[1]);
+
+        let governanceAccount = await program.account.governanceState.fetch(councilGovernance);
+        expect(governanceAccount.emergencyCouncil.map((key) => key.toString()))
+            .to.deep.equal([members[0], members[2], members[3]].map((key) => key.toString()));
 
-#[error_code]
-pub enum GovernanceError {
-    #[msg("Invalid voting period")]
-    InvalidVotingPeriod,
-    #[msg("Invalid voting delay")]
-    InvalidVotingDelay,
-    #[msg("Invalid quorum percentage")]
-    InvalidQuorum,
-    #[msg("Insufficient tokens to create proposal")]
-    InsufficientTokens,
-    #[msg("Voting has not started yet")]
-    VotingNotStarted,
-    #[msg("Voting has already ended")]
-    VotingEnded,
-    #[msg("Voting period has not ended yet")]
-    VotingNotEnded,
-    #[msg("Proposal has already been executed")]
-    AlreadyExecuted,
-    #[msg("Proposal has been cancelled")]
-    ProposalCancelled,
-    #[msg("Quorum has not been reached")]
-    QuorumNotReached,
-    #[msg("Proposal did not pass")]
-    ProposalNotPassed,
-    #[msg("Proposal was vetoed")]
-    ProposalVetoed,
-    #[msg("Invalid emergency action")]
-    InvalidEmergencyAction,
-}
+        try {
+            await update('addCouncilMember', members[0]);
+            expect.fail('duplicate member should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('AlreadyCouncilMember');
+        }
 
-// Save as: tests/governance.ts
-import * as anchor from '@project-serum/anchor';
-import { Program } from '@project-serum/anchor';
-import { NexusGovernance } from '../target/types/nexus_governance';
-import { expect } from 'chai';
+        // Fill to capacity, then one more
+        for (let i = governanceAccount.emergencyCouncil.length; i < 10; i++) {
+            await update('addCouncilMember', anchor.web3.Keypair.generate().publicKey);
+        }
+        try {
+            await update('addCouncilMember', anchor.web3.Keypair.generate().publicKey);
+            expect.fail('council over capacity should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('EmergencyCouncilFull');
+        }
 
-describe('nexus-governance', () => {
-    const provider = anchor.AnchorProvider.env();
-    anchor.setProvider(provider);
+        governanceAccount = await program.account.governanceState.fetch(councilGovernance);
+        expect(governanceAccount.emergencyCouncil.length).to.equal(10);
+    });
 
-    const program = anchor.workspace.NexusGovernance as Program<NexusGovernance>;
-    let governance: anchor.web3.PublicKey;
-    let proposal: anchor.web3.PublicKey;
+    it('Forfeits a vetoed proposal deposit into economics', async () => {
+        // `vetoedProposal` closed voting with veto weight cast against it
+        const economicsProgram = anchor.workspace.NexusEconomics as Program<NexusEconomics>;
+        const before = await economicsProgram.account.economicsState.fetch(economics);
+        const settle = (protocol: anchor.web3.PublicKey) => program.methods
+            .settleDeposit()
+            .accounts({
+                governance: governance,
+                proposal: vetoedProposal,
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: vaultAuthority,
+                depositVault: depositVault,
+                economics: economics,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocol,
+                tokenMint: tokenMint,
+                economicsProgram: economicsProgram.programId,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
 
-    it('Creates governance', async () => {
-        const config = {
-            votingDelay: new anchor.BN(24 * 60 * 60),    // 1 day
-            votingPeriod: new anchor.BN(5 * 24 * 60 * 60), // 5 days
-            quorumPercentage: 10,                        // 10%
-            proposalThreshold: new anchor.BN(100000),    // 100,000 tokens
-            emergencyThreshold: 80,                      // 80%
-        };
+        // Only the treasuries registered with the realm can receive the deposit
+        try {
+            await settle(proposerTokenAccount);
+            expect.fail('a deposit should not be forfeited to an unregistered account');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('EconomicsMismatch');
+        }
 
-        const tx = await program.methods
-            .createGovernance(config)
+        await settle(protocolTreasury);
+
+        const after = await economicsProgram.account.economicsState.fetch(economics);
+        const proposalAccount = await program.account.proposal.fetch(vetoedProposal);
+        expect(proposalAccount.depositSettled).to.be.true;
+        expect(after.totalFeesCollected.sub(before.totalFeesCollected).toNumber())
+            .to.equal(proposalAccount.deposit.toNumber());
+    });
+
+    it('Creates a proposal with an elevated quorum', async () => {
+        const elevated = anchor.web3.Keypair.generate();
+
+        await program.methods
+            .createProposal({ core: {} }, "Sensitive", "Needs wider turnout", "https://docs.nexus.ai/proposals/4", 40, null, null)
+            .accounts({
+                governance: quorumGovernance,
+                proposal: elevated.publicKey,
+                proposerRecord: quorumProposerRecord,
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: vaultAuthority,
+                depositVault: depositVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([elevated])
+            .rpc();
+
+        const proposalAccount = await program.account.proposal.fetch(elevated.publicKey);
+        expect(proposalAccount.quorum).to.equal(40);
+    });
+
+    it('Stamps the per-type quorum on new proposals', async () => {
+        // `typedQuorumGovernance` has a 10% quorum with a 30% override for Core proposals
+        // and no proposal cooldown
+        const [record] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("proposer"), typedQuorumGovernance.toBuffer(), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const quorums = {};
+
+        for (const proposalType of [{ core: {} }, { operational: {} }]) {
+            const next = anchor.web3.Keypair.generate();
+            await program.methods
+                .createProposal(proposalType, "Typed quorum", "Quorum depends on the proposal type", "https://docs.nexus.ai/proposals/5", null, null, null)
+                .accounts({
+                    governance: typedQuorumGovernance,
+                    proposal: next.publicKey,
+                    proposerRecord: record,
+                    proposer: provider.wallet.publicKey,
+                    proposerTokenAccount: proposerTokenAccount,
+                    vaultAuthority: vaultAuthority,
+                    depositVault: depositVault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([next])
+                .rpc();
+            quorums[Object.keys(proposalType)[0]] = (await program.account.proposal.fetch(next.publicKey)).quorum;
+        }
+
+        expect(quorums['core']).to.equal(30);
+        expect(quorums['operational']).to.equal(10);
+    });
+
+    it('Takes the per-type deposit on new proposals', async () => {
+        // `typedDepositGovernance` takes 1,000 by default and 5,000 for Core proposals,
+        // with no proposal cooldown
+        const [record] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("proposer"), typedDepositGovernance.toBuffer(), provider.wallet.publicKey.toBuffer()],
+            program.programId
+        );
+        const taken = {};
+
+        for (const proposalType of [{ core: {} }, { operational: {} }]) {
+            const next = anchor.web3.Keypair.generate();
+            const before = await provider.connection.getTokenAccountBalance(depositVault);
+            await program.methods
+                .createProposal(proposalType, "Typed deposit", "Deposit depends on the proposal type", "https://docs.nexus.ai/proposals/7", null, null, null)
+                .accounts({
+                    governance: typedDepositGovernance,
+                    proposal: next.publicKey,
+                    proposerRecord: record,
+                    proposer: provider.wallet.publicKey,
+                    proposerTokenAccount: proposerTokenAccount,
+                    vaultAuthority: vaultAuthority,
+                    depositVault: depositVault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([next])
+                .rpc();
+            const after = await provider.connection.getTokenAccountBalance(depositVault);
+            const proposalAccount = await program.account.proposal.fetch(next.publicKey);
+            const escrowed = Number(after.value.amount) - Number(before.value.amount);
+            expect(proposalAccount.deposit.toNumber()).to.equal(escrowed);
+            taken[Object.keys(proposalType)[0]] = escrowed;
+        }
+
+        expect(taken['core']).to.equal(5_000);
+        expect(taken['operational']).to.equal(1_000);
+    });
+
+    it('Rejects a quorum override below the configured quorum', async () => {
+        const lowered = anchor.web3.Keypair.generate();
+
+        try {
+            await program.methods
+                .createProposal({ core: {} }, "Lowered", "Quorum below config", "https://docs.nexus.ai/proposals/5", 5, null, null)
+                .accounts({
+                    governance: quorumGovernance,
+                    proposal: lowered.publicKey,
+                    proposerRecord: quorumProposerRecord,
+                    proposer: provider.wallet.publicKey,
+                    proposerTokenAccount: proposerTokenAccount,
+                    vaultAuthority: vaultAuthority,
+                    depositVault: depositVault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([lowered])
+                .rpc();
+            expect.fail('override below the configured quorum should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidQuorum');
+        }
+    });
+
+    it('Requires the elevated turnout to execute', async () => {
+        // `elevatedQuorumProposal` asked for 40% quorum and closed with 20% turnout,
+        // which would have passed the governance-wide 10%
+        try {
+            await program.methods
+                .executeProposal()
+                .accounts({
+                    governance: governance,
+                    tokenMint: tokenMint,
+                    proposal: elevatedQuorumProposal,
+                    executor: provider.wallet.publicKey,
+                })
+                .rpc();
+            expect.fail('turnout below the elevated quorum should not execute');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('QuorumNotReached');
+        }
+    });
+
+    it('Refuses to execute against zero voting supply', async () => {
+        // `emptyGovernance` has no locked tokens, so its quorum threshold would be zero
+        try {
+            await program.methods
+                .executeProposal()
+                .accounts({
+                    governance: emptyGovernance,
+                    tokenMint: tokenMint,
+                    proposal: emptySupplyProposal,
+                    executor: provider.wallet.publicKey,
+                })
+                .rpc();
+            expect.fail('zero supply should not execute');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('NoVotingSupply');
+        }
+    });
+
+    it('Refuses to execute a proposal nobody voted on', async () => {
+        // `silentProposal` closed voting without a single vote
+        try {
+            await program.methods
+                .executeProposal()
+                .accounts({
+                    governance: governance,
+                    tokenMint: tokenMint,
+                    proposal: silentProposal,
+                    executor: provider.wallet.publicKey,
+                })
+                .rpc();
+            expect.fail('zero turnout should not execute');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('NoVotesCast');
+        }
+    });
+
+    it('Requires the absolute quorum floor as well as the percentage', async () => {
+        // `floorGovernance` has a 10% quorum and a 1,000,000 token floor. `flooredProposal`
+        // closed with 15% of the locked supply voting yes, but fewer tokens than the floor.
+        const proposalAccount = await program.account.proposal.fetch(flooredProposal);
+        expect(proposalAccount.quorumFloor.toNumber()).to.equal(1_000_000);
+
+        try {
+            await program.methods
+                .executeProposal()
+                .accounts({
+                    governance: floorGovernance,
+                    tokenMint: tokenMint,
+                    proposal: flooredProposal,
+                    executor: provider.wallet.publicKey,
+                })
+                .rpc();
+            expect.fail('turnout below the absolute floor should not execute');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('QuorumNotReached');
+        }
+    });
+
+    it('Measures quorum against the configured supply basis', async () => {
+        // `basisProposal` drew 10% of locked supply but well under 10% of total supply.
+        // `lockedGovernance` and `supplyGovernance` differ only in quorum_basis.
+        await program.methods
+            .executeProposal()
+            .accounts({
+                governance: lockedGovernance,
+                tokenMint: tokenMint,
+                proposal: basisProposal,
+                executor: provider.wallet.publicKey,
+            })
+            .rpc();
+
+        try {
+            await program.methods
+                .executeProposal()
+                .accounts({
+                    governance: supplyGovernance,
+                    tokenMint: tokenMint,
+                    proposal: supplyBasisProposal,
+                    executor: provider.wallet.publicKey,
+                })
+                .rpc();
+            expect.fail('same turnout should miss quorum against total supply');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('QuorumNotReached');
+        }
+    });
+
+    it('Executes a custom proposal type at its approval threshold', async () => {
+        // `treasuryGovernance` defines custom type 0 as "Treasury" requiring 60% approval,
+        // and `treasuryProposal` closed with exactly 60% yes
+        const governanceAccount = await program.account.governanceState.fetch(treasuryGovernance);
+        expect(governanceAccount.config.customProposalTypes[0].name).to.equal("Treasury");
+        expect(governanceAccount.config.customProposalTypes[0].approvalThreshold).to.equal(60);
+
+        await program.methods
+            .executeProposal()
+            .accounts({
+                governance: treasuryGovernance,
+                tokenMint: tokenMint,
+                proposal: treasuryProposal,
+                executor: provider.wallet.publicKey,
+            })
+            .rpc();
+
+        const proposalAccount = await program.account.proposal.fetch(treasuryProposal);
+        expect(proposalAccount.proposalType).to.deep.equal({ custom: { 0: 0 } });
+        expect(proposalAccount.executed).to.be.true;
+    });
+
+    it('Reports an exact tie as defeated', async () => {
+        // `tiedProposal` ended with equal yes and no weight
+        try {
+            await program.methods
+                .executeProposal()
+                .accounts({
+                    governance: governance,
+                    tokenMint: tokenMint,
+                    proposal: tiedProposal,
+                    executor: provider.wallet.publicKey,
+                })
+                .rpc();
+            expect.fail('tied proposal should not execute');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('ProposalTied');
+        }
+
+        const proposalAccount = await program.account.proposal.fetch(tiedProposal);
+        expect(proposalAccount.executed).to.be.false;
+    });
+
+    it('Returns vote receipts before and after voting', async () => {
+        const voter = anchor.web3.Keypair.generate();
+        const [voteRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vote"), openProposal.toBuffer(), voter.publicKey.toBuffer()],
+            program.programId
+        );
+        const query = () => program.methods
+            .getVoteReceipt()
+            .accounts({ proposal: openProposal, voter: voter.publicKey, voteRecord: voteRecord })
+            .view();
+
+        const before = await query();
+        expect(before.voted).to.be.false;
+        expect(before.vote).to.be.null;
+
+        await program.methods
+            .castVote({ no: {} })
             .accounts({
                 governance: governance,
-                authority: provider.wallet.publicKey,
+                tokenMint: tokenMint,
+                proposal: openProposal,
+                voteRecord: voteRecord,
+                voterProfile: voterProfileFor(governance, voter.publicKey),
+                voter: voter.publicKey,
+                voterTokenAccount: secondVoterTokenAccount,
                 systemProgram: anchor.web3.SystemProgram.programId,
             })
+            .signers([voter])
             .rpc();
 
-        const governanceAccount = await program.account.governanceState.fetch(governance);
-        expect(governanceAccount.config.quorumPercentage).to.equal(config.quorumPercentage);
+        const after = await query();
+        expect(after.voted).to.be.true;
+        expect(after.vote).to.deep.equal({ no: {} });
     });
 
-    it('Creates proposal', async () => {
-        const proposalType = { core: {} };
-        const title = "Test Proposal";
-        const description = "This is a test proposal";
-        const link = "https://docs.nexus.ai/proposals/1";
+    it('Checks the owner and deployment of a lock before counting its vote', async () => {
+        // `voterLock` is locked for a year in the economics deployment of a fresh realm;
+        // `foreignLock` has the same owner but sits in another deployment
+        const economicsProgram = anchor.workspace.NexusEconomics as Program<NexusEconomics>;
+        const { governance: lockRealm, vaultAuthority: lockVaultAuthority, depositVault: lockDepositVault } =
+            await newRealm(8, { proposalCooldown: new anchor.BN(0) });
+        const { owner: lockVoter, lock: voterLock } = await lockFor(economics, 1_000, 365 * 24 * 60 * 60);
 
-        const tx = await program.methods
-            .createProposal(
-                proposalType,
-                title,
-                description,
-                link
-            )
+        const foreignAuthority = anchor.web3.Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(foreignAuthority.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+        );
+        const [foreignEconomics] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("economics"), foreignAuthority.publicKey.toBuffer()],
+            economicsProgram.programId
+        );
+        await economicsProgram.methods
+            .initializeEconomics((await economicsProgram.account.economicsState.fetch(economics)).config)
+            .accounts({
+                economics: foreignEconomics,
+                tokenMint: tokenMint,
+                authority: foreignAuthority.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([foreignAuthority])
+            .rpc();
+        const { lock: foreignLock } = await lockFor(foreignEconomics, 1_000, 365 * 24 * 60 * 60, lockVoter);
+
+        const target = anchor.web3.Keypair.generate();
+        await program.methods
+            .createProposal({ operational: {} }, "Lock vote", "Proposal voted on with locks", "", null, null, null)
+            .accounts({
+                governance: lockRealm,
+                proposal: target.publicKey,
+                proposerRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("proposer"), lockRealm.toBuffer(), provider.wallet.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: lockVaultAuthority,
+                depositVault: lockDepositVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([target])
+            .rpc();
+
+        const voteWithLock = (voter: anchor.web3.Keypair, lock = voterLock) => program.methods
+            .castVoteWithProof({ yes: {} })
+            .accounts({
+                governance: lockRealm,
+                tokenMint: tokenMint,
+                proposal: target.publicKey,
+                voteRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("vote"), target.publicKey.toBuffer(), voter.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                voterProfile: voterProfileFor(lockRealm, voter.publicKey),
+                voter: voter.publicKey,
+                lock: lock,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([voter])
+            .rpc();
+        const stranger = anchor.web3.Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(stranger.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+        );
+
+        // Someone else's lock carries no weight
+        try {
+            await voteWithLock(stranger);
+            expect.fail('voting with a lock owned by someone else should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('LockOwnerMismatch');
+        }
+
+        // Nor does a lock held in another deployment's custody
+        try {
+            await voteWithLock(lockVoter, foreignLock);
+            expect.fail('voting with a lock from another economics deployment should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('LockRealmMismatch');
+        }
+
+        // The voter's own lock passes both checks and only waits on the voting window
+        try {
+            await voteWithLock(lockVoter);
+            expect.fail('a vote before the window opens should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('VotingNotStarted');
+        }
+    });
+
+    it('Bounds the lock duration boost and applies it only once voting opens', async () => {
+        // A boost past double weight is rejected when the realm is created
+        try {
+            await newRealm(12, { lockDurationBoostBps: 10_001 });
+            expect.fail('a lock duration boost above 10,000 bps should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidLockDurationBoost');
+        }
+
+        const { governance: durationRealm, vaultAuthority: durationVaultAuthority, depositVault: durationDepositVault } =
+            await newRealm(12, { proposalCooldown: new anchor.BN(0), lockDurationBoostBps: 10_000 });
+        expect((await program.account.governanceState.fetch(durationRealm)).config.lockDurationBoostBps).to.equal(10_000);
+
+        const { owner: longLocker, lock: longLock } = await lockFor(economics, 1_000, 4 * 365 * 24 * 60 * 60);
+        const durationProposal = anchor.web3.Keypair.generate();
+        await program.methods
+            .createProposal({ operational: {} }, "Duration", "Proposal voted on with a long lock", "", null, null, null)
+            .accounts({
+                governance: durationRealm,
+                proposal: durationProposal.publicKey,
+                proposerRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("proposer"), durationRealm.toBuffer(), provider.wallet.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: durationVaultAuthority,
+                depositVault: durationDepositVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([durationProposal])
+            .rpc();
+
+        // Voting opens a day after creation at the earliest, so the boosted vote is
+        // refused and nothing is tallied yet
+        const [voteRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vote"), durationProposal.publicKey.toBuffer(), longLocker.publicKey.toBuffer()],
+            program.programId
+        );
+        try {
+            await program.methods
+                .castVoteWithProof({ yes: {} })
+                .accounts({
+                    governance: durationRealm,
+                    tokenMint: tokenMint,
+                    proposal: durationProposal.publicKey,
+                    voteRecord: voteRecord,
+                    voterProfile: voterProfileFor(durationRealm, longLocker.publicKey),
+                    voter: longLocker.publicKey,
+                    lock: longLock,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([longLocker])
+                .rpc();
+            expect.fail('a vote before the window opens should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('VotingNotStarted');
+        }
+        expect(await provider.connection.getAccountInfo(voteRecord)).to.be.null;
+        const proposal = await program.account.proposal.fetch(durationProposal.publicKey);
+        expect(proposal.yesVotes.toNumber()).to.equal(0);
+    });
+
+    it('Counts participation only for votes that are recorded', async () => {
+        // A fresh realm whose proposals open for voting a day after creation at the
+        // earliest, so a vote cast straight away is rejected
+        const { governance: participationRealm, vaultAuthority: participationVaultAuthority, depositVault: participationDepositVault } =
+            await newRealm(7, { proposalCooldown: new anchor.BN(0) });
+        const early = anchor.web3.Keypair.generate();
+        await program.methods
+            .createProposal({ operational: {} }, "Participation", "Proposal voted on before its window", "", null, null, null)
+            .accounts({
+                governance: participationRealm,
+                proposal: early.publicKey,
+                proposerRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("proposer"), participationRealm.toBuffer(), provider.wallet.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: participationVaultAuthority,
+                depositVault: participationDepositVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([early])
+            .rpc();
+
+        const voter = anchor.web3.Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(voter.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+        );
+        const voterAccount = await createAccount(provider.connection, await provider.wallet.payer, tokenMint, voter.publicKey);
+        const participation = () => program.methods
+            .getParticipation()
+            .accounts({
+                governance: participationRealm,
+                voter: voter.publicKey,
+                voterProfile: voterProfileFor(participationRealm, voter.publicKey),
+            })
+            .view();
+
+        expect((await participation()).toNumber()).to.equal(0);
+
+        try {
+            await program.methods
+                .castVote({ yes: {} })
+                .accounts({
+                    governance: participationRealm,
+                    tokenMint: tokenMint,
+                    proposal: early.publicKey,
+                    voteRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                        [Buffer.from("vote"), early.publicKey.toBuffer(), voter.publicKey.toBuffer()],
+                        program.programId
+                    )[0],
+                    voterProfile: voterProfileFor(participationRealm, voter.publicKey),
+                    voter: voter.publicKey,
+                    voterTokenAccount: voterAccount,
+                    systemProgram: anchor.web3.SystemProgram.programId,
+                })
+                .signers([voter])
+                .rpc();
+            expect.fail('a vote before the window opens should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('VotingNotStarted');
+        }
+
+        // The rejected vote left no profile behind
+        expect((await participation()).toNumber()).to.equal(0);
+        expect(await provider.connection.getAccountInfo(voterProfileFor(participationRealm, voter.publicKey))).to.be.null;
+    });
+
+    it('Rewards voters for participation and pays non-voters nothing', async () => {
+        const [participationRewards] = anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("participation"), governance.toBuffer()],
+            program.programId
+        );
+        // The reward vault is owned by the participation PDA and funded with 1,000,000 tokens
+        const payer = await provider.wallet.payer;
+        const participationVault = await createAccount(
+            provider.connection,
+            payer,
+            tokenMint,
+            participationRewards,
+            anchor.web3.Keypair.generate()
+        );
+        await transfer(provider.connection, payer, voterTokenAccount, participationVault, provider.wallet.publicKey, 1_000_000);
+        const rewardPerVote = 100;
+
+        await program.methods
+            .configureParticipationRewards(new anchor.BN(rewardPerVote), new anchor.BN(30 * 24 * 60 * 60), new anchor.BN(10_000))
             .accounts({
                 governance: governance,
-                proposal: proposal,
+                participationRewards: participationRewards,
+                authority: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+
+        const claim = (voter: anchor.web3.PublicKey, voterTokenAccount: anchor.web3.PublicKey, signers: anchor.web3.Keypair[]) =>
+            program.methods
+                .claimParticipationReward()
+                .accounts({
+                    governance: governance,
+                    participationRewards: participationRewards,
+                    voterProfile: voterProfileFor(governance, voter),
+                    voter: voter,
+                    rewardVault: participationVault,
+                    voterTokenAccount: voterTokenAccount,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers(signers)
+                .rpc();
+
+        // The wallet voted on `proposal` earlier in this suite
+        const profile = await program.account.voterProfile.fetch(voterProfileFor(governance, provider.wallet.publicKey));
+        const balanceBefore = (await getAccount(provider.connection, voterTokenAccount)).amount;
+        await claim(provider.wallet.publicKey, voterTokenAccount, []);
+        const balanceAfter = (await getAccount(provider.connection, voterTokenAccount)).amount;
+        expect(Number(balanceAfter - balanceBefore)).to.equal(profile.qualifyingVotes.toNumber() * rewardPerVote);
+
+        // Every vote is rewarded once
+        try {
+            await claim(provider.wallet.publicKey, voterTokenAccount, []);
+            expect.fail('a second claim without new votes should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('NoParticipationToReward');
+        }
+
+        // A wallet that never voted has no profile to claim against
+        const nonVoter = anchor.web3.Keypair.generate();
+        const vaultBefore = (await getAccount(provider.connection, participationVault)).amount;
+        try {
+            await claim(nonVoter.publicKey, secondVoterTokenAccount, [nonVoter]);
+            expect.fail('a non-voter should not earn a participation reward');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('AccountNotInitialized');
+        }
+        const vaultAfter = (await getAccount(provider.connection, participationVault)).amount;
+        expect(vaultAfter).to.equal(vaultBefore);
+    });
+
+    it('Accepts an execution batch at the cap and rejects one over it', async () => {
+        // `openProposal` is still voting, so every entry is skipped rather than executed
+        const batch = (size: number) => program.methods
+            .executeProposalsBatch()
+            .accounts({
+                governance: governance,
+                tokenMint: tokenMint,
+                executor: provider.wallet.publicKey,
+            })
+            .remainingAccounts(Array(size).fill({ pubkey: openProposal, isWritable: true, isSigner: false }));
+
+        await batch(10).rpc();
+
+        try {
+            await batch(11).rpc();
+            expect.fail('a batch over the cap should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('BatchTooLarge');
+        }
+    });
+
+    it('Bounds per-type timelocks and queues nothing before voting ends', async () => {
+        // A realm with a 7 day core and 1 day operational delay
+        const day = 24 * 60 * 60;
+        const delays = (core: number) => ({
+            core: new anchor.BN(core),
+            technical: new anchor.BN(0),
+            operational: new anchor.BN(day),
+            custom: new anchor.BN(0),
+        });
+
+        try {
+            await newRealm(10, { executionDelays: delays(31 * day) });
+            expect.fail('a timelock above 30 days should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidExecutionDelay');
+        }
+
+        const { governance: timelockGovernance, vaultAuthority: timelockVaultAuthority, depositVault: timelockVault } =
+            await newRealm(9, { proposalCooldown: new anchor.BN(0), executionDelays: delays(7 * day) });
+        const realm = await program.account.governanceState.fetch(timelockGovernance);
+        expect(realm.config.executionDelays.core.toNumber()).to.equal(7 * day);
+        expect(realm.config.executionDelays.operational.toNumber()).to.equal(day);
+
+        const core = anchor.web3.Keypair.generate();
+        await program.methods
+            .createProposal({ core: {} }, "Timelocked", "Core proposal behind a timelock", "", null, null, null)
+            .accounts({
+                governance: timelockGovernance,
+                proposal: core.publicKey,
+                proposerRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("proposer"), timelockGovernance.toBuffer(), provider.wallet.publicKey.toBuffer()],
+                    program.programId
+                )[0],
                 proposer: provider.wallet.publicKey,
                 proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: timelockVaultAuthority,
+                depositVault: timelockVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
                 systemProgram: anchor.web3.SystemProgram.programId,
             })
+            .signers([core])
             .rpc();
 
-        const proposalAccount = await program.account.proposal.fetch(proposal);
-        expect(proposalAccount.title).to.equal(title);
+        // The timelock only starts once the vote has passed
+        try {
+            await program.methods
+                .queueProposal()
+                .accounts({ governance: timelockGovernance, tokenMint: tokenMint, proposal: core.publicKey })
+                .rpc();
+            expect.fail('a proposal still voting should not be queued');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('VotingNotEnded');
+        }
+        const unqueued = await program.account.proposal.fetch(core.publicKey);
+        expect(unqueued.eta.toNumber()).to.equal(0);
     });
 
-    it('Casts vote', async () => {
-        const vote = { yes: {} };
+    it('Batch-closes only paired entries, skipping live proposals', async () => {
+        // A fresh realm with a proposal that has only just been created
+        const { governance: closeRealm, vaultAuthority: closeVaultAuthority, depositVault: closeVault } =
+            await newRealm(11, { proposalCooldown: new anchor.BN(0) });
+        const live = anchor.web3.Keypair.generate();
+        await program.methods
+            .createProposal({ operational: {} }, "Live", "Proposal that is still voting", "", null, null, null)
+            .accounts({
+                governance: closeRealm,
+                proposal: live.publicKey,
+                proposerRecord: anchor.web3.PublicKey.findProgramAddressSync(
+                    [Buffer.from("proposer"), closeRealm.toBuffer(), provider.wallet.publicKey.toBuffer()],
+                    program.programId
+                )[0],
+                proposer: provider.wallet.publicKey,
+                proposerTokenAccount: proposerTokenAccount,
+                vaultAuthority: closeVaultAuthority,
+                depositVault: closeVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .signers([live])
+            .rpc();
+        const entry = (pubkey: anchor.web3.PublicKey) => ({ pubkey, isWritable: true, isSigner: false });
+        const close = (remaining: ReturnType<typeof entry>[]) => program.methods
+            .closeTerminalAccounts()
+            .accounts({ governance: closeRealm, tokenMint: tokenMint, keeper: provider.wallet.publicKey })
+            .remainingAccounts(remaining)
+            .rpc();
+
+        // Every account needs the recipient of its rent next to it
+        try {
+            await close([entry(live.publicKey)]);
+            expect.fail('an account without a recipient should be rejected');
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal('InvalidCloseBatch');
+        }
+
+        await close([entry(live.publicKey), entry(provider.wallet.publicKey)]);
+        expect(await provider.connection.getAccountInfo(live.publicKey)).to.not.be.null;
+    });
 
+    it('Executes a batch of proposals, skipping ineligible ones', async () => {
+        // `proposal` has ended voting and passed; `openProposal` is still in its voting window
         const tx = await program.methods
-            .castVote(vote)
+            .executeProposalsBatch()
             .accounts({
                 governance: governance,
-                proposal: proposal,
-                voteRecord: voteRecord,
-                voter: provider.wallet.publicKey,
-                voterTokenAccount: voterTokenAccount,
-                systemProgram: anchor.web3.SystemProgram.programId,
+                tokenMint: tokenMint,
+                executor: provider.wallet.publicKey,
             })
+            .remainingAccounts([
+                { pubkey: proposal, isWritable: true, isSigner: false },
+                { pubkey: openProposal, isWritable: true, isSigner: false },
+            ])
             .rpc();
 
-        const proposalAccount = await program.account.proposal.fetch(proposal);
-        expect(proposalAccount.yesVotes.toNumber()).to.be.above(0);
+        const executed = await program.account.proposal.fetch(proposal);
+        const skipped = await program.account.proposal.fetch(openProposal);
+        expect(executed.executed).to.be.true;
+        expect(skipped.executed).to.be.false;
     });
 });
 
@@ -425,22 +4219,52 @@ async function main() {
         quorumPercentage: 10,                        // 10%
         proposalThreshold: new anchor.BN(100000),    // 100,000 tokens
         emergencyThreshold: 80,                      // 80%
+        proposalCooldown: new anchor.BN(24 * 60 * 60), // 1 day
+        proposalDeposit: new anchor.BN(1000),         // 1,000 tokens
+        quorumBasis: { lockedSupply: {} },
+        quorumFloor: new anchor.BN(0),
+        minVoteWeight: new anchor.BN(0),
+        voteWeightCap: { none: {} },
+        voteExtension: { window: new anchor.BN(0), extension: new anchor.BN(0), maxTotalExtension: new anchor.BN(0) },
+        vetoWindow: new anchor.BN(0),
+        minDescriptionLen: 0,
+        votingMode: { linear: {} },
+        typeQuorums: { core: null, technical: null, operational: null },
+        typeDeposits: { core: null, technical: null, operational: null },
+        allowedLinkPrefixes: [],
+        customProposalTypes: [],
+        executionDelays: { core: new anchor.BN(0), technical: new anchor.BN(0), operational: new anchor.BN(0), custom: new anchor.BN(0) },
+        lockDurationBoostBps: 0,
+        voterCountMinWeight: new anchor.BN(0),
+        lockProposerThreshold: false,
     };
 
-    const governance = anchor.web3.Keypair.generate();
+    const [governance] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("governance"), provider.wallet.publicKey.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+        program.programId
+    );
+    const tokenMint = new anchor.web3.PublicKey(process.env.NEXUS_TOKEN_MINT);
+    const economics = new anchor.web3.PublicKey(process.env.NEXUS_ECONOMICS_STATE);
+    const venexusTreasury = new anchor.web3.PublicKey(process.env.NEXUS_VENEXUS_TREASURY);
+    const ainexusTreasury = new anchor.web3.PublicKey(process.env.NEXUS_AINEXUS_TREASURY);
+    const protocolTreasury = new anchor.web3.PublicKey(process.env.NEXUS_PROTOCOL_TREASURY);
 
     try {
         const tx = await program.methods
-            .createGovernance(config)
+            .createGovernance(config, [], new anchor.BN(0))
             .accounts({
-                governance: governance.publicKey,
+                governance: governance,
+                tokenMint: tokenMint,
+                economics: economics,
+                venexusTreasury: venexusTreasury,
+                ainexusTreasury: ainexusTreasury,
+                protocolTreasury: protocolTreasury,
                 authority: provider.wallet.publicKey,
                 systemProgram: anchor.web3.SystemProgram.programId,
             })
-            .signers([governance])
             .rpc();
 
-        console.log("Governance deployed at:", governance.publicKey.toString());
+        console.log("Governance deployed at:", governance.toString());
         console.log("Transaction signature:", tx);
     } catch (error) {
         console.error("Deployment failed:", error);
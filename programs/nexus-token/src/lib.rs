@@ -1,7 +1,10 @@
 // Save as: programs/nexus-token/src/lib.rs
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::system_program;
 use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use nexus_common::{apply_bps, mul_div, Rounding, TokenAmount};
 
 declare_id!("NEXUSxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
@@ -38,16 +41,66 @@ pub mod nexus_token {
         Ok(())
     }
 
+    // Records the supply ceiling that `mint_tokens` enforces. Only the current
+    // mint authority can set it, and never below the supply already minted.
+    pub fn initialize_token_state(
+        ctx: Context<InitializeTokenState>,
+        supply_cap: u64,
+        vesting_cap: u64,
+    ) -> Result<()> {
+        require!(
+            supply_cap >= ctx.accounts.token_mint.supply,
+            NexusError::InvalidSupplyCap
+        );
+
+        let token_state = &mut ctx.accounts.token_state;
+        token_state.authority = ctx.accounts.token_authority.key();
+        token_state.supply_cap = supply_cap;
+        token_state.vesting_cap = vesting_cap;
+        token_state.total_vesting_allocated = 0;
+        Ok(())
+    }
+
+    pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
+        require!(amount > 0, NexusError::InvalidAmount);
+
+        let new_supply = ctx.accounts.token_mint.supply
+            .checked_add(amount)
+            .ok_or(NexusError::Overflow)?;
+        require!(
+            new_supply <= ctx.accounts.token_state.supply_cap,
+            NexusError::SupplyCapExceeded
+        );
+
+        token::mint_to(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.token_mint.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.token_authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
     pub fn create_vesting_schedule(
         ctx: Context<CreateVestingSchedule>,
         amount: u64,
         start_ts: i64,
         duration: i64,
         cliff: i64,
+        cliff_unlock_bps: u16,
+        release_fee_bps: u16,
+        fee_treasury: Option<Pubkey>,
     ) -> Result<()> {
-        require!(amount > 0, NexusError::InvalidAmount);
-        require!(duration > 0, NexusError::InvalidDuration);
-        require!(cliff <= duration, NexusError::InvalidCliff);
+        validate_vesting_schedule(amount, duration, cliff, cliff_unlock_bps, release_fee_bps, &fee_treasury)?;
+        ctx.accounts.token_state.allocate_vesting(amount)?;
+        // Fail with a clear error rather than the token program's opaque one
+        require!(ctx.accounts.from.amount >= amount, NexusError::InsufficientFunds);
 
         let vesting_account = &mut ctx.accounts.vesting_account;
         vesting_account.beneficiary = ctx.accounts.beneficiary.key();
@@ -56,6 +109,13 @@ pub mod nexus_token {
         vesting_account.start_timestamp = start_ts;
         vesting_account.duration = duration;
         vesting_account.cliff = cliff;
+        vesting_account.cliff_unlock_bps = cliff_unlock_bps;
+        vesting_account.release_fee_bps = release_fee_bps;
+        vesting_account.fee_treasury = fee_treasury.unwrap_or_default();
+        vesting_account.authority = ctx.accounts.authority.key();
+        vesting_account.revoked_at = 0;
+        vesting_account.original_beneficiary = ctx.accounts.beneficiary.key();
+        vesting_account.pending_beneficiary = Pubkey::default();
 
         // Transfer tokens to vesting account
         token::transfer(
@@ -73,7 +133,136 @@ pub mod nexus_token {
         Ok(())
     }
 
-    pub fn release_vested_tokens(ctx: Context<ReleaseVestedTokens>) -> Result<()> {
+    pub fn create_vesting_batch<'c: 'info, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, CreateVestingBatch<'info>>,
+        schedules: Vec<VestingParams>,
+        expected_total: u64,
+    ) -> Result<()> {
+        // remaining_accounts holds a (vesting PDA, beneficiary) pair per schedule
+        require!(!schedules.is_empty(), NexusError::InvalidBatch);
+        require!(schedules.len() <= MAX_VESTING_BATCH, NexusError::BatchTooLarge);
+        require!(
+            ctx.remaining_accounts.len() == schedules.len() * 2,
+            NexusError::InvalidBatch
+        );
+
+        let total = schedules
+            .iter()
+            .try_fold(0u64, |total, params| total.checked_add(params.amount))
+            .ok_or(NexusError::Overflow)?;
+        require!(total == expected_total, NexusError::BatchTotalMismatch);
+        ctx.accounts.token_state.allocate_vesting(total)?;
+        require!(ctx.accounts.from.amount >= total, NexusError::InsufficientFunds);
+
+        let rent = Rent::get()?;
+        for (params, accounts) in schedules.iter().zip(ctx.remaining_accounts.chunks(2)) {
+            let vesting_info = &accounts[0];
+            let beneficiary_info = &accounts[1];
+
+            validate_vesting_schedule(
+                params.amount,
+                params.duration,
+                params.cliff,
+                params.cliff_unlock_bps,
+                params.release_fee_bps,
+                &params.fee_treasury,
+            )?;
+
+            // Same address `create_vesting_schedule` would derive; an existing
+            // schedule for the beneficiary fails the whole batch
+            let (expected, bump) = Pubkey::find_program_address(
+                &[b"vesting", beneficiary_info.key.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(vesting_info.key(), expected, NexusError::InvalidBatch);
+            require!(vesting_info.data_is_empty(), NexusError::InvalidBatch);
+
+            let signer: &[&[u8]] = &[b"vesting", beneficiary_info.key.as_ref(), &[bump]];
+            let required_lamports = rent.minimum_balance(VestingAccount::LEN);
+            let current_lamports = vesting_info.lamports();
+            if current_lamports == 0 {
+                system_program::create_account(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::CreateAccount {
+                            from: ctx.accounts.authority.to_account_info(),
+                            to: vesting_info.clone(),
+                        },
+                        &[signer],
+                    ),
+                    required_lamports,
+                    VestingAccount::LEN as u64,
+                    &crate::ID,
+                )?;
+            } else {
+                // Lamports sent to the address ahead of time would make
+                // `create_account` fail, so claim it the way Anchor's `init` does
+                if required_lamports > current_lamports {
+                    system_program::transfer(
+                        CpiContext::new(
+                            ctx.accounts.system_program.to_account_info(),
+                            system_program::Transfer {
+                                from: ctx.accounts.authority.to_account_info(),
+                                to: vesting_info.clone(),
+                            },
+                        ),
+                        required_lamports - current_lamports,
+                    )?;
+                }
+                system_program::allocate(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Allocate { account_to_allocate: vesting_info.clone() },
+                        &[signer],
+                    ),
+                    VestingAccount::LEN as u64,
+                )?;
+                system_program::assign(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Assign { account_to_assign: vesting_info.clone() },
+                        &[signer],
+                    ),
+                    &crate::ID,
+                )?;
+            }
+
+            let mut vesting_account = Account::<VestingAccount>::try_from_unchecked(vesting_info)?;
+            vesting_account.beneficiary = beneficiary_info.key();
+            vesting_account.total_amount = params.amount;
+            vesting_account.released_amount = 0;
+            vesting_account.start_timestamp = params.start_ts;
+            vesting_account.duration = params.duration;
+            vesting_account.cliff = params.cliff;
+            vesting_account.cliff_unlock_bps = params.cliff_unlock_bps;
+            vesting_account.release_fee_bps = params.release_fee_bps;
+            vesting_account.fee_treasury = params.fee_treasury.unwrap_or_default();
+            vesting_account.authority = ctx.accounts.authority.key();
+            vesting_account.revoked_at = 0;
+            vesting_account.original_beneficiary = beneficiary_info.key();
+            vesting_account.pending_beneficiary = Pubkey::default();
+            vesting_account.exit(&crate::ID)?;
+
+            // Each schedule is funded from the single source account
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.from.to_account_info(),
+                        to: vesting_info.clone(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                ),
+                params.amount,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // `amount` releases only part of what has vested, e.g. to time taxable
+    // events; `None` releases everything available
+    pub fn release_vested_tokens(ctx: Context<ReleaseVestedTokens>, amount: Option<u64>) -> Result<()> {
         let vesting_account = &mut ctx.accounts.vesting_account;
         let clock = Clock::get()?;
         
@@ -83,33 +272,205 @@ pub mod nexus_token {
             vesting_account.start_timestamp,
             vesting_account.duration,
             vesting_account.cliff,
-            clock.unix_timestamp,
+            vesting_account.cliff_unlock_bps,
+            vesting_account.vesting_time(clock.unix_timestamp),
         )?;
 
         require!(releasable > 0, NexusError::NoTokensToRelease);
+        let release = match amount {
+            Some(amount) => {
+                require!(amount > 0 && amount <= releasable, NexusError::InvalidReleaseAmount);
+                amount
+            }
+            None => releasable,
+        };
+
+        // Skim the protocol fee before paying the beneficiary
+        let fee = apply_bps(release, vesting_account.release_fee_bps as u64, Rounding::Down)?;
+        let seed_key = vesting_account.original_beneficiary;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vesting",
+            seed_key.as_ref(),
+            &[ctx.bumps.vesting_account],
+        ]];
+
+        if fee > 0 {
+            let fee_treasury = ctx.accounts.fee_treasury.as_ref()
+                .ok_or(NexusError::MissingFeeTreasury)?;
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.vesting_account.to_account_info(),
+                        to: fee_treasury.to_account_info(),
+                        authority: ctx.accounts.vesting_account.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
 
-        // Transfer tokens to beneficiary
+        // Transfer tokens to the beneficiary, or to the destination they chose
+        let destination = match ctx.accounts.destination.as_ref() {
+            Some(destination) => {
+                require_keys_eq!(
+                    destination.mint,
+                    ctx.accounts.beneficiary_token.mint,
+                    NexusError::InvalidDestination
+                );
+                destination.to_account_info()
+            }
+            None => ctx.accounts.beneficiary_token.to_account_info(),
+        };
         token::transfer(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 token::Transfer {
                     from: ctx.accounts.vesting_account.to_account_info(),
-                    to: ctx.accounts.beneficiary_token.to_account_info(),
+                    to: destination,
                     authority: ctx.accounts.vesting_account.to_account_info(),
                 },
-                &[&[
-                    b"vesting",
-                    ctx.accounts.beneficiary.key().as_ref(),
-                    &[ctx.bumps.vesting_account],
-                ]],
+                signer_seeds,
             ),
-            releasable,
+            release - fee,
+        )?;
+
+        vesting_account.released_amount += release;
+        ctx.accounts.token_state.release_vesting(release);
+
+        Ok(())
+    }
+
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>, revoke_mode: RevokeMode) -> Result<()> {
+        let vesting_account = &mut ctx.accounts.vesting_account;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(vesting_account.revoked_at == 0, NexusError::AlreadyRevoked);
+
+        // Tokens vested so far stay releasable; vesting stops at `revoked_at`
+        let releasable = calculate_releasable_amount(
+            vesting_account.total_amount,
+            vesting_account.released_amount,
+            vesting_account.start_timestamp,
+            vesting_account.duration,
+            vesting_account.cliff,
+            vesting_account.cliff_unlock_bps,
+            now,
         )?;
+        let unvested = vesting_account.total_amount - vesting_account.released_amount - releasable;
+        vesting_account.revoked_at = now;
+        ctx.accounts.token_state.release_vesting(unvested);
+
+        if unvested == 0 {
+            return Ok(());
+        }
+
+        let seed_key = vesting_account.original_beneficiary;
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vesting",
+            seed_key.as_ref(),
+            &[ctx.bumps.vesting_account],
+        ]];
+
+        match revoke_mode {
+            RevokeMode::ReturnToTreasury => {
+                let treasury = ctx.accounts.treasury.as_ref()
+                    .ok_or(NexusError::MissingTreasury)?;
+                require_keys_eq!(
+                    treasury.mint,
+                    ctx.accounts.token_mint.key(),
+                    NexusError::InvalidDestination
+                );
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::Transfer {
+                            from: ctx.accounts.vesting_account.to_account_info(),
+                            to: treasury.to_account_info(),
+                            authority: ctx.accounts.vesting_account.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    unvested,
+                )?;
+            }
+            RevokeMode::Burn => {
+                token::burn(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::Burn {
+                            mint: ctx.accounts.token_mint.to_account_info(),
+                            from: ctx.accounts.vesting_account.to_account_info(),
+                            authority: ctx.accounts.vesting_account.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    unvested,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
 
-        vesting_account.released_amount += releasable;
+    // First step of reassigning a grant: the authority nominates the new
+    // beneficiary, who must accept before anything changes
+    pub fn transfer_beneficiary(ctx: Context<TransferBeneficiary>, new_beneficiary: Pubkey) -> Result<()> {
+        let vesting_account = &mut ctx.accounts.vesting_account;
+        require!(
+            new_beneficiary != Pubkey::default() && new_beneficiary != vesting_account.beneficiary,
+            NexusError::InvalidBeneficiary
+        );
 
+        vesting_account.pending_beneficiary = new_beneficiary;
         Ok(())
     }
+
+    pub fn accept_beneficiary(ctx: Context<AcceptBeneficiary>) -> Result<()> {
+        let vesting_account = &mut ctx.accounts.vesting_account;
+        vesting_account.beneficiary = ctx.accounts.new_beneficiary.key();
+        vesting_account.pending_beneficiary = Pubkey::default();
+        Ok(())
+    }
+
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<HealthStatus> {
+        let vesting_account = &ctx.accounts.vesting_account;
+
+        if vesting_account.released_amount > vesting_account.total_amount {
+            return Ok(HealthStatus::OverReleased);
+        }
+        if vesting_account.duration <= 0 || vesting_account.cliff > vesting_account.duration {
+            return Ok(HealthStatus::InvalidSchedule);
+        }
+        if vesting_account.cliff_unlock_bps > BPS_DENOMINATOR {
+            return Ok(HealthStatus::InvalidCliffUnlock);
+        }
+
+        Ok(HealthStatus::Healthy)
+    }
+
+    // Amounts are returned with the mint's decimals so clients can scale them
+    pub fn get_vesting_summary(ctx: Context<GetVestingSummary>) -> Result<VestingSummary> {
+        let vesting_account = &ctx.accounts.vesting_account;
+        let decimals = ctx.accounts.token_mint.decimals;
+
+        let releasable = calculate_releasable_amount(
+            vesting_account.total_amount,
+            vesting_account.released_amount,
+            vesting_account.start_timestamp,
+            vesting_account.duration,
+            vesting_account.cliff,
+            vesting_account.cliff_unlock_bps,
+            vesting_account.vesting_time(Clock::get()?.unix_timestamp),
+        )?;
+
+        Ok(VestingSummary {
+            total: TokenAmount::new(vesting_account.total_amount, decimals),
+            released: TokenAmount::new(vesting_account.released_amount, decimals),
+            releasable: TokenAmount::new(releasable, decimals),
+        })
+    }
 }
 
 #[derive(Accounts)]
@@ -122,6 +483,41 @@ pub struct Initialize<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeTokenState<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = TokenState::LEN,
+        seeds = [b"token_state"],
+        bump
+    )]
+    pub token_state: Account<'info, TokenState>,
+    #[account(
+        seeds = [b"token_mint"],
+        bump,
+        constraint = token_mint.mint_authority == COption::Some(token_authority.key()) @ NexusError::Unauthorized
+    )]
+    pub token_mint: Account<'info, Mint>,
+    pub token_authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintTokens<'info> {
+    #[account(seeds = [b"token_state"], bump)]
+    pub token_state: Account<'info, TokenState>,
+    #[account(mut, seeds = [b"token_mint"], bump)]
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut, token::mint = token_mint)]
+    pub destination: Account<'info, TokenAccount>,
+    #[account(address = token_state.authority @ NexusError::Unauthorized)]
+    pub token_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct CreateVestingSchedule<'info> {
     #[account(
@@ -132,6 +528,8 @@ pub struct CreateVestingSchedule<'info> {
         bump
     )]
     pub vesting_account: Account<'info, VestingAccount>,
+    #[account(mut, seeds = [b"token_state"], bump)]
+    pub token_state: Account<'info, TokenState>,
     pub beneficiary: AccountInfo<'info>,
     #[account(mut)]
     pub from: Account<'info, TokenAccount>,
@@ -141,21 +539,114 @@ pub struct CreateVestingSchedule<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct CreateVestingBatch<'info> {
+    #[account(mut, seeds = [b"token_state"], bump)]
+    pub token_state: Account<'info, TokenState>,
+    #[account(mut)]
+    pub from: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct ReleaseVestedTokens<'info> {
     #[account(
         mut,
-        seeds = [b"vesting", beneficiary.key().as_ref()],
+        seeds = [b"vesting", vesting_account.original_beneficiary.as_ref()],
         bump,
         has_one = beneficiary
     )]
     pub vesting_account: Account<'info, VestingAccount>,
+    #[account(mut, seeds = [b"token_state"], bump)]
+    pub token_state: Account<'info, TokenState>,
     pub beneficiary: Signer<'info>,
-    #[account(mut)]
+    #[account(mut, constraint = beneficiary_token.owner == beneficiary.key() @ NexusError::InvalidDestination)]
     pub beneficiary_token: Account<'info, TokenAccount>,
+    // Optional cold wallet; defaults to `beneficiary_token`
+    #[account(mut)]
+    pub destination: Option<Account<'info, TokenAccount>>,
+    #[account(mut, address = vesting_account.fee_treasury @ NexusError::MissingFeeTreasury)]
+    pub fee_treasury: Option<Account<'info, TokenAccount>>,
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    #[account(
+        mut,
+        seeds = [b"vesting", vesting_account.original_beneficiary.as_ref()],
+        bump,
+        has_one = beneficiary,
+        has_one = authority
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+    #[account(mut, seeds = [b"token_state"], bump)]
+    pub token_state: Account<'info, TokenState>,
+    /// CHECK: validated by `has_one`
+    pub beneficiary: AccountInfo<'info>,
+    pub authority: Signer<'info>,
+    #[account(mut, seeds = [b"token_mint"], bump)]
+    pub token_mint: Account<'info, Mint>,
+    // Required in ReturnToTreasury mode
+    #[account(mut)]
+    pub treasury: Option<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct TransferBeneficiary<'info> {
+    #[account(mut, has_one = authority)]
+    pub vesting_account: Account<'info, VestingAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptBeneficiary<'info> {
+    #[account(
+        mut,
+        constraint = vesting_account.pending_beneficiary == new_beneficiary.key() @ NexusError::NotPendingBeneficiary
+    )]
+    pub vesting_account: Account<'info, VestingAccount>,
+    pub new_beneficiary: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    pub vesting_account: Account<'info, VestingAccount>,
+}
+
+#[derive(Accounts)]
+pub struct GetVestingSummary<'info> {
+    pub vesting_account: Account<'info, VestingAccount>,
+    #[account(seeds = [b"token_mint"], bump)]
+    pub token_mint: Account<'info, Mint>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VestingSummary {
+    pub total: TokenAmount,
+    pub released: TokenAmount,
+    pub releasable: TokenAmount,
+}
+
+// What happens to unvested tokens when a schedule is revoked
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum RevokeMode {
+    ReturnToTreasury,
+    Burn,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum HealthStatus {
+    Healthy,
+    OverReleased,
+    InvalidSchedule,
+    InvalidCliffUnlock,
+}
+
 #[account]
 pub struct VestingAccount {
     pub beneficiary: Pubkey,
@@ -164,10 +655,127 @@ pub struct VestingAccount {
     pub start_timestamp: i64,
     pub duration: i64,
     pub cliff: i64,
+    pub cliff_unlock_bps: u16,
+    pub release_fee_bps: u16,
+    pub fee_treasury: Pubkey,
+    pub authority: Pubkey,
+    pub revoked_at: i64,  // 0 while the schedule is live
+    // The PDA stays derived from the first beneficiary after a transfer
+    pub original_beneficiary: Pubkey,
+    pub pending_beneficiary: Pubkey,  // default when no transfer is pending
 }
 
 impl VestingAccount {
-    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8;
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 2 + 2 + 32 + 32 + 8 + 32 + 32;
+
+    // Vesting is frozen at the revocation time
+    pub fn vesting_time(&self, now: i64) -> i64 {
+        if self.revoked_at > 0 {
+            now.min(self.revoked_at)
+        } else {
+            now
+        }
+    }
+}
+
+// Program-wide token settings, at `[b"token_state"]`
+#[account]
+pub struct TokenState {
+    pub authority: Pubkey,
+    pub supply_cap: u64,  // hard ceiling on mint supply for `mint_tokens`
+    pub vesting_cap: u64,  // size of the vesting pool
+    pub total_vesting_allocated: u64,  // granted and not yet released or revoked
+}
+
+impl TokenState {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8;
+
+    // Outstanding grants may never exceed the vesting pool
+    pub fn allocate_vesting(&mut self, amount: u64) -> Result<()> {
+        let allocated = self.total_vesting_allocated
+            .checked_add(amount)
+            .ok_or(NexusError::Overflow)?;
+        require!(allocated <= self.vesting_cap, NexusError::VestingPoolExceeded);
+        self.total_vesting_allocated = allocated;
+        Ok(())
+    }
+
+    // Saturates so schedules granted before the pool was tracked cannot underflow it
+    pub fn release_vesting(&mut self, amount: u64) {
+        self.total_vesting_allocated = self.total_vesting_allocated.saturating_sub(amount);
+    }
+}
+
+// One schedule in a `create_vesting_batch` call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VestingParams {
+    pub amount: u64,
+    pub start_ts: i64,
+    pub duration: i64,
+    pub cliff: i64,
+    pub cliff_unlock_bps: u16,
+    pub release_fee_bps: u16,
+    pub fee_treasury: Option<Pubkey>,
+}
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const MAX_RELEASE_FEE_BPS: u16 = 1_000; // 10%
+// Each schedule adds two accounts and 37 bytes of instruction data; eight is
+// the most that fits a legacy transaction alongside the fixed accounts
+const MAX_VESTING_BATCH: usize = 8;
+
+fn validate_vesting_schedule(
+    amount: u64,
+    duration: i64,
+    cliff: i64,
+    cliff_unlock_bps: u16,
+    release_fee_bps: u16,
+    fee_treasury: &Option<Pubkey>,
+) -> Result<()> {
+    require!(amount > 0, NexusError::InvalidAmount);
+    require!(duration > 0, NexusError::InvalidDuration);
+    require!(cliff <= duration, NexusError::InvalidCliff);
+    require!(cliff_unlock_bps <= BPS_DENOMINATOR, NexusError::InvalidCliffUnlock);
+    require!(release_fee_bps <= MAX_RELEASE_FEE_BPS, NexusError::InvalidReleaseFee);
+    require!(
+        release_fee_bps == 0 || fee_treasury.is_some(),
+        NexusError::MissingFeeTreasury
+    );
+    Ok(())
+}
+
+// Nothing vests before the cliff. At the cliff `cliff_unlock_bps` of the total
+// unlocks as a lump sum and the remainder vests linearly until `start + duration`.
+fn calculate_releasable_amount(
+    total_amount: u64,
+    released_amount: u64,
+    start_timestamp: i64,
+    duration: i64,
+    cliff: i64,
+    cliff_unlock_bps: u16,
+    now: i64,
+) -> Result<u64> {
+    let elapsed = now.saturating_sub(start_timestamp);
+    if elapsed < cliff {
+        return Ok(0);
+    }
+
+    let cliff_amount = apply_bps(total_amount, cliff_unlock_bps as u64, Rounding::Down)?;
+    let remainder = total_amount - cliff_amount;
+
+    let vested = if elapsed >= duration {
+        total_amount
+    } else {
+        let linear = mul_div(
+            remainder,
+            (elapsed - cliff) as u64,
+            (duration - cliff) as u64,
+            Rounding::Down,
+        )?;
+        cliff_amount + linear
+    };
+
+    Ok(vested.saturating_sub(released_amount))
 }
 
 #[error_code]
@@ -180,12 +788,48 @@ pub enum NexusError {
     InvalidCliff,
     #[msg("No tokens available for release")]
     NoTokensToRelease,
+    #[msg("Release amount must be positive and at most the releasable amount")]
+    InvalidReleaseAmount,
+    #[msg("Cliff unlock must be at most 10000 basis points")]
+    InvalidCliffUnlock,
+    #[msg("Math overflow")]
+    Overflow,
+    #[msg("Release fee must be at most 1000 basis points")]
+    InvalidReleaseFee,
+    #[msg("A fee treasury is required when a release fee is set")]
+    MissingFeeTreasury,
+    #[msg("Release destination must hold the vested token")]
+    InvalidDestination,
+    #[msg("Batch accounts do not match the schedules")]
+    InvalidBatch,
+    #[msg("Batch total does not match the expected total")]
+    BatchTotalMismatch,
+    #[msg("Batch holds more entries than the instruction accepts")]
+    BatchTooLarge,
+    #[msg("Vesting schedule has already been revoked")]
+    AlreadyRevoked,
+    #[msg("A treasury account is required to return revoked tokens")]
+    MissingTreasury,
+    #[msg("Source token account balance is below the vesting amount")]
+    InsufficientFunds,
+    #[msg("New beneficiary must be set and differ from the current one")]
+    InvalidBeneficiary,
+    #[msg("Signer is not the pending beneficiary")]
+    NotPendingBeneficiary,
+    #[msg("Signer is not the token authority")]
+    Unauthorized,
+    #[msg("Supply cap is below the current supply")]
+    InvalidSupplyCap,
+    #[msg("Minting would exceed the supply cap")]
+    SupplyCapExceeded,
+    #[msg("Grant would exceed the vesting pool")]
+    VestingPoolExceeded,
 }
 
 // Save as: programs/nexus-dao/src/lib.rs
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
 declare_id!("NEXUSDAOxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
@@ -193,6 +837,27 @@ declare_id!("NEXUSDAOxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 pub mod nexus_dao {
     use super::*;
 
+    // Creates the singleton config. Only the program's upgrade authority can
+    // do so, and it becomes the config authority.
+    pub fn initialize_dao_config(ctx: Context<InitializeDaoConfig>, params: DaoParams) -> Result<()> {
+        params.validate()?;
+
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.token_mint = ctx.accounts.token_mint.key();
+        config.params = params;
+        config.bump = ctx.bumps.config;
+        Ok(())
+    }
+
+    // Adjusts quorum and voting bounds; proposals already created keep the
+    // window, quorum and minimum voting balance they were created with
+    pub fn update_dao_config(ctx: Context<UpdateDaoConfig>, params: DaoParams) -> Result<()> {
+        params.validate()?;
+        ctx.accounts.config.params = params;
+        Ok(())
+    }
+
     pub fn create_proposal(
         ctx: Context<CreateProposal>,
         title: String,
@@ -200,6 +865,19 @@ pub mod nexus_dao {
         voting_delay: i64,
         voting_period: i64,
     ) -> Result<()> {
+        let params = &ctx.accounts.config.params;
+        require!(
+            description.len() >= params.min_description_len as usize,
+            NexusError::DescriptionTooShort
+        );
+        require!(
+            voting_delay >= params.min_voting_delay
+                && voting_delay <= params.max_voting_delay
+                && voting_period >= params.min_voting_period
+                && voting_period <= params.max_voting_period,
+            NexusError::InvalidVotingWindow
+        );
+
         let proposal = &mut ctx.accounts.proposal;
         let clock = Clock::get()?;
 
@@ -212,6 +890,8 @@ pub mod nexus_dao {
         proposal.executed = false;
         proposal.yes_votes = 0;
         proposal.no_votes = 0;
+        proposal.min_vote_balance = params.min_vote_balance;
+        proposal.quorum = params.quorum;
 
         Ok(())
     }
@@ -234,7 +914,13 @@ pub mod nexus_dao {
         );
 
         let voting_power = ctx.accounts.voter_token_account.amount;
-        
+
+        // Keeps dust accounts from spamming votes; separate from any proposer threshold
+        require!(
+            voting_power >= proposal.min_vote_balance,
+            NexusError::VoteBalanceTooLow
+        );
+
         if support {
             proposal.yes_votes = proposal.yes_votes.checked_add(voting_power)
                 .ok_or(NexusError::VoteOverflow)?;
@@ -251,6 +937,22 @@ pub mod nexus_dao {
         Ok(())
     }
 
+    pub fn get_vote_receipt(ctx: Context<GetVoteReceipt>) -> Result<VoteReceipt> {
+        let vote_info = ctx.accounts.vote_account.to_account_info();
+
+        // A missing vote account means the wallet has not voted, not an error
+        if vote_info.data_is_empty() {
+            return Ok(VoteReceipt { voted: false, support: None, voting_power: 0 });
+        }
+
+        let vote = Account::<Vote>::try_from(&vote_info)?;
+        Ok(VoteReceipt {
+            voted: true,
+            support: Some(vote.support),
+            voting_power: vote.voting_power,
+        })
+    }
+
     pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
         let proposal = &mut ctx.accounts.proposal;
         let clock = Clock::get()?;
@@ -262,9 +964,12 @@ pub mod nexus_dao {
         require!(!proposal.executed, NexusError::ProposalAlreadyExecuted);
 
         let total_votes = proposal.yes_votes + proposal.no_votes;
-        let quorum = 1_000_000; // Example: 1M tokens needed for quorum
-
-        require!(total_votes >= quorum, NexusError::QuorumNotReached);
+        require!(
+            total_votes >= proposal.quorum,
+            NexusError::QuorumNotReached
+        );
+        // An exact tie is defeated, reported distinctly from a plain loss
+        require!(proposal.yes_votes != proposal.no_votes, NexusError::ProposalTied);
         require!(
             proposal.yes_votes > proposal.no_votes,
             NexusError::ProposalNotPassed
@@ -274,10 +979,69 @@ pub mod nexus_dao {
 
         Ok(())
     }
+
+    pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp > proposal.voting_ends_at,
+            NexusError::VotingNotEnded
+        );
+        require!(proposal.executed, NexusError::ProposalNotExecuted);
+
+        Ok(())
+    }
+
+    pub fn close_vote(ctx: Context<CloseVote>) -> Result<()> {
+        let proposal_info = ctx.accounts.proposal.to_account_info();
+
+        // Proposals are only closed after execution, so a missing proposal is settled
+        if !proposal_info.data_is_empty() {
+            let proposal = Account::<Proposal>::try_from(&proposal_info)?;
+            require!(proposal.executed, NexusError::ProposalNotExecuted);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeDaoConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = DaoConfig::LEN,
+        seeds = [DAO_CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, DaoConfig>,
+    pub token_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::NexusDao>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ NexusError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDaoConfig<'info> {
+    #[account(
+        mut,
+        seeds = [DAO_CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority @ NexusError::Unauthorized
+    )]
+    pub config: Account<'info, DaoConfig>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct CreateProposal<'info> {
+    #[account(seeds = [DAO_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, DaoConfig>,
     #[account(
         init,
         payer = proposer,
@@ -291,6 +1055,8 @@ pub struct CreateProposal<'info> {
 
 #[derive(Accounts)]
 pub struct CastVote<'info> {
+    #[account(seeds = [DAO_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, DaoConfig>,
     #[account(mut)]
     pub proposal: Account<'info, Proposal>,
     #[account(
@@ -303,10 +1069,21 @@ pub struct CastVote<'info> {
     pub vote_account: Account<'info, Vote>,
     #[account(mut)]
     pub voter: Signer<'info>,
+    #[account(token::authority = voter, token::mint = config.token_mint)]
     pub voter_token_account: Account<'info, TokenAccount>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct GetVoteReceipt<'info> {
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: wallet being queried
+    pub voter: UncheckedAccount<'info>,
+    /// CHECK: may not exist yet; the address is pinned by the seeds
+    #[account(seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()], bump)]
+    pub vote_account: UncheckedAccount<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ExecuteProposal<'info> {
     #[account(mut)]
@@ -314,6 +1091,61 @@ pub struct ExecuteProposal<'info> {
     pub executor: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct CloseProposal<'info> {
+    #[account(mut, has_one = proposer, close = proposer)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVote<'info> {
+    /// CHECK: may already be closed; the address is pinned to the vote's proposal
+    #[account(address = vote_account.proposal)]
+    pub proposal: UncheckedAccount<'info>,
+    #[account(mut, has_one = voter, close = voter)]
+    pub vote_account: Account<'info, Vote>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+}
+
+#[account]
+pub struct DaoConfig {
+    pub authority: Pubkey,  // may update `params`
+    pub token_mint: Pubkey,  // only balances of this mint carry voting power
+    pub params: DaoParams,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DaoParams {
+    pub quorum: u64,  // yes plus no votes, in tokens, needed to execute
+    pub min_voting_delay: i64,
+    pub max_voting_delay: i64,
+    pub min_voting_period: i64,
+    pub max_voting_period: i64,
+    pub min_vote_balance: u64,  // smallest token balance that may vote on new proposals
+    pub min_description_len: u32,  // shortest description a proposal may be created with
+}
+
+impl DaoParams {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8 + 8 + 4;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.quorum > 0
+                && self.min_voting_delay >= 0
+                && self.min_voting_delay <= self.max_voting_delay
+                && self.min_voting_period > 0
+                && self.min_voting_period <= self.max_voting_period
+                && self.max_voting_period <= MAX_VOTING_PERIOD,
+            NexusError::InvalidDaoConfig
+        );
+        Ok(())
+    }
+}
+
 #[account]
 pub struct Proposal {
     pub proposer: Pubkey,
@@ -325,6 +1157,8 @@ pub struct Proposal {
     pub executed: bool,
     pub yes_votes: u64,
     pub no_votes: u64,
+    pub min_vote_balance: u64,  // config's minimum voting balance at creation
+    pub quorum: u64,  // config's quorum at creation
 }
 
 #[account]
@@ -336,13 +1170,36 @@ pub struct Vote {
 }
 
 impl Proposal {
-    pub const LEN: usize = 8 + 32 + 100 + 1000 + 8 + 8 + 8 + 1 + 8 + 8;
+    pub const LEN: usize = 8 + 32 + 100 + 1000 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8;
 }
 
 impl Vote {
     pub const LEN: usize = 8 + 32 + 32 + 1 + 8;
 }
 
+impl DaoConfig {
+    pub const LEN: usize = 8 + 32 + 32 + DaoParams::LEN + 1;
+}
+
+/// Seed of the singleton DaoConfig PDA.
+pub const DAO_CONFIG_SEED: &[u8] = b"dao_config";
+
+/// Longest voting period the config may allow.
+pub const MAX_VOTING_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+/// Derives the Vote address for `voter` on `proposal`.
+/// Seeds: `[b"vote", proposal, voter]`.
+pub fn vote_address(proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vote", proposal.as_ref(), voter.as_ref()], &crate::ID)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VoteReceipt {
+    pub voted: bool,
+    pub support: Option<bool>,
+    pub voting_power: u64,
+}
+
 #[error_code]
 pub enum NexusError {
     #[msg("Voting has not started yet")]
@@ -359,4 +1216,18 @@ pub enum NexusError {
     ProposalNotPassed,
     #[msg("Vote calculation overflow")]
     VoteOverflow,
+    #[msg("Proposal was defeated by an exact tie")]
+    ProposalTied,
+    #[msg("Proposal has not been executed")]
+    ProposalNotExecuted,
+    #[msg("Description is too short")]
+    DescriptionTooShort,
+    #[msg("Token balance is below the proposal's minimum to vote")]
+    VoteBalanceTooLow,
+    #[msg("Signer is not the DAO config or upgrade authority")]
+    Unauthorized,
+    #[msg("Quorum must be positive and each voting bound ordered and in range")]
+    InvalidDaoConfig,
+    #[msg("Voting delay or period is outside the DAO config bounds")]
+    InvalidVotingWindow,
 }
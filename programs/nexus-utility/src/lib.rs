@@ -2,6 +2,9 @@
 
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount};
+use nexus_economics::program::NexusEconomics;
+use nexus_common::{apply_bps, integer_sqrt, is_arweave_tx_id, mul_div, Rounding, ARWEAVE_TX_ID_LEN, BPS_DENOMINATOR};
+use nexus_economics::{EconomicsState, FeeType, LockAccount};
 
 declare_id!("NEXUSUTILxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
 
@@ -20,57 +23,215 @@ pub mod nexus_utility {
     const BASE_STORAGE_FEE: u64 = 50;     // 50 NEXUS per GB
 
     pub fn initialize_service(ctx: Context<InitializeService>, config: ServiceConfig) -> Result<()> {
+        require!(
+            config.compression_ratio_bps > 0 && config.compression_ratio_bps <= 10_000,
+            UtilityError::InvalidServiceConfig
+        );
+        require!(
+            config.retention_discount.max_discount_bps <= MAX_RETENTION_DISCOUNT_BPS
+                && (config.retention_discount.step_discount_bps == 0
+                    || config.retention_discount.step_seconds > 0),
+            UtilityError::InvalidServiceConfig
+        );
+        require!(
+            config.refund_window >= 0 && config.cancellation_fee_bps as u64 <= BPS_DENOMINATOR,
+            UtilityError::InvalidServiceConfig
+        );
+        require!(
+            config.lock_payment_discount_bps <= MAX_LOCK_PAYMENT_DISCOUNT_BPS,
+            UtilityError::InvalidServiceConfig
+        );
+        for agent_type in [AgentType::Pattern, AgentType::Prediction, AgentType::Anomaly, AgentType::Custom] {
+            require!(
+                config.agent_type_config(&agent_type).max_parameters_len as usize <= MAX_AGENT_PARAMETERS_LEN,
+                UtilityError::InvalidServiceConfig
+            );
+        }
+
         let service = &mut ctx.accounts.service;
         service.authority = ctx.accounts.authority.key();
         service.config = config;
+        service.economics_program = ctx.accounts.economics_program.key();
+        service.economics_state = ctx.accounts.economics.key();
+        service.token_mint = ctx.accounts.economics.token_mint;
+        service.venexus_treasury = ctx.accounts.venexus_treasury.key();
+        service.ainexus_treasury = ctx.accounts.ainexus_treasury.key();
+        service.protocol_treasury = ctx.accounts.protocol_treasury.key();
         service.total_streams = 0;
         service.total_agents = 0;
         service.total_storage = 0;
+        service.active = true;
+        service.fee_exempt = Vec::new();
+        service.bump = ctx.bumps.service;
         Ok(())
     }
 
-    pub fn create_stream(ctx: Context<CreateStream>, stream_config: StreamConfig) -> Result<()> {
+    pub fn add_fee_exemption(ctx: Context<UpdateFeeExemptions>, account: Pubkey) -> Result<()> {
         let service = &mut ctx.accounts.service;
-        let user = &mut ctx.accounts.user;
-        let user_tokens = ctx.accounts.user_token_account.amount;
+        require!(!service.is_fee_exempt(&account), UtilityError::AlreadyFeeExempt);
+        require!(
+            service.fee_exempt.len() < MAX_FEE_EXEMPT_ACCOUNTS,
+            UtilityError::TooManyFeeExemptions
+        );
 
-        // Calculate fee based on tier
-        let fee = calculate_stream_fee(user_tokens, BASE_STREAM_FEE);
+        service.fee_exempt.push(account);
+        Ok(())
+    }
 
-        // Transfer fee
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
-                    from: ctx.accounts.user_token_account.to_account_info(),
-                    to: ctx.accounts.fee_account.to_account_info(),
-                    authority: ctx.accounts.user.to_account_info(),
-                },
-            ),
-            fee,
+    pub fn remove_fee_exemption(ctx: Context<UpdateFeeExemptions>, account: Pubkey) -> Result<()> {
+        let service = &mut ctx.accounts.service;
+        require!(service.is_fee_exempt(&account), UtilityError::NotFeeExempt);
+
+        service.fee_exempt.retain(|exempt| *exempt != account);
+        Ok(())
+    }
+
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        stream_config: StreamConfig,
+        idempotency_key: Option<[u8; 32]>,
+        payment: PaymentMethod,
+    ) -> Result<()> {
+        // A retry of an op that already landed hands back the new account's
+        // rent and charges nothing
+        let stream_key = ctx.accounts.stream.key();
+        if !claim_idempotency_key(
+            ctx.accounts.idempotency_record.as_deref_mut(),
+            idempotency_key,
+            ctx.accounts.user.key(),
+            stream_key,
+        )? {
+            return ctx.accounts.stream.close(ctx.accounts.user.to_account_info());
+        }
+
+        let service = &mut ctx.accounts.service;
+        require!(service.active, UtilityError::ServiceInactive);
+        require!(
+            service.config.data_rate_allowed(stream_config.data_rate),
+            UtilityError::InvalidStreamConfig
+        );
+        let user = &mut ctx.accounts.user;
+        let user_tokens = tier_balance(
+            service,
+            ctx.accounts.user_token_account.amount,
+            ctx.accounts.lock.as_deref(),
+            Clock::get()?.unix_timestamp,
         )?;
 
+        let fee = stream_fee(service, &user.key(), user_tokens, stream_config.retention_period, payment)?;
+        if fee > 0 {
+            match payment {
+                PaymentMethod::Wallet => {
+                    require!(
+                        ctx.accounts.user_token_account.amount >= fee,
+                        UtilityError::InsufficientFunds
+                    );
+                    token::transfer(
+                        CpiContext::new(
+                            ctx.accounts.token_program.to_account_info(),
+                            token::Transfer {
+                                from: ctx.accounts.user_token_account.to_account_info(),
+                                to: ctx.accounts.fee_account.to_account_info(),
+                                authority: ctx.accounts.user.to_account_info(),
+                            },
+                        ),
+                        fee,
+                    )?;
+                }
+                PaymentMethod::Lock => {
+                    let lock = ctx.accounts.lock.as_ref().ok_or(UtilityError::LockPaymentAccountsMissing)?;
+                    let (Some(economics), Some(custody_authority), Some(lock_token_account), Some(economics_program)) = (
+                        ctx.accounts.economics.as_ref(),
+                        ctx.accounts.custody_authority.as_ref(),
+                        ctx.accounts.lock_token_account.as_ref(),
+                        ctx.accounts.economics_program.as_ref(),
+                    ) else {
+                        return err!(UtilityError::LockPaymentAccountsMissing);
+                    };
+                    require_keys_eq!(economics_program.key(), service.economics_program, UtilityError::EconomicsMismatch);
+                    require_keys_eq!(economics.key(), service.economics_state, UtilityError::EconomicsMismatch);
+                    require!(lock.locked && lock.amount >= fee, UtilityError::InsufficientFunds);
+
+                    nexus_economics::cpi::pay_from_lock(
+                        CpiContext::new(
+                            economics_program.to_account_info(),
+                            nexus_economics::cpi::accounts::PayFromLock {
+                                lock: lock.to_account_info(),
+                                economics: economics.to_account_info(),
+                                owner: user.to_account_info(),
+                                custody_authority: custody_authority.to_account_info(),
+                                lock_token_account: lock_token_account.to_account_info(),
+                                destination: ctx.accounts.fee_account.to_account_info(),
+                                token_program: ctx.accounts.token_program.to_account_info(),
+                            },
+                        ),
+                        fee,
+                    )?;
+                }
+            }
+        }
+
         // Create stream
         let stream = &mut ctx.accounts.stream;
         stream.owner = user.key();
-        stream.config = stream_config;
         stream.created_at = Clock::get()?.unix_timestamp;
+        stream.expires_at = stream.created_at + stream_config.retention_period;
+        stream.config = stream_config;
         stream.active = true;
+        stream.usage = 0;
+        stream.overage_billed = 0;
+        // A fee paid from a lock left the lock for good, so it is not refundable
+        stream.fee_paid = if payment == PaymentMethod::Lock { 0 } else { fee };
+        stream.service = service.key();
+        // update_stream reprices at the tier the stream was bought at
+        stream.price_bps = if service.is_fee_exempt(&user.key()) {
+            0
+        } else {
+            tier_price_bps(user_tokens) as u16
+        };
+        stream.payment_account = ctx.accounts.user_token_account.key();
 
         service.total_streams += 1;
 
         Ok(())
     }
 
-    pub fn deploy_ai_agent(ctx: Context<DeployAgent>, agent_config: AgentConfig) -> Result<()> {
-        let service = &mut ctx.accounts.service;
-        let user = &mut ctx.accounts.user;
-        let user_tokens = ctx.accounts.user_token_account.amount;
+    pub fn renew_stream(ctx: Context<RenewStream>, extension: i64) -> Result<()> {
+        require!(ctx.accounts.service.active, UtilityError::ServiceInactive);
+        let stream = &mut ctx.accounts.stream;
+        let user_tokens = tier_balance(
+            &ctx.accounts.service,
+            ctx.accounts.user_token_account.amount,
+            ctx.accounts.lock.as_deref(),
+            Clock::get()?.unix_timestamp,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
 
-        // Calculate fee based on tier
-        let fee = calculate_ai_fee(user_tokens, BASE_AI_FEE);
+        require!(stream.active, UtilityError::StreamInactive);
+        require!(
+            extension > 0 && stream.config.retention_period > 0,
+            UtilityError::InvalidRenewal
+        );
+
+        // Charge the stream fee prorated over the original retention period
+        let full_fee = stream_fee(
+            &ctx.accounts.service,
+            &ctx.accounts.user.key(),
+            user_tokens,
+            stream.config.retention_period,
+            PaymentMethod::Wallet,
+        )?;
+        let fee = mul_div(
+            full_fee,
+            extension as u64,
+            stream.config.retention_period as u64,
+            Rounding::Down,
+        )?;
+        require!(
+            ctx.accounts.user_token_account.amount >= fee,
+            UtilityError::InsufficientFunds
+        );
 
-        // Transfer fee
         token::transfer(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
@@ -83,140 +244,1060 @@ pub mod nexus_utility {
             fee,
         )?;
 
+        // Lapsed streams renew from now rather than from their old expiry
+        stream.expires_at = stream.expires_at.max(now) + extension;
+        stream.config.retention_period += extension;
+        stream.fee_paid += fee;
+
+        Ok(())
+    }
+
+    // Changes the data rate and/or retention period of a live stream. The
+    // unexpired part of the stream is repriced at the tier it was created at:
+    // the fee prorated over the time left, scaled by the data rate relative to
+    // the current one. The owner pays any increase from the account that paid
+    // for the stream and is refunded any decrease, up to what it has paid.
+    pub fn update_stream(
+        ctx: Context<UpdateStream>,
+        data_rate: Option<u64>,
+        retention_period: Option<i64>,
+    ) -> Result<()> {
+        let service = &ctx.accounts.service;
+        require!(service.active, UtilityError::ServiceInactive);
+        let stream = &mut ctx.accounts.stream;
+        let now = Clock::get()?.unix_timestamp;
+        require!(stream.active && now < stream.expires_at, UtilityError::StreamInactive);
+
+        let old_rate = stream.config.data_rate;
+        let old_period = stream.config.retention_period;
+        let new_rate = data_rate.unwrap_or(old_rate);
+        let new_period = retention_period.unwrap_or(old_period);
+        let new_expires_at = stream.expires_at + (new_period - old_period);
+        require!(
+            old_rate > 0 && service.config.data_rate_allowed(new_rate),
+            UtilityError::InvalidStreamConfig
+        );
+        require!(
+            old_period > 0 && new_period > 0 && new_expires_at > now,
+            UtilityError::InvalidStreamConfig
+        );
+
+        let price_bps = stream.price_bps as u64;
+        let remaining_value = |rate: u64, period: i64, expires_at: i64| -> Result<u64> {
+            let full_fee = calculate_stream_fee(
+                price_bps,
+                BASE_STREAM_FEE,
+                period,
+                &service.config.retention_discount,
+            )?;
+            let prorated = mul_div(full_fee, (expires_at - now) as u64, period as u64, Rounding::Down)?;
+            mul_div(prorated, rate, old_rate, Rounding::Down)
+        };
+        let (old_value, new_value) = if service.is_fee_exempt(&ctx.accounts.user.key()) {
+            (0, 0)
+        } else {
+            (
+                remaining_value(old_rate, old_period, stream.expires_at)?,
+                remaining_value(new_rate, new_period, new_expires_at)?,
+            )
+        };
+
+        if new_value > old_value {
+            let charge = new_value - old_value;
+            require!(
+                ctx.accounts.user_token_account.amount >= charge,
+                UtilityError::InsufficientFunds
+            );
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.user_token_account.to_account_info(),
+                        to: ctx.accounts.fee_account.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                charge,
+            )?;
+            stream.fee_paid += charge;
+        } else if old_value > new_value {
+            // Never more than was paid, so a lock-paid stream refunds nothing
+            let refund = (old_value - new_value).min(stream.fee_paid);
+            let service_key = service.key();
+            let seeds: &[&[u8]] = &[
+                b"fee_authority",
+                service_key.as_ref(),
+                &[ctx.bumps.fee_authority],
+            ];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.fee_account.to_account_info(),
+                        to: ctx.accounts.user_token_account.to_account_info(),
+                        authority: ctx.accounts.fee_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                refund,
+            )?;
+            stream.fee_paid -= refund;
+        }
+
+        stream.config.data_rate = new_rate;
+        stream.config.retention_period = new_period;
+        stream.expires_at = new_expires_at;
+
+        Ok(())
+    }
+
+    // Permissionless cleanup of a stream past its retention window. The
+    // keeper is paid a small bounty out of the stream's rent and the rest is
+    // returned to the owner by the `close` constraint.
+    pub fn reap_expired_stream(ctx: Context<ReapExpiredStream>) -> Result<()> {
+        let service = &mut ctx.accounts.service;
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            Clock::get()?.unix_timestamp > stream.expires_at,
+            UtilityError::StreamNotExpired
+        );
+
+        if stream.active {
+            stream.active = false;
+            service.total_streams = service.total_streams
+                .checked_sub(1)
+                .ok_or(UtilityError::CounterUnderflow)?;
+        }
+
+        let stream_info = stream.to_account_info();
+        let bounty = REAP_BOUNTY_LAMPORTS.min(stream_info.lamports());
+        **stream_info.try_borrow_mut_lamports()? -= bounty;
+        **ctx.accounts.keeper.to_account_info().try_borrow_mut_lamports()? += bounty;
+
+        Ok(())
+    }
+
+    pub fn deploy_ai_agent(
+        ctx: Context<DeployAgent>,
+        agent_config: AgentConfig,
+        idempotency_key: Option<[u8; 32]>,
+    ) -> Result<()> {
+        // A retry of an op that already landed hands back the new account's
+        // rent and charges nothing
+        let agent_key = ctx.accounts.agent.key();
+        if !claim_idempotency_key(
+            ctx.accounts.idempotency_record.as_deref_mut(),
+            idempotency_key,
+            ctx.accounts.user.key(),
+            agent_key,
+        )? {
+            return ctx.accounts.agent.close(ctx.accounts.user.to_account_info());
+        }
+
+        let service = &mut ctx.accounts.service;
+        require!(service.active, UtilityError::ServiceInactive);
+        let user = &mut ctx.accounts.user;
+        let user_tokens = tier_balance(
+            service,
+            ctx.accounts.user_token_account.amount,
+            ctx.accounts.lock.as_deref(),
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        require!(
+            agent_config.model.len() <= MAX_MODEL_LEN,
+            UtilityError::ModelNameTooLong
+        );
+
+        // Enforce the per-type capability limits
+        let type_config = service.config.agent_type_config(&agent_config.agent_type);
+        require!(
+            agent_config.parameters.len() <= type_config.max_parameters_len as usize,
+            UtilityError::AgentParametersTooLarge
+        );
+
+        let fee = agent_fee(service, &user.key(), user_tokens, &agent_config.agent_type)?;
+        if fee > 0 {
+            require!(
+                ctx.accounts.user_token_account.amount >= fee,
+                UtilityError::InsufficientFunds
+            );
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.user_token_account.to_account_info(),
+                        to: ctx.accounts.fee_account.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
+
         // Deploy agent
         let agent = &mut ctx.accounts.agent;
         agent.owner = user.key();
         agent.config = agent_config;
         agent.deployed_at = Clock::get()?.unix_timestamp;
         agent.active = true;
+        agent.fee_paid = fee;
+        agent.service = service.key();
 
         service.total_agents += 1;
 
         Ok(())
     }
 
-    pub fn store_data(ctx: Context<StoreData>, size: u64, data_config: DataConfig) -> Result<()> {
+    pub fn retire_agent(ctx: Context<RetireAgent>) -> Result<()> {
+        let service = &mut ctx.accounts.service;
+        let agent = &mut ctx.accounts.agent;
+
+        require!(agent.active, UtilityError::AgentInactive);
+
+        agent.active = false;
+        service.total_agents = service.total_agents
+            .checked_sub(1)
+            .ok_or(UtilityError::CounterUnderflow)?;
+
+        // Rent is refunded to the owner by the `close` constraint
+        Ok(())
+    }
+
+    pub fn store_data(
+        ctx: Context<StoreData>,
+        size: u64,
+        data_config: DataConfig,
+        content_hash: [u8; 32],
+        irys_tx_id: Option<String>,
+        idempotency_key: Option<[u8; 32]>,
+    ) -> Result<()> {
+        // A retry of an op that already landed hands back the new account's
+        // rent and charges nothing
+        let storage_key = ctx.accounts.storage.key();
+        if !claim_idempotency_key(
+            ctx.accounts.idempotency_record.as_deref_mut(),
+            idempotency_key,
+            ctx.accounts.user.key(),
+            storage_key,
+        )? {
+            return ctx.accounts.storage.close(ctx.accounts.user.to_account_info());
+        }
+
         let service = &mut ctx.accounts.service;
+        require!(service.active, UtilityError::ServiceInactive);
+        require!(size > 0, UtilityError::InvalidStorageSize);
+        // The commitment is what lets the owner later prove what was stored
+        require!(content_hash != [0u8; 32], UtilityError::InvalidContentHash);
+        if let Some(tx_id) = &irys_tx_id {
+            require!(is_arweave_tx_id(tx_id), UtilityError::InvalidIrysTxId);
+        }
         let user = &mut ctx.accounts.user;
-        let user_tokens = ctx.accounts.user_token_account.amount;
+        let user_tokens = tier_balance(
+            service,
+            ctx.accounts.user_token_account.amount,
+            ctx.accounts.lock.as_deref(),
+            Clock::get()?.unix_timestamp,
+        )?;
 
-        // Calculate fee based on tier and size
-        let fee = calculate_storage_fee(user_tokens, BASE_STORAGE_FEE, size);
+        let fee = storage_fee(service, &user.key(), user_tokens, size, &data_config)?;
+        if fee > 0 {
+            require!(
+                ctx.accounts.user_token_account.amount >= fee,
+                UtilityError::InsufficientFunds
+            );
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.user_token_account.to_account_info(),
+                        to: ctx.accounts.fee_account.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                fee,
+            )?;
+        }
 
-        // Transfer fee
+        // Store data metadata
+        let storage = &mut ctx.accounts.storage;
+        storage.owner = user.key();
+        storage.size = size;
+        storage.config = data_config;
+        storage.stored_at = Clock::get()?.unix_timestamp;
+        storage.content_hash = content_hash;
+        storage.irys_tx_id = irys_tx_id.clone();
+        storage.fee_paid = fee;
+        storage.service = service.key();
+
+        service.total_storage += size;
+
+        emit!(DataStored {
+            storage: storage.key(),
+            owner: storage.owner,
+            size,
+            fee,
+            content_hash,
+            irys_tx_id,
+        });
+
+        Ok(())
+    }
+
+    // Cancels a stream, agent or storage record created by mistake and returns
+    // its creation fee less the cancellation fee. Only allowed inside the
+    // service's refund window and before the operation has been used.
+    pub fn refund_operation(ctx: Context<RefundOperation>) -> Result<()> {
+        let service = &mut ctx.accounts.service;
+        let now = Clock::get()?.unix_timestamp;
+
+        let (created_at, fee_paid) = match (&ctx.accounts.stream, &ctx.accounts.agent, &ctx.accounts.storage) {
+            (Some(stream), None, None) => {
+                require!(stream.usage == 0, UtilityError::OperationConsumed);
+                if stream.active {
+                    service.total_streams = service.total_streams
+                        .checked_sub(1)
+                        .ok_or(UtilityError::CounterUnderflow)?;
+                }
+                (stream.created_at, stream.fee_paid)
+            }
+            (None, Some(agent), None) => {
+                if agent.active {
+                    service.total_agents = service.total_agents
+                        .checked_sub(1)
+                        .ok_or(UtilityError::CounterUnderflow)?;
+                }
+                (agent.deployed_at, agent.fee_paid)
+            }
+            (None, None, Some(storage)) => {
+                // A recorded Irys upload means the bytes were already stored
+                require!(storage.irys_tx_id.is_none(), UtilityError::OperationConsumed);
+                service.total_storage = service.total_storage
+                    .checked_sub(storage.size)
+                    .ok_or(UtilityError::CounterUnderflow)?;
+                (storage.stored_at, storage.fee_paid)
+            }
+            _ => return err!(UtilityError::InvalidRefundTarget),
+        };
+
+        require!(
+            service.config.refund_window > 0 && now <= created_at + service.config.refund_window,
+            UtilityError::RefundWindowClosed
+        );
+
+        let cancellation_fee = apply_bps(fee_paid, service.config.cancellation_fee_bps as u64, Rounding::Up)?;
+        let refund = fee_paid - cancellation_fee;
+        if refund > 0 {
+            let service_key = service.key();
+            let seeds: &[&[u8]] = &[
+                b"fee_authority",
+                service_key.as_ref(),
+                &[ctx.bumps.fee_authority],
+            ];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.fee_account.to_account_info(),
+                        to: ctx.accounts.user_token_account.to_account_info(),
+                        authority: ctx.accounts.fee_authority.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                refund,
+            )?;
+        }
+
+        // Rent is refunded to the owner by the `close` constraint
+        Ok(())
+    }
+
+    pub fn report_usage(ctx: Context<ReportUsage>, consumed: u64) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(stream.active, UtilityError::StreamInactive);
+        require!(consumed > 0, UtilityError::InvalidUsage);
+
+        stream.usage = stream.usage.checked_add(consumed).ok_or(UtilityError::Overflow)?;
+
+        // The contract covers `data_rate` bytes per second of the stream's life so far
+        let elapsed = (now.min(stream.expires_at) - stream.created_at).max(0) as u128;
+        let allowance = (stream.config.data_rate as u128).saturating_mul(elapsed);
+        let overage = (stream.usage as u128).saturating_sub(allowance);
+
+        // Bill whole megabytes not billed before; the remainder carries to the next report
+        let unbilled = overage.saturating_sub(stream.overage_billed as u128);
+        let billable_mb = (unbilled / BYTES_PER_MB as u128) as u64;
+        if billable_mb == 0 {
+            return Ok(());
+        }
+        let fee = billable_mb
+            .checked_mul(ctx.accounts.service.config.overage_fee_per_mb)
+            .ok_or(UtilityError::Overflow)?;
+        stream.overage_billed += billable_mb * BYTES_PER_MB;
+
+        // The owner pre-approves the fee authority as a delegate for overage billing
+        let service_key = ctx.accounts.service.key();
+        let seeds: &[&[u8]] = &[
+            b"fee_authority",
+            service_key.as_ref(),
+            &[ctx.bumps.fee_authority],
+        ];
+        require!(
+            ctx.accounts.owner_token_account.amount >= fee,
+            UtilityError::InsufficientFunds
+        );
         token::transfer(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 token::Transfer {
-                    from: ctx.accounts.user_token_account.to_account_info(),
+                    from: ctx.accounts.owner_token_account.to_account_info(),
                     to: ctx.accounts.fee_account.to_account_info(),
-                    authority: ctx.accounts.user.to_account_info(),
+                    authority: ctx.accounts.fee_authority.to_account_info(),
                 },
+                &[seeds],
             ),
             fee,
         )?;
 
-        // Store data metadata
-        let storage = &mut ctx.accounts.storage;
-        storage.owner = user.key();
-        storage.size = size;
-        storage.config = data_config;
-        storage.stored_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
 
-        service.total_storage += size;
+    pub fn route_fees(ctx: Context<RouteFees>, amount: u64, fee_type: FeeType) -> Result<()> {
+        let service = &ctx.accounts.service;
+
+        // Only the economics deployment registered at init may receive fees
+        require_keys_eq!(ctx.accounts.economics_program.key(), service.economics_program, UtilityError::EconomicsMismatch);
+        require_keys_eq!(ctx.accounts.economics.key(), service.economics_state, UtilityError::EconomicsMismatch);
+        require_keys_eq!(ctx.accounts.venexus_treasury.key(), service.venexus_treasury, UtilityError::EconomicsMismatch);
+        require_keys_eq!(ctx.accounts.ainexus_treasury.key(), service.ainexus_treasury, UtilityError::EconomicsMismatch);
+        require_keys_eq!(ctx.accounts.protocol_treasury.key(), service.protocol_treasury, UtilityError::EconomicsMismatch);
+
+        let service_key = service.key();
+        let seeds: &[&[u8]] = &[
+            b"fee_authority",
+            service_key.as_ref(),
+            &[ctx.bumps.fee_authority],
+        ];
+
+        nexus_economics::cpi::process_fee(
+            CpiContext::new_with_signer(
+                ctx.accounts.economics_program.to_account_info(),
+                nexus_economics::cpi::accounts::ProcessFee {
+                    economics: ctx.accounts.economics.to_account_info(),
+                    fee_account: ctx.accounts.fee_account.to_account_info(),
+                    venexus_treasury: ctx.accounts.venexus_treasury.to_account_info(),
+                    ainexus_treasury: ctx.accounts.ainexus_treasury.to_account_info(),
+                    protocol_treasury: ctx.accounts.protocol_treasury.to_account_info(),
+                    token_mint: ctx.accounts.token_mint.to_account_info(),
+                    fee_authority: ctx.accounts.fee_authority.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            fee_type,
+            None,
+            None,
+        )?;
+
+        Ok(())
+    }
 
+    // Winding down: deactivate_service stops new streams, renewals, agents and
+    // storage; sweep_service_fees empties the fee account; close_service
+    // recovers the rent once nothing is left to settle.
+    pub fn deactivate_service(ctx: Context<DeactivateService>) -> Result<()> {
+        ctx.accounts.service.active = false;
         Ok(())
     }
 
+    pub fn sweep_service_fees(ctx: Context<SweepServiceFees>) -> Result<()> {
+        require!(!ctx.accounts.service.active, UtilityError::ServiceActive);
+
+        let service_key = ctx.accounts.service.key();
+        let seeds: &[&[u8]] = &[
+            b"fee_authority",
+            service_key.as_ref(),
+            &[ctx.bumps.fee_authority],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.fee_account.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.fee_authority.to_account_info(),
+                },
+                &[seeds],
+            ),
+            ctx.accounts.fee_account.amount,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn close_service(ctx: Context<CloseService>, force: bool) -> Result<()> {
+        let service = &ctx.accounts.service;
+
+        require!(!service.active, UtilityError::ServiceActive);
+        require!(ctx.accounts.fee_account.amount == 0, UtilityError::FeesNotSwept);
+        // Open streams and agents would be orphaned; `force` acknowledges that
+        require!(
+            force || (service.total_streams == 0 && service.total_agents == 0),
+            UtilityError::ServiceInUse
+        );
+
+        // Rent is refunded to the authority by the `close` constraint
+        Ok(())
+    }
+
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<HealthStatus> {
+        let service = &ctx.accounts.service;
+
+        if service.authority == Pubkey::default() {
+            return Ok(HealthStatus::Uninitialized);
+        }
+        if service.config.max_streams_per_user == 0
+            || service.config.max_agents_per_user == 0
+            || service.config.max_storage_per_user == 0
+        {
+            return Ok(HealthStatus::InvalidConfig);
+        }
+
+        Ok(HealthStatus::Healthy)
+    }
+
+    // Fee the matching create_stream, deploy_ai_agent or store_data call would
+    // charge `user` right now, computed by the same helpers. Read with `.view()`.
+    pub fn quote_fee(ctx: Context<QuoteFee>, query: FeeQuery) -> Result<u64> {
+        let service = &ctx.accounts.service;
+        let user = ctx.accounts.user.key();
+        let user_tokens = tier_balance(
+            service,
+            ctx.accounts.user_token_account.amount,
+            ctx.accounts.lock.as_deref(),
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        match query {
+            FeeQuery::Stream { retention_period, payment } => {
+                stream_fee(service, &user, user_tokens, retention_period, payment)
+            }
+            FeeQuery::Agent { agent_type } => agent_fee(service, &user, user_tokens, &agent_type),
+            FeeQuery::Storage { size, data_config } => {
+                require!(size > 0, UtilityError::InvalidStorageSize);
+                storage_fee(service, &user, user_tokens, size, &data_config)
+            }
+        }
+    }
+
+    pub fn get_voting_power(ctx: Context<GetVotingPower>) -> Result<u64> {
+        Ok(voting_power(
+            &ctx.accounts.lock,
+            Clock::get()?.unix_timestamp,
+            &ctx.accounts.service.config.decay_curve,
+        ))
+    }
+
     // Helper functions
-    fn calculate_stream_fee(tokens: u64, base_fee: u64) -> u64 {
-        if tokens >= TIER3_TOKENS {
-            base_fee / 2  // 50% discount
-        } else if tokens >= TIER2_TOKENS {
-            (base_fee * 7) / 10  // 30% discount
-        } else if tokens >= TIER1_TOKENS {
-            (base_fee * 9) / 10  // 10% discount
-        } else {
-            base_fee
+
+    // Balance used for tier selection: liquid tokens plus the veNEXUS power of
+    // an active lock from the service's economics deployment
+    fn tier_balance(service: &ServiceState, liquid: u64, lock: Option<&LockAccount>, now: i64) -> Result<u64> {
+        let power = match lock {
+            Some(lock) => {
+                require_keys_eq!(lock.economics, service.economics_state, UtilityError::EconomicsMismatch);
+                voting_power(lock, now, &service.config.decay_curve)
+            }
+            None => 0,
+        };
+        Ok(liquid.saturating_add(power))
+    }
+
+    // veNEXUS power of a lock, falling from the full amount at
+    // VE_MAX_LOCK_DURATION remaining to zero at the lock's end along `curve`
+    fn voting_power(lock: &LockAccount, now: i64, curve: &DecayCurve) -> u64 {
+        if !lock.locked || lock.end_time <= now {
+            return 0;
+        }
+        let remaining = (lock.end_time - now).min(VE_MAX_LOCK_DURATION) as u64;
+        let max = VE_MAX_LOCK_DURATION as u64;
+        // Every share below is at most 1, so power never exceeds the amount
+        match curve {
+            DecayCurve::None => lock.amount,
+            DecayCurve::Linear => {
+                mul_div(lock.amount, remaining, max, Rounding::Down).unwrap_or(lock.amount)
+            }
+            // sqrt(remaining / max) taken in basis points: remaining * 10_000^2
+            // stays far below u64::MAX for a four-year lock
+            DecayCurve::Sqrt => {
+                let share_bps = integer_sqrt(remaining * BPS_DENOMINATOR * BPS_DENOMINATOR / max);
+                apply_bps(lock.amount, share_bps, Rounding::Down).unwrap_or(lock.amount)
+            }
         }
     }
 
-    fn calculate_ai_fee(tokens: u64, base_fee: u64) -> u64 {
+    // Share of the base price paid at each tier. Fees round down, in the user's favor.
+    fn tier_price_bps(tokens: u64) -> u64 {
         if tokens >= TIER3_TOKENS {
-            base_fee / 2  // 50% discount
+            5_000  // 50% discount
         } else if tokens >= TIER2_TOKENS {
-            (base_fee * 7) / 10  // 30% discount
+            7_000  // 30% discount
         } else if tokens >= TIER1_TOKENS {
-            (base_fee * 9) / 10  // 10% discount
+            9_000  // 10% discount
         } else {
-            base_fee
+            BPS_DENOMINATOR
         }
     }
 
-    fn calculate_storage_fee(tokens: u64, base_fee: u64, size: u64) -> u64 {
-        let base = base_fee * size;
-        if tokens >= TIER3_TOKENS {
-            base / 2  // 50% discount
-        } else if tokens >= TIER2_TOKENS {
-            (base * 7) / 10  // 30% discount
-        } else if tokens >= TIER1_TOKENS {
-            (base * 9) / 10  // 10% discount
+    fn calculate_stream_fee(
+        price_bps: u64,
+        base_fee: u64,
+        retention_period: i64,
+        retention_discount: &RetentionDiscount,
+    ) -> Result<u64> {
+        let tier_fee = apply_bps(base_fee, price_bps, Rounding::Down)?;
+
+        // The retention discount applies on top of the tier price
+        let discount_bps = retention_discount.discount_bps(retention_period) as u64;
+        apply_bps(tier_fee, BPS_DENOMINATOR - discount_bps, Rounding::Down)
+    }
+
+    fn calculate_ai_fee(tokens: u64, base_fee: u64) -> Result<u64> {
+        apply_bps(base_fee, tier_price_bps(tokens), Rounding::Down)
+    }
+
+    // Compressed data bills on its estimated compressed size, rounded up so any
+    // non-empty store bills at least one unit
+    fn billable_storage_size(size: u64, data_config: &DataConfig, config: &ServiceConfig) -> Result<u64> {
+        let size = if data_config.compression {
+            apply_bps(size, config.compression_ratio_bps as u64, Rounding::Up)?
         } else {
-            base
+            size
+        };
+        Ok(size.max(config.min_billable_size))
+    }
+
+    fn calculate_storage_fee(tokens: u64, base_fee: u64, size: u64) -> Result<u64> {
+        let base = base_fee.checked_mul(size).ok_or(UtilityError::Overflow)?;
+        apply_bps(base, tier_price_bps(tokens), Rounding::Down)
+    }
+
+    // Full fees as charged, shared by the paying instructions and quote_fee.
+    // Exempt accounts pay nothing.
+
+    fn stream_fee(
+        service: &ServiceState,
+        user: &Pubkey,
+        user_tokens: u64,
+        retention_period: i64,
+        payment: PaymentMethod,
+    ) -> Result<u64> {
+        if service.is_fee_exempt(user) {
+            return Ok(0);
+        }
+        // Tier price, then the discount for the committed retention period
+        let fee = calculate_stream_fee(
+            tier_price_bps(user_tokens),
+            BASE_STREAM_FEE,
+            retention_period,
+            &service.config.retention_discount,
+        )?;
+        // Paying from locked stake earns the service's extra discount
+        match payment {
+            PaymentMethod::Wallet => Ok(fee),
+            PaymentMethod::Lock => apply_bps(
+                fee,
+                BPS_DENOMINATOR - service.config.lock_payment_discount_bps as u64,
+                Rounding::Down,
+            ),
+        }
+    }
+
+    fn agent_fee(service: &ServiceState, user: &Pubkey, user_tokens: u64, agent_type: &AgentType) -> Result<u64> {
+        if service.is_fee_exempt(user) {
+            return Ok(0);
+        }
+        // Tier price of the agent type's base fee
+        let type_config = service.config.agent_type_config(agent_type);
+        let base_fee = apply_bps(BASE_AI_FEE, type_config.fee_multiplier_bps as u64, Rounding::Down)?;
+        calculate_ai_fee(user_tokens, base_fee)
+    }
+
+    fn storage_fee(
+        service: &ServiceState,
+        user: &Pubkey,
+        user_tokens: u64,
+        size: u64,
+        data_config: &DataConfig,
+    ) -> Result<u64> {
+        if service.is_fee_exempt(user) {
+            return Ok(0);
+        }
+        // Tier price of the billable size after compression, then the
+        // encryption surcharge
+        let billable_size = billable_storage_size(size, data_config, &service.config)?;
+        let mut fee = calculate_storage_fee(user_tokens, BASE_STORAGE_FEE, billable_size)?;
+        if data_config.encryption {
+            fee = apply_bps(
+                fee,
+                BPS_DENOMINATOR + service.config.encryption_surcharge_bps as u64,
+                Rounding::Down,
+            )?;
+        }
+        // Tiny stores would otherwise round down to a free write
+        Ok(fee.max(service.config.min_storage_fee))
+    }
+
+    // Records the first use of a client-supplied key against `resource`.
+    // Returns false when the key was already used, so the caller skips the op.
+    fn claim_idempotency_key(
+        record: Option<&mut IdempotencyRecord>,
+        key: Option<[u8; 32]>,
+        user: Pubkey,
+        resource: Pubkey,
+    ) -> Result<bool> {
+        match (key, record) {
+            (None, None) => Ok(true),
+            (Some(key), Some(record)) => {
+                // `init_if_needed` leaves a fresh record zeroed
+                if record.resource != Pubkey::default() {
+                    return Ok(false);
+                }
+                record.user = user;
+                record.key = key;
+                record.resource = resource;
+                Ok(true)
+            }
+            _ => err!(UtilityError::IdempotencyRecordMismatch),
         }
     }
 }
 
 #[derive(Accounts)]
 pub struct InitializeService<'info> {
-    #[account(init, payer = authority, space = 8 + size_of::<ServiceState>())]
+    // `init` fails on an existing account, so a live service can never be re-initialized
+    #[account(
+        init,
+        payer = authority,
+        space = ServiceState::LEN,
+        seeds = [SERVICE_SEED, authority.key().as_ref()],
+        bump
+    )]
     pub service: Account<'info, ServiceState>,
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub economics: Account<'info, EconomicsState>,
+    pub venexus_treasury: Account<'info, TokenAccount>,
+    pub ainexus_treasury: Account<'info, TokenAccount>,
+    pub protocol_treasury: Account<'info, TokenAccount>,
+    pub economics_program: Program<'info, NexusEconomics>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+pub struct ReportUsage<'info> {
+    #[account(has_one = authority)]
+    pub service: Account<'info, ServiceState>,
+    pub authority: Signer<'info>,
+    #[account(mut, has_one = service)]
+    pub stream: Account<'info, StreamAccount>,
+    #[account(mut, constraint = owner_token_account.owner == stream.owner @ UtilityError::Unauthorized)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns the service fee account and is the owner's billing delegate
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = fee_authority)]
+    pub fee_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RouteFees<'info> {
+    #[account(has_one = authority)]
+    pub service: Account<'info, ServiceState>,
+    pub authority: Signer<'info>,
+    /// CHECK: PDA that owns the service fee account
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = fee_authority)]
+    pub fee_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub economics: Account<'info, EconomicsState>,
+    #[account(mut)]
+    pub venexus_treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub ainexus_treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub protocol_treasury: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub token_mint: Account<'info, token::Mint>,
+    pub economics_program: Program<'info, NexusEconomics>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeExemptions<'info> {
+    #[account(mut, has_one = authority)]
+    pub service: Account<'info, ServiceState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivateService<'info> {
+    #[account(mut, has_one = authority)]
+    pub service: Account<'info, ServiceState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepServiceFees<'info> {
+    #[account(has_one = authority)]
+    pub service: Account<'info, ServiceState>,
+    pub authority: Signer<'info>,
+    /// CHECK: PDA that owns the service fee account
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = fee_authority)]
+    pub fee_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = fee_account.mint)]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseService<'info> {
+    #[account(mut, has_one = authority, close = authority)]
+    pub service: Account<'info, ServiceState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: PDA that owns the service fee account
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(token::authority = fee_authority)]
+    pub fee_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(stream_config: StreamConfig, idempotency_key: Option<[u8; 32]>)]
 pub struct CreateStream<'info> {
     #[account(mut)]
     pub service: Account<'info, ServiceState>,
-    #[account(init, payer = user, space = 8 + size_of::<StreamAccount>())]
+    #[account(init, payer = user, space = StreamAccount::LEN)]
     pub stream: Account<'info, StreamAccount>,
     #[account(mut)]
     pub user: Signer<'info>,
+    #[account(mut, token::authority = user, token::mint = service.token_mint)]
     pub user_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    // Optional economics lock whose veNEXUS power counts towards the tier,
+    // and which pays the fee under `PaymentMethod::Lock`
+    #[account(mut, constraint = lock.owner == user.key() @ UtilityError::Unauthorized)]
+    pub lock: Option<Account<'info, LockAccount>>,
+    /// CHECK: PDA that owns the service fee account
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = fee_authority, token::mint = service.token_mint)]
     pub fee_account: Account<'info, TokenAccount>,
+    // The four accounts below are required with `PaymentMethod::Lock`
+    pub economics: Option<Account<'info, EconomicsState>>,
+    /// CHECK: PDA that owns lock custody accounts, verified by the economics program
+    pub custody_authority: Option<UncheckedAccount<'info>>,
+    /// CHECK: lock custody account, verified by the economics program
+    #[account(mut)]
+    pub lock_token_account: Option<UncheckedAccount<'info>>,
+    pub economics_program: Option<Program<'info, NexusEconomics>>,
+    // Present exactly when an idempotency key is supplied
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = IdempotencyRecord::LEN,
+        seeds = [b"idempotency", user.key().as_ref(), idempotency_key.unwrap_or_default().as_ref()],
+        bump
+    )]
+    pub idempotency_record: Option<Account<'info, IdempotencyRecord>>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+pub struct RenewStream<'info> {
+    pub service: Account<'info, ServiceState>,
+    #[account(mut, has_one = service, constraint = stream.owner == user.key() @ UtilityError::Unauthorized)]
+    pub stream: Account<'info, StreamAccount>,
+    pub user: Signer<'info>,
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    // Optional economics lock whose veNEXUS power counts towards the tier
+    #[account(constraint = lock.owner == user.key() @ UtilityError::Unauthorized)]
+    pub lock: Option<Account<'info, LockAccount>>,
+    /// CHECK: PDA that owns the service fee account
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = fee_authority, token::mint = service.token_mint)]
+    pub fee_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStream<'info> {
+    pub service: Account<'info, ServiceState>,
+    #[account(mut, has_one = service, constraint = stream.owner == user.key() @ UtilityError::Unauthorized)]
+    pub stream: Account<'info, StreamAccount>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        token::authority = user,
+        address = stream.payment_account @ UtilityError::PaymentAccountMismatch
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns the service fee account
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = fee_authority)]
+    pub fee_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReapExpiredStream<'info> {
+    #[account(mut)]
+    pub service: Account<'info, ServiceState>,
+    #[account(mut, close = owner, has_one = owner, has_one = service)]
+    pub stream: Account<'info, StreamAccount>,
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    pub service: Account<'info, ServiceState>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteFee<'info> {
+    pub service: Account<'info, ServiceState>,
+    /// CHECK: only read for its key, to apply fee exemptions
+    pub user: UncheckedAccount<'info>,
+    #[account(token::authority = user)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    // Optional economics lock whose veNEXUS power counts towards the tier
+    #[account(constraint = lock.owner == user.key() @ UtilityError::Unauthorized)]
+    pub lock: Option<Account<'info, LockAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct GetVotingPower<'info> {
+    pub service: Account<'info, ServiceState>,
+    pub lock: Account<'info, LockAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_config: AgentConfig, idempotency_key: Option<[u8; 32]>)]
 pub struct DeployAgent<'info> {
     #[account(mut)]
     pub service: Account<'info, ServiceState>,
-    #[account(init, payer = user, space = 8 + size_of::<AgentAccount>())]
+    #[account(init, payer = user, space = AgentAccount::LEN)]
     pub agent: Account<'info, AgentAccount>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub user_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    // Optional economics lock whose veNEXUS power counts towards the tier
+    #[account(constraint = lock.owner == user.key() @ UtilityError::Unauthorized)]
+    pub lock: Option<Account<'info, LockAccount>>,
+    /// CHECK: PDA that owns the service fee account
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = fee_authority, token::mint = service.token_mint)]
     pub fee_account: Account<'info, TokenAccount>,
+    // Present exactly when an idempotency key is supplied
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = IdempotencyRecord::LEN,
+        seeds = [b"idempotency", user.key().as_ref(), idempotency_key.unwrap_or_default().as_ref()],
+        bump
+    )]
+    pub idempotency_record: Option<Account<'info, IdempotencyRecord>>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+pub struct RetireAgent<'info> {
+    #[account(mut)]
+    pub service: Account<'info, ServiceState>,
+    #[account(
+        mut,
+        close = user,
+        has_one = service,
+        constraint = agent.owner == user.key() @ UtilityError::Unauthorized
+    )]
+    pub agent: Account<'info, AgentAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefundOperation<'info> {
+    #[account(mut)]
+    pub service: Account<'info, ServiceState>,
+    // Exactly one of the three operations being refunded, charged by `service`
+    #[account(mut, close = user, has_one = service, constraint = stream.owner == user.key() @ UtilityError::Unauthorized)]
+    pub stream: Option<Account<'info, StreamAccount>>,
+    #[account(mut, close = user, has_one = service, constraint = agent.owner == user.key() @ UtilityError::Unauthorized)]
+    pub agent: Option<Account<'info, AgentAccount>>,
+    #[account(mut, close = user, has_one = service, constraint = storage.owner == user.key() @ UtilityError::Unauthorized)]
+    pub storage: Option<Account<'info, StorageAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut, token::authority = user)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    /// CHECK: PDA that owns the service fee account
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = fee_authority)]
+    pub fee_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    size: u64,
+    data_config: DataConfig,
+    content_hash: [u8; 32],
+    irys_tx_id: Option<String>,
+    idempotency_key: Option<[u8; 32]>
+)]
 pub struct StoreData<'info> {
     #[account(mut)]
     pub service: Account<'info, ServiceState>,
-    #[account(init, payer = user, space = 8 + size_of::<StorageAccount>())]
+    #[account(init, payer = user, space = StorageAccount::LEN)]
     pub storage: Account<'info, StorageAccount>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub user_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    // Optional economics lock whose veNEXUS power counts towards the tier
+    #[account(constraint = lock.owner == user.key() @ UtilityError::Unauthorized)]
+    pub lock: Option<Account<'info, LockAccount>>,
+    /// CHECK: PDA that owns the service fee account
+    #[account(seeds = [b"fee_authority", service.key().as_ref()], bump)]
+    pub fee_authority: AccountInfo<'info>,
+    #[account(mut, token::authority = fee_authority, token::mint = service.token_mint)]
     pub fee_account: Account<'info, TokenAccount>,
+    // Present exactly when an idempotency key is supplied
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = IdempotencyRecord::LEN,
+        seeds = [b"idempotency", user.key().as_ref(), idempotency_key.unwrap_or_default().as_ref()],
+        bump
+    )]
+    pub idempotency_record: Option<Account<'info, IdempotencyRecord>>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
@@ -228,6 +1309,37 @@ pub struct ServiceState {
     pub total_streams: u64,
     pub total_agents: u64,
     pub total_storage: u64,
+    // Economics deployment that collected fees are routed to
+    pub economics_program: Pubkey,
+    pub economics_state: Pubkey,
+    pub token_mint: Pubkey,  // mint fees are charged in, taken from the economics deployment
+    pub venexus_treasury: Pubkey,
+    pub ainexus_treasury: Pubkey,
+    pub protocol_treasury: Pubkey,
+    pub active: bool,  // false once the service is being wound down
+    pub fee_exempt: Vec<Pubkey>,  // partner and system accounts that are not charged
+    pub bump: u8,
+}
+
+impl ServiceState {
+    pub const LEN: usize = 8 + 32 + ServiceConfig::LEN + 8 + 8 + 8 + 32 * 6 + 1
+        + 4 + 32 * MAX_FEE_EXEMPT_ACCOUNTS + 1;
+
+    pub fn is_fee_exempt(&self, account: &Pubkey) -> bool {
+        self.fee_exempt.contains(account)
+    }
+}
+
+// Marks a client-supplied idempotency key as used by `user`
+#[account]
+pub struct IdempotencyRecord {
+    pub user: Pubkey,
+    pub key: [u8; 32],
+    pub resource: Pubkey,  // stream, agent or storage account the key created
+}
+
+impl IdempotencyRecord {
+    pub const LEN: usize = 8 + 32 + 32 + 32;
 }
 
 #[account]
@@ -235,7 +1347,18 @@ pub struct StreamAccount {
     pub owner: Pubkey,
     pub config: StreamConfig,
     pub created_at: i64,
+    pub expires_at: i64,
     pub active: bool,
+    pub usage: u64,           // cumulative bytes reported
+    pub overage_billed: u64,  // bytes over the contracted rate already billed
+    pub fee_paid: u64,        // refundable fee, returned less the cancellation fee on refund; 0 when paid from a lock
+    pub service: Pubkey,      // service whose fee account holds the fee
+    pub price_bps: u16,       // tier price at creation, 0 for exempt owners
+    pub payment_account: Pubkey,  // token account the stream was created from
+}
+
+impl StreamAccount {
+    pub const LEN: usize = 8 + 32 + StreamConfig::LEN + 8 + 8 + 1 + 8 + 8 + 8 + 32 + 2 + 32;
 }
 
 #[account]
@@ -244,6 +1367,12 @@ pub struct AgentAccount {
     pub config: AgentConfig,
     pub deployed_at: i64,
     pub active: bool,
+    pub fee_paid: u64,
+    pub service: Pubkey,  // service whose fee account holds the fee
+}
+
+impl AgentAccount {
+    pub const LEN: usize = 8 + 32 + AgentConfig::LEN + 8 + 1 + 8 + 32;
 }
 
 #[account]
@@ -252,6 +1381,24 @@ pub struct StorageAccount {
     pub size: u64,
     pub config: DataConfig,
     pub stored_at: i64,
+    pub content_hash: [u8; 32],      // commitment to the stored bytes
+    pub irys_tx_id: Option<String>,  // where the bytes were uploaded, if known
+    pub fee_paid: u64,
+    pub service: Pubkey,             // service whose fee account holds the fee
+}
+
+impl StorageAccount {
+    pub const LEN: usize = 8 + 32 + 8 + DataConfig::LEN + 8 + 32 + 1 + 4 + ARWEAVE_TX_ID_LEN + 8 + 32;
+}
+
+#[event]
+pub struct DataStored {
+    pub storage: Pubkey,
+    pub owner: Pubkey,
+    pub size: u64,
+    pub fee: u64,
+    pub content_hash: [u8; 32],
+    pub irys_tx_id: Option<String>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -259,6 +1406,71 @@ pub struct ServiceConfig {
     pub max_streams_per_user: u64,
     pub max_agents_per_user: u64,
     pub max_storage_per_user: u64,
+    pub pattern_agent: AgentTypeConfig,
+    pub prediction_agent: AgentTypeConfig,
+    pub anomaly_agent: AgentTypeConfig,
+    pub custom_agent: AgentTypeConfig,
+    pub compression_ratio_bps: u16,      // Estimated compressed/raw size, 10_000 = no savings
+    pub encryption_surcharge_bps: u16,   // Added on top of the storage fee for encrypted data
+    pub retention_discount: RetentionDiscount,
+    pub overage_fee_per_mb: u64,  // Billed for usage beyond a stream's data_rate
+    pub refund_window: i64,         // Seconds after creation an unused operation can be refunded, 0 disables
+    pub cancellation_fee_bps: u16,  // Kept from the fee on refund
+    pub max_data_rate: u64,         // Highest data_rate a stream may contract, 0 leaves it unbounded
+    pub decay_curve: DecayCurve,    // How a lock's veNEXUS power falls off towards its end
+    pub min_billable_size: u64,     // Stores smaller than this, after compression, are billed at this size
+    pub min_storage_fee: u64,       // Floor on any non-exempt store_data fee, in token base units
+    pub lock_payment_discount_bps: u16,  // Taken off fees paid from locked stake
+}
+
+impl ServiceConfig {
+    pub const LEN: usize = 8 + 8 + 8 + AgentTypeConfig::LEN * 4 + 2 + 2 + RetentionDiscount::LEN + 8 + 8 + 2 + 8 + 1 + 8 + 8 + 2;
+
+    pub fn data_rate_allowed(&self, data_rate: u64) -> bool {
+        self.max_data_rate == 0 || data_rate <= self.max_data_rate
+    }
+
+    pub fn agent_type_config(&self, agent_type: &AgentType) -> &AgentTypeConfig {
+        match agent_type {
+            AgentType::Pattern => &self.pattern_agent,
+            AgentType::Prediction => &self.prediction_agent,
+            AgentType::Anomaly => &self.anomaly_agent,
+            AgentType::Custom => &self.custom_agent,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AgentTypeConfig {
+    pub fee_multiplier_bps: u16,  // Applied to BASE_AI_FEE, 10_000 = 1x
+    pub max_parameters_len: u32,
+}
+
+impl AgentTypeConfig {
+    pub const LEN: usize = 2 + 4;
+}
+
+// Stepped stream discount: every full `step_seconds` of retention earns
+// `step_discount_bps`, up to `max_discount_bps`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RetentionDiscount {
+    pub step_seconds: i64,
+    pub step_discount_bps: u16,
+    pub max_discount_bps: u16,
+}
+
+impl RetentionDiscount {
+    pub const LEN: usize = 8 + 2 + 2;
+
+    pub fn discount_bps(&self, retention_period: i64) -> u16 {
+        if self.step_seconds <= 0 || retention_period <= 0 {
+            return 0;
+        }
+        let steps = (retention_period / self.step_seconds) as u64;
+        steps
+            .saturating_mul(self.step_discount_bps as u64)
+            .min(self.max_discount_bps as u64) as u16
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -268,6 +1480,10 @@ pub struct StreamConfig {
     pub retention_period: i64,
 }
 
+impl StreamConfig {
+    pub const LEN: usize = 1 + 8 + 8;
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct AgentConfig {
     pub agent_type: AgentType,
@@ -275,6 +1491,10 @@ pub struct AgentConfig {
     pub parameters: Vec<u8>,
 }
 
+impl AgentConfig {
+    pub const LEN: usize = 1 + 4 + MAX_MODEL_LEN + 4 + MAX_AGENT_PARAMETERS_LEN;
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct DataConfig {
     pub data_type: DataType,
@@ -282,6 +1502,54 @@ pub struct DataConfig {
     pub compression: bool,
 }
 
+impl DataConfig {
+    pub const LEN: usize = 1 + 1 + 1;
+}
+
+pub const SERVICE_SEED: &[u8] = b"service";
+const MAX_MODEL_LEN: usize = 64;
+const MAX_FEE_EXEMPT_ACCOUNTS: usize = 16;
+const MAX_AGENT_PARAMETERS_LEN: usize = 1024;
+const MAX_RETENTION_DISCOUNT_BPS: u16 = 5_000; // 50%
+const MAX_LOCK_PAYMENT_DISCOUNT_BPS: u16 = 5_000; // 50%
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const REAP_BOUNTY_LAMPORTS: u64 = 100_000; // Paid to whoever reaps an expired stream
+const VE_MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60; // Matches the economics max lock
+
+/// Derives the ServiceState address initialized by `authority`.
+/// Seeds: `[b"service", authority]`.
+pub fn service_address(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SERVICE_SEED, authority.as_ref()], &crate::ID)
+}
+
+// Inputs of the operation being priced by quote_fee
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum FeeQuery {
+    Stream { retention_period: i64, payment: PaymentMethod },
+    Agent { agent_type: AgentType },
+    Storage { size: u64, data_config: DataConfig },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum PaymentMethod {
+    Wallet,  // Transfer from the user's token account
+    Lock,    // Draw down the user's economics lock at a discount
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum DecayCurve {
+    Linear,  // Falls in proportion to the time remaining
+    Sqrt,    // Holds up longer, then drops steeply near the end
+    None,    // Full power until the lock expires
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum HealthStatus {
+    Healthy,
+    Uninitialized,
+    InvalidConfig,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub enum StreamType {
     Financial,
@@ -317,4 +1585,62 @@ pub enum UtilityError {
     StorageLimitExceeded,
     #[msg("Insufficient tokens")]
     InsufficientTokens,
+    #[msg("Stream is not active")]
+    StreamInactive,
+    #[msg("Invalid renewal period")]
+    InvalidRenewal,
+    #[msg("Signer does not own this account")]
+    Unauthorized,
+    #[msg("Agent parameters exceed the limit for this agent type")]
+    AgentParametersTooLarge,
+    #[msg("Agent is not active")]
+    AgentInactive,
+    #[msg("Counter underflow")]
+    CounterUnderflow,
+    #[msg("Economics account does not match the registered deployment")]
+    EconomicsMismatch,
+    #[msg("Model name is too long")]
+    ModelNameTooLong,
+    #[msg("Math overflow")]
+    Overflow,
+    #[msg("Token account balance is too low for this payment")]
+    InsufficientFunds,
+    #[msg("Paying from a lock needs the lock and its economics accounts")]
+    LockPaymentAccountsMissing,
+    #[msg("Service has been deactivated")]
+    ServiceInactive,
+    #[msg("Service must be deactivated first")]
+    ServiceActive,
+    #[msg("Collected fees must be swept before closing")]
+    FeesNotSwept,
+    #[msg("Service still has open streams or agents")]
+    ServiceInUse,
+    #[msg("Content hash must be non-zero")]
+    InvalidContentHash,
+    #[msg("Irys transaction id must be 43 characters of base64url")]
+    InvalidIrysTxId,
+    #[msg("Fee exemption list is full")]
+    TooManyFeeExemptions,
+    #[msg("Account is already fee exempt")]
+    AlreadyFeeExempt,
+    #[msg("Account is not fee exempt")]
+    NotFeeExempt,
+    #[msg("Idempotency record must be passed exactly when a key is supplied")]
+    IdempotencyRecordMismatch,
+    #[msg("Refund window has closed")]
+    RefundWindowClosed,
+    #[msg("Operation has already been used")]
+    OperationConsumed,
+    #[msg("Pass exactly one stream, agent or storage account to refund")]
+    InvalidRefundTarget,
+    #[msg("Stream is still within its retention window")]
+    StreamNotExpired,
+    #[msg("Stream data rate or retention period is outside the service limits")]
+    InvalidStreamConfig,
+    #[msg("Storage size must be greater than zero")]
+    InvalidStorageSize,
+    #[msg("Token account is not the one that paid for this stream")]
+    PaymentAccountMismatch,
+    #[msg("Reported usage must be greater than zero")]
+    InvalidUsage,
 }
@@ -20,18 +20,38 @@ describe('nexus-token', () => {
 
   let mintPda: anchor.web3.PublicKey;
   let mintBump: number;
+  let tokenState: anchor.web3.PublicKey;
+  const VESTING_POOL = 100 * 1_000_000_000; // 100 tokens granted across this suite
   let tokenAuthority: anchor.web3.Keypair;
   let vestingAccount: anchor.web3.Keypair;
   let beneficiaryToken: anchor.web3.PublicKey;
+  let cliffBeneficiary: anchor.web3.Keypair;
+  let cliffBeneficiaryToken: anchor.web3.PublicKey;
+  let cliffVestingAccount: anchor.web3.PublicKey;
+  let feeBeneficiary: anchor.web3.Keypair;
+  let feeBeneficiaryToken: anchor.web3.PublicKey;
+  let feeVestingAccount: anchor.web3.PublicKey;
+  let feeTreasury: anchor.web3.PublicKey;
+  let coldBeneficiary: anchor.web3.Keypair;
+  let coldBeneficiaryToken: anchor.web3.PublicKey;
+  let coldVestingAccount: anchor.web3.PublicKey;
+  let coldWalletToken: anchor.web3.PublicKey;
 
   before(async () => {
     tokenAuthority = anchor.web3.Keypair.generate();
     vestingAccount = anchor.web3.Keypair.generate();
+    cliffBeneficiary = anchor.web3.Keypair.generate();
+    feeBeneficiary = anchor.web3.Keypair.generate();
+    coldBeneficiary = anchor.web3.Keypair.generate();
 
     [mintPda, mintBump] = await anchor.web3.PublicKey.findProgramAddress(
       [Buffer.from("token_mint")],
       program.programId
     );
+    [tokenState] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("token_state")],
+      program.programId
+    );
 
     // Create beneficiary token account
     beneficiaryToken = await createAccount(
@@ -40,6 +60,57 @@ describe('nexus-token', () => {
       mintPda,
       provider.wallet.publicKey
     );
+
+    [cliffVestingAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("vesting"), cliffBeneficiary.publicKey.toBuffer()],
+      program.programId
+    );
+
+    cliffBeneficiaryToken = await createAccount(
+      provider.connection,
+      await provider.wallet.payer,
+      mintPda,
+      cliffBeneficiary.publicKey
+    );
+
+    [feeVestingAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("vesting"), feeBeneficiary.publicKey.toBuffer()],
+      program.programId
+    );
+
+    feeBeneficiaryToken = await createAccount(
+      provider.connection,
+      await provider.wallet.payer,
+      mintPda,
+      feeBeneficiary.publicKey
+    );
+
+    feeTreasury = await createAccount(
+      provider.connection,
+      await provider.wallet.payer,
+      mintPda,
+      tokenAuthority.publicKey
+    );
+
+    [coldVestingAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("vesting"), coldBeneficiary.publicKey.toBuffer()],
+      program.programId
+    );
+
+    coldBeneficiaryToken = await createAccount(
+      provider.connection,
+      await provider.wallet.payer,
+      mintPda,
+      coldBeneficiary.publicKey
+    );
+
+    // A third-party account the hot wallet does not own
+    coldWalletToken = await createAccount(
+      provider.connection,
+      await provider.wallet.payer,
+      mintPda,
+      anchor.web3.Keypair.generate().publicKey
+    );
   });
 
   it("Initializes the token", async () => {
@@ -60,21 +131,66 @@ describe('nexus-token', () => {
     expect(mint.mintAuthority.toString()).to.equal(tokenAuthority.publicKey.toString());
   });
 
+  it("Mints up to the supply cap and no further", async () => {
+    const headroom = 1_000;
+    const supply = (await getMint(provider.connection, mintPda)).supply;
+
+    await program.methods
+      .initializeTokenState(new anchor.BN((supply + BigInt(headroom)).toString()), new anchor.BN(VESTING_POOL))
+      .accounts({
+        tokenState: tokenState,
+        tokenMint: mintPda,
+        tokenAuthority: tokenAuthority.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([tokenAuthority])
+      .rpc();
+
+    const mint = (amount: number) => program.methods
+      .mintTokens(new anchor.BN(amount))
+      .accounts({
+        tokenState: tokenState,
+        tokenMint: mintPda,
+        destination: beneficiaryToken,
+        tokenAuthority: tokenAuthority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([tokenAuthority])
+      .rpc();
+
+    await mint(headroom);
+    expect((await getMint(provider.connection, mintPda)).supply).to.equal(supply + BigInt(headroom));
+
+    try {
+      await mint(1);
+      expect.fail("minting past the cap should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("SupplyCapExceeded");
+    }
+    expect((await getMint(provider.connection, mintPda)).supply).to.equal(supply + BigInt(headroom));
+  });
+
   it("Creates a vesting schedule", async () => {
     const amount = new anchor.BN(1000000000); // 1 token
     const startTs = new anchor.BN(Math.floor(Date.now() / 1000));
     const duration = new anchor.BN(365 * 24 * 60 * 60); // 1 year
     const cliff = new anchor.BN(90 * 24 * 60 * 60); // 90 days
+    const cliffUnlockBps = 0;
 
     await program.methods
       .createVestingSchedule(
         amount,
         startTs,
         duration,
-        cliff
+        cliff,
+        cliffUnlockBps,
+        0,
+        null
       )
       .accounts({
         vestingAccount: vestingAccount.publicKey,
+        tokenState: tokenState,
         beneficiary: provider.wallet.publicKey,
         from: tokenAuthority.publicKey,
         authority: tokenAuthority.publicKey,
@@ -89,6 +205,619 @@ describe('nexus-token', () => {
     expect(account.totalAmount.toNumber()).to.equal(amount.toNumber());
     expect(account.beneficiary.toString()).to.equal(provider.wallet.publicKey.toString());
   });
+
+  it("Reports a vesting account created through the program as healthy", async () => {
+    // Every instruction keeps released_amount <= total_amount and the schedule
+    // valid, so the other statuses only flag state written outside the program
+    const healthy = await program.methods
+      .healthCheck()
+      .accounts({ vestingAccount: vestingAccount.publicKey })
+      .view();
+
+    expect(healthy).to.deep.equal({ healthy: {} });
+  });
+
+  it("Returns vesting amounts with the mint decimals", async () => {
+    const summary = await program.methods
+      .getVestingSummary()
+      .accounts({ vestingAccount: vestingAccount.publicKey, tokenMint: mintPda })
+      .view();
+
+    const mint = await getMint(provider.connection, mintPda);
+    const account = await program.account.vestingAccount.fetch(vestingAccount.publicKey);
+    for (const amount of [summary.total, summary.released, summary.releasable]) {
+      expect(amount.decimals).to.equal(mint.decimals);
+    }
+    expect(summary.total.raw.toString()).to.equal(account.totalAmount.toString());
+    expect(summary.released.raw.toString()).to.equal(account.releasedAmount.toString());
+  });
+
+  it("Unlocks the cliff percentage at the cliff and vests the rest linearly", async () => {
+    const amount = new anchor.BN(1_000_000_000);
+    const duration = new anchor.BN(100);
+    const cliff = new anchor.BN(20);
+    const cliffUnlockBps = 2_500; // 25% at the cliff
+    // Start exactly one cliff in the past so the cliff is reached now
+    const startTs = new anchor.BN(Math.floor(Date.now() / 1000)).sub(cliff);
+
+    await program.methods
+      .createVestingSchedule(amount, startTs, duration, cliff, cliffUnlockBps, 0, null)
+      .accounts({
+        vestingAccount: cliffVestingAccount,
+        tokenState: tokenState,
+        beneficiary: cliffBeneficiary.publicKey,
+        from: tokenAuthority.publicKey,
+        authority: tokenAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([tokenAuthority])
+      .rpc();
+
+    await program.methods
+      .releaseVestedTokens(null)
+      .accounts({
+        vestingAccount: cliffVestingAccount,
+        tokenState: tokenState,
+        beneficiary: cliffBeneficiary.publicKey,
+        beneficiaryToken: cliffBeneficiaryToken,
+        destination: null,
+        feeTreasury: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([cliffBeneficiary])
+      .rpc();
+
+    const lumpSum = amount.muln(cliffUnlockBps).divn(10_000);
+    let account = await program.account.vestingAccount.fetch(cliffVestingAccount);
+    // The lump sum plus at most a couple of seconds of linear vesting
+    expect(account.releasedAmount.gte(lumpSum)).to.be.true;
+    expect(account.releasedAmount.lt(lumpSum.add(amount.sub(lumpSum).divn(10)))).to.be.true;
+
+    await new Promise((resolve) => setTimeout(resolve, 5_000));
+
+    await program.methods
+      .releaseVestedTokens(null)
+      .accounts({
+        vestingAccount: cliffVestingAccount,
+        tokenState: tokenState,
+        beneficiary: cliffBeneficiary.publicKey,
+        beneficiaryToken: cliffBeneficiaryToken,
+        destination: null,
+        feeTreasury: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([cliffBeneficiary])
+      .rpc();
+
+    const releasedAtCliff = account.releasedAmount;
+    account = await program.account.vestingAccount.fetch(cliffVestingAccount);
+    expect(account.releasedAmount.gt(releasedAtCliff)).to.be.true;
+    expect(account.releasedAmount.lt(amount)).to.be.true;
+  });
+
+  it("Splits a release between the beneficiary and the fee treasury", async () => {
+    const amount = new anchor.BN(1_000_000_000);
+    const duration = new anchor.BN(100);
+    const releaseFeeBps = 500; // 5%
+    // Fully vested so the whole amount is released at once
+    const startTs = new anchor.BN(Math.floor(Date.now() / 1000)).sub(duration.muln(2));
+
+    await program.methods
+      .createVestingSchedule(amount, startTs, duration, new anchor.BN(0), 0, releaseFeeBps, feeTreasury)
+      .accounts({
+        vestingAccount: feeVestingAccount,
+        tokenState: tokenState,
+        beneficiary: feeBeneficiary.publicKey,
+        from: tokenAuthority.publicKey,
+        authority: tokenAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([tokenAuthority])
+      .rpc();
+
+    await program.methods
+      .releaseVestedTokens(null)
+      .accounts({
+        vestingAccount: feeVestingAccount,
+        tokenState: tokenState,
+        beneficiary: feeBeneficiary.publicKey,
+        beneficiaryToken: feeBeneficiaryToken,
+        destination: null,
+        feeTreasury: feeTreasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([feeBeneficiary])
+      .rpc();
+
+    const fee = amount.muln(releaseFeeBps).divn(10_000);
+    const beneficiary = await getAccount(provider.connection, feeBeneficiaryToken);
+    const treasury = await getAccount(provider.connection, feeTreasury);
+    expect(Number(beneficiary.amount)).to.equal(amount.sub(fee).toNumber());
+    expect(Number(treasury.amount)).to.equal(fee.toNumber());
+  });
+
+  it("Releases vested tokens to a separate destination account", async () => {
+    const amount = new anchor.BN(1_000_000);
+    const duration = new anchor.BN(100);
+    const startTs = new anchor.BN(Math.floor(Date.now() / 1000)).sub(duration.muln(2));
+
+    await program.methods
+      .createVestingSchedule(amount, startTs, duration, new anchor.BN(0), 0, 0, null)
+      .accounts({
+        vestingAccount: coldVestingAccount,
+        tokenState: tokenState,
+        beneficiary: coldBeneficiary.publicKey,
+        from: tokenAuthority.publicKey,
+        authority: tokenAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([tokenAuthority])
+      .rpc();
+
+    await program.methods
+      .releaseVestedTokens(null)
+      .accounts({
+        vestingAccount: coldVestingAccount,
+        tokenState: tokenState,
+        beneficiary: coldBeneficiary.publicKey,
+        beneficiaryToken: coldBeneficiaryToken,
+        destination: coldWalletToken,
+        feeTreasury: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([coldBeneficiary])
+      .rpc();
+
+    const hot = await getAccount(provider.connection, coldBeneficiaryToken);
+    const cold = await getAccount(provider.connection, coldWalletToken);
+    expect(Number(hot.amount)).to.equal(0);
+    expect(Number(cold.amount)).to.equal(amount.toNumber());
+  });
+
+  it("Transfers a schedule to a new beneficiary in two steps", async () => {
+    const amount = new anchor.BN(1_000_000);
+    const duration = new anchor.BN(100);
+    const startTs = new anchor.BN(Math.floor(Date.now() / 1000)).sub(duration.muln(2));
+    const oldBeneficiary = anchor.web3.Keypair.generate();
+    const newBeneficiary = anchor.web3.Keypair.generate();
+    const [grantVesting] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), oldBeneficiary.publicKey.toBuffer()],
+      program.programId
+    );
+    const payer = await provider.wallet.payer;
+    const oldBeneficiaryToken = await createAccount(provider.connection, payer, mintPda, oldBeneficiary.publicKey);
+    const newBeneficiaryToken = await createAccount(provider.connection, payer, mintPda, newBeneficiary.publicKey);
+
+    await program.methods
+      .createVestingSchedule(amount, startTs, duration, new anchor.BN(0), 0, 0, null)
+      .accounts({
+        vestingAccount: grantVesting,
+        tokenState: tokenState,
+        beneficiary: oldBeneficiary.publicKey,
+        from: tokenAuthority.publicKey,
+        authority: tokenAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([tokenAuthority])
+      .rpc();
+
+    await program.methods
+      .transferBeneficiary(newBeneficiary.publicKey)
+      .accounts({ vestingAccount: grantVesting, authority: tokenAuthority.publicKey })
+      .signers([tokenAuthority])
+      .rpc();
+
+    // Nominating alone changes nothing, and only the nominee can accept
+    let account = await program.account.vestingAccount.fetch(grantVesting);
+    expect(account.beneficiary.toString()).to.equal(oldBeneficiary.publicKey.toString());
+    try {
+      await program.methods
+        .acceptBeneficiary()
+        .accounts({ vestingAccount: grantVesting, newBeneficiary: oldBeneficiary.publicKey })
+        .signers([oldBeneficiary])
+        .rpc();
+      expect.fail("only the pending beneficiary can accept");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("NotPendingBeneficiary");
+    }
+
+    await program.methods
+      .acceptBeneficiary()
+      .accounts({ vestingAccount: grantVesting, newBeneficiary: newBeneficiary.publicKey })
+      .signers([newBeneficiary])
+      .rpc();
+
+    account = await program.account.vestingAccount.fetch(grantVesting);
+    expect(account.beneficiary.toString()).to.equal(newBeneficiary.publicKey.toString());
+    expect(account.originalBeneficiary.toString()).to.equal(oldBeneficiary.publicKey.toString());
+
+    const release = (beneficiary: anchor.web3.Keypair, beneficiaryToken: anchor.web3.PublicKey) =>
+      program.methods
+        .releaseVestedTokens(null)
+        .accounts({
+          vestingAccount: grantVesting,
+          tokenState: tokenState,
+          beneficiary: beneficiary.publicKey,
+          beneficiaryToken: beneficiaryToken,
+          destination: null,
+          feeTreasury: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([beneficiary])
+        .rpc();
+
+    try {
+      await release(oldBeneficiary, oldBeneficiaryToken);
+      expect.fail("the previous beneficiary should no longer release");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+    }
+
+    await release(newBeneficiary, newBeneficiaryToken);
+    const received = await getAccount(provider.connection, newBeneficiaryToken);
+    expect(Number(received.amount)).to.equal(amount.toNumber());
+  });
+
+  it("Creates vesting schedules for five beneficiaries in one call", async () => {
+    const beneficiaries = [0, 1, 2, 3, 4].map(() => anchor.web3.Keypair.generate());
+    const vestingPdas = beneficiaries.map((beneficiary) => anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), beneficiary.publicKey.toBuffer()],
+      program.programId
+    )[0]);
+    const startTs = new anchor.BN(Math.floor(Date.now() / 1000));
+    const schedules = beneficiaries.map((_, i) => ({
+      amount: new anchor.BN((i + 1) * 1_000_000),
+      startTs,
+      duration: new anchor.BN(365 * 24 * 60 * 60),
+      cliff: new anchor.BN(90 * 24 * 60 * 60),
+      cliffUnlockBps: 0,
+      releaseFeeBps: 0,
+      feeTreasury: null,
+    }));
+    const total = schedules.reduce((sum, schedule) => sum.add(schedule.amount), new anchor.BN(0));
+
+    // Lamports sent to a schedule's address ahead of time must not block the batch
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: provider.wallet.publicKey,
+        toPubkey: vestingPdas[2],
+        lamports: 1_000,
+      })
+    ));
+
+    await program.methods
+      .createVestingBatch(schedules, total)
+      .accounts({
+        tokenState: tokenState,
+        from: tokenAuthority.publicKey,
+        authority: tokenAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(beneficiaries.flatMap((beneficiary, i) => [
+        { pubkey: vestingPdas[i], isWritable: true, isSigner: false },
+        { pubkey: beneficiary.publicKey, isWritable: false, isSigner: false },
+      ]))
+      .signers([tokenAuthority])
+      .rpc();
+
+    for (let i = 0; i < beneficiaries.length; i++) {
+      const account = await program.account.vestingAccount.fetch(vestingPdas[i]);
+      expect(account.beneficiary.toString()).to.equal(beneficiaries[i].publicKey.toString());
+      expect(account.totalAmount.toNumber()).to.equal(schedules[i].amount.toNumber());
+    }
+  });
+
+  it("Accepts a vesting batch at the cap and rejects one over it", async () => {
+    // Eight schedules with their accounts just fit in one transaction
+    const batch = (size: number, accountPairs: number) => {
+      const beneficiaries = Array.from({ length: size }, () => anchor.web3.Keypair.generate());
+      const schedules = beneficiaries.map(() => ({
+        amount: new anchor.BN(1_000_000),
+        startTs: new anchor.BN(Math.floor(Date.now() / 1000)),
+        duration: new anchor.BN(365 * 24 * 60 * 60),
+        cliff: new anchor.BN(0),
+        cliffUnlockBps: 0,
+        releaseFeeBps: 0,
+        feeTreasury: null,
+      }));
+      return program.methods
+        .createVestingBatch(schedules, new anchor.BN(size * 1_000_000))
+        .accounts({
+          tokenState: tokenState,
+          from: tokenAuthority.publicKey,
+          authority: tokenAuthority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(beneficiaries.slice(0, accountPairs).flatMap((beneficiary) => [
+          {
+            pubkey: anchor.web3.PublicKey.findProgramAddressSync(
+              [Buffer.from("vesting"), beneficiary.publicKey.toBuffer()],
+              program.programId
+            )[0],
+            isWritable: true,
+            isSigner: false,
+          },
+          { pubkey: beneficiary.publicKey, isWritable: false, isSigner: false },
+        ]))
+        .signers([tokenAuthority]);
+    };
+
+    await batch(8, 8).rpc();
+
+    try {
+      // Nine pairs would not fit a transaction, but the cap is checked before the accounts
+      await batch(9, 0).rpc();
+      expect.fail("a batch over the cap should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("BatchTooLarge");
+    }
+  });
+
+  it("Releases a partial amount and then the remainder", async () => {
+    const amount = new anchor.BN(1_000_000);
+    const duration = new anchor.BN(100);
+    // Fully vested so the releasable amount stays fixed between calls
+    const startTs = new anchor.BN(Math.floor(Date.now() / 1000)).sub(duration.muln(2));
+    const beneficiary = anchor.web3.Keypair.generate();
+    const [vesting] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), beneficiary.publicKey.toBuffer()],
+      program.programId
+    );
+    const beneficiaryTokenAccount = await createAccount(
+      provider.connection,
+      await provider.wallet.payer,
+      mintPda,
+      beneficiary.publicKey
+    );
+
+    await program.methods
+      .createVestingSchedule(amount, startTs, duration, new anchor.BN(0), 0, 0, null)
+      .accounts({
+        vestingAccount: vesting,
+        tokenState: tokenState,
+        beneficiary: beneficiary.publicKey,
+        from: tokenAuthority.publicKey,
+        authority: tokenAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([tokenAuthority])
+      .rpc();
+
+    const release = (requested: anchor.BN | null) => program.methods
+      .releaseVestedTokens(requested)
+      .accounts({
+        vestingAccount: vesting,
+        tokenState: tokenState,
+        beneficiary: beneficiary.publicKey,
+        beneficiaryToken: beneficiaryTokenAccount,
+        destination: null,
+        feeTreasury: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([beneficiary])
+      .rpc();
+
+    try {
+      await release(amount.addn(1));
+      expect.fail("a release above the releasable amount should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidReleaseAmount");
+    }
+
+    const partial = new anchor.BN(300_000);
+    await release(partial);
+    let account = await program.account.vestingAccount.fetch(vesting);
+    expect(account.releasedAmount.toNumber()).to.equal(partial.toNumber());
+    expect(Number((await getAccount(provider.connection, beneficiaryTokenAccount)).amount)).to.equal(partial.toNumber());
+
+    await release(null);
+    account = await program.account.vestingAccount.fetch(vesting);
+    expect(account.releasedAmount.toNumber()).to.equal(amount.toNumber());
+    expect(Number((await getAccount(provider.connection, beneficiaryTokenAccount)).amount)).to.equal(amount.toNumber());
+  });
+
+  it("Burns or returns unvested tokens when a schedule is revoked", async () => {
+    const amount = new anchor.BN(1_000_000);
+    // Nothing has vested yet, so the whole amount is unvested
+    const startTs = new anchor.BN(Math.floor(Date.now() / 1000));
+    const duration = new anchor.BN(365 * 24 * 60 * 60);
+
+    const createRevocable = async () => {
+      const beneficiary = anchor.web3.Keypair.generate();
+      const [vesting] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vesting"), beneficiary.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createVestingSchedule(amount, startTs, duration, new anchor.BN(0), 0, 0, null)
+        .accounts({
+          vestingAccount: vesting,
+          tokenState: tokenState,
+          beneficiary: beneficiary.publicKey,
+          from: tokenAuthority.publicKey,
+          authority: tokenAuthority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([tokenAuthority])
+        .rpc();
+      return { beneficiary: beneficiary.publicKey, vesting };
+    };
+
+    const burned = await createRevocable();
+
+    // Only the NEXUS mint itself can be passed to burn from
+    const payer = await provider.wallet.payer;
+    const otherMint = await createMint(provider.connection, payer, provider.wallet.publicKey, null, 0);
+    try {
+      await program.methods
+        .revokeVesting({ burn: {} })
+        .accounts({
+          vestingAccount: burned.vesting,
+          tokenState: tokenState,
+          beneficiary: burned.beneficiary,
+          authority: tokenAuthority.publicKey,
+          tokenMint: otherMint,
+          treasury: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([tokenAuthority])
+        .rpc();
+      expect.fail("revoking against another mint should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintSeeds");
+    }
+
+    const supplyBefore = (await getMint(provider.connection, mintPda)).supply;
+    await program.methods
+      .revokeVesting({ burn: {} })
+      .accounts({
+        vestingAccount: burned.vesting,
+        tokenState: tokenState,
+        beneficiary: burned.beneficiary,
+        authority: tokenAuthority.publicKey,
+        tokenMint: mintPda,
+        treasury: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([tokenAuthority])
+      .rpc();
+    const supplyAfter = (await getMint(provider.connection, mintPda)).supply;
+    expect(Number(supplyBefore - supplyAfter)).to.be.closeTo(amount.toNumber(), 100);
+
+    const returned = await createRevocable();
+    const treasuryBefore = (await getAccount(provider.connection, feeTreasury)).amount;
+    await program.methods
+      .revokeVesting({ returnToTreasury: {} })
+      .accounts({
+        vestingAccount: returned.vesting,
+        tokenState: tokenState,
+        beneficiary: returned.beneficiary,
+        authority: tokenAuthority.publicKey,
+        tokenMint: mintPda,
+        treasury: feeTreasury,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([tokenAuthority])
+      .rpc();
+    const treasuryAfter = (await getAccount(provider.connection, feeTreasury)).amount;
+    expect(treasuryAfter > treasuryBefore).to.be.true;
+
+    const account = await program.account.vestingAccount.fetch(returned.vesting);
+    expect(account.revokedAt.toNumber()).to.be.above(0);
+  });
+
+  it("Rejects a release fee above the maximum", async () => {
+    const stranger = anchor.web3.Keypair.generate();
+    const [strangerVesting] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), stranger.publicKey.toBuffer()],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .createVestingSchedule(new anchor.BN(1_000), new anchor.BN(0), new anchor.BN(100), new anchor.BN(0), 0, 1_001, feeTreasury)
+        .accounts({
+          vestingAccount: strangerVesting,
+          tokenState: tokenState,
+          beneficiary: stranger.publicKey,
+          from: tokenAuthority.publicKey,
+          authority: tokenAuthority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([tokenAuthority])
+        .rpc();
+      expect.fail("release fee above the maximum should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidReleaseFee");
+    }
+  });
+
+  it("Rejects a schedule the source account cannot fund", async () => {
+    const grantee = anchor.web3.Keypair.generate();
+    const [granteeVesting] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), grantee.publicKey.toBuffer()],
+      program.programId
+    );
+    const emptySource = await createAccount(
+      provider.connection,
+      await provider.wallet.payer,
+      mintPda,
+      tokenAuthority.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+
+    try {
+      await program.methods
+        .createVestingSchedule(new anchor.BN(1_000), new anchor.BN(0), new anchor.BN(100), new anchor.BN(0), 0, 0, null)
+        .accounts({
+          vestingAccount: granteeVesting,
+          tokenState: tokenState,
+          beneficiary: grantee.publicKey,
+          from: emptySource,
+          authority: tokenAuthority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([tokenAuthority])
+        .rpc();
+      expect.fail("an underfunded source should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InsufficientFunds");
+    }
+
+    const vesting = await provider.connection.getAccountInfo(granteeVesting);
+    expect(vesting).to.be.null;
+  });
+
+  it("Rejects grants beyond the vesting pool", async () => {
+    const state = await program.account.tokenState.fetch(tokenState);
+    const remaining = state.vestingCap.sub(state.totalVestingAllocated);
+    const grantee = anchor.web3.Keypair.generate();
+    const [granteeVesting] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), grantee.publicKey.toBuffer()],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .createVestingSchedule(remaining.addn(1), new anchor.BN(0), new anchor.BN(100), new anchor.BN(0), 0, 0, null)
+        .accounts({
+          vestingAccount: granteeVesting,
+          tokenState: tokenState,
+          beneficiary: grantee.publicKey,
+          from: tokenAuthority.publicKey,
+          authority: tokenAuthority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([tokenAuthority])
+        .rpc();
+      expect.fail("a grant past the vesting pool should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("VestingPoolExceeded");
+    }
+
+    const after = await program.account.tokenState.fetch(tokenState);
+    expect(after.totalVestingAllocated.toString()).to.equal(state.totalVestingAllocated.toString());
+  });
 });
 
 // Save as: tests/nexus-dao.ts
@@ -96,6 +825,7 @@ describe('nexus-token', () => {
 import * as anchor from '@project-serum/anchor';
 import { Program } from '@project-serum/anchor';
 import { NexusDao } from '../target/types/nexus_dao';
+import { createMint, createAccount, mintTo } from '@solana/spl-token';
 import { expect } from 'chai';
 
 describe('nexus-dao', () => {
@@ -103,9 +833,67 @@ describe('nexus-dao', () => {
   anchor.setProvider(provider);
 
   const program = anchor.workspace.NexusDao as Program<NexusDao>;
+  // Loaded into the test validator with the wallet as authority, a 1M token
+  // quorum, a 16 byte minimum description and a voting mint the wallet can mint
+  const [daoConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("dao_config")],
+    program.programId
+  );
 
   let proposal: anchor.web3.Keypair;
   let voteAccount: anchor.web3.Keypair;
+  let tiedProposal: anchor.web3.PublicKey;
+  let openProposal: anchor.web3.PublicKey;
+  let voterTokenAccount: anchor.web3.PublicKey;
+  let executedProposal: anchor.web3.PublicKey;
+  let executedVote: anchor.web3.PublicKey;
+  let executedVoter: anchor.web3.Keypair;
+  let sybilProposal: anchor.web3.PublicKey;
+  let dustVoter: anchor.web3.Keypair;
+  let dustVoterTokenAccount: anchor.web3.PublicKey;
+  let thresholdVoter: anchor.web3.Keypair;
+  let thresholdVoterTokenAccount: anchor.web3.PublicKey;
+
+  before(async () => {
+    // `sybilProposal` requires 1,000 tokens to vote; `dustVoter` holds 999 and
+    // `thresholdVoter` holds exactly 1,000
+    const payer = await provider.wallet.payer;
+    const mint = (await program.account.daoConfig.fetch(daoConfig)).tokenMint;
+    dustVoter = anchor.web3.Keypair.generate();
+    thresholdVoter = anchor.web3.Keypair.generate();
+    for (const voter of [dustVoter, thresholdVoter]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(voter.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+      );
+    }
+    dustVoterTokenAccount = await createAccount(provider.connection, payer, mint, dustVoter.publicKey);
+    thresholdVoterTokenAccount = await createAccount(provider.connection, payer, mint, thresholdVoter.publicKey);
+    await mintTo(provider.connection, payer, mint, dustVoterTokenAccount, payer, 999);
+    await mintTo(provider.connection, payer, mint, thresholdVoterTokenAccount, payer, 1_000);
+
+    // The minimum is snapshotted at creation, so the config can be restored straight after
+    const original = (await program.account.daoConfig.fetch(daoConfig)).params;
+    const setParams = (params: any) => program.methods
+      .updateDaoConfig(params)
+      .accounts({ config: daoConfig, authority: provider.wallet.publicKey })
+      .rpc();
+    await setParams({ ...original, minVotingDelay: new anchor.BN(0), minVoteBalance: new anchor.BN(1_000) });
+
+    const sybil = anchor.web3.Keypair.generate();
+    await program.methods
+      .createProposal("Sybil test", "Only holders of 1,000 tokens may vote", new anchor.BN(0), original.minVotingPeriod)
+      .accounts({
+        config: daoConfig,
+        proposal: sybil.publicKey,
+        proposer: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([sybil])
+      .rpc();
+    sybilProposal = sybil.publicKey;
+
+    await setParams(original);
+  });
 
   beforeEach(async () => {
     proposal = anchor.web3.Keypair.generate();
@@ -126,6 +914,7 @@ describe('nexus-dao', () => {
         votingPeriod
       )
       .accounts({
+        config: daoConfig,
         proposal: proposal.publicKey,
         proposer: provider.wallet.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -134,17 +923,38 @@ describe('nexus-dao', () => {
       .rpc();
 
     const proposalAccount = await program.account.proposal.fetch(proposal.publicKey);
+    const config = await program.account.daoConfig.fetch(daoConfig);
     expect(proposalAccount.title).to.equal(title);
+    expect(proposalAccount.quorum.toString()).to.equal(config.params.quorum.toString());
     expect(proposalAccount.description).to.equal(description);
     expect(proposalAccount.executed).to.be.false;
   });
 
+  it("Rejects a proposal with a too short description", async () => {
+    try {
+      await program.methods
+        .createProposal("Short", "tbd", new anchor.BN(60), new anchor.BN(24 * 60 * 60))
+        .accounts({
+          config: daoConfig,
+          proposal: proposal.publicKey,
+          proposer: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([proposal])
+        .rpc();
+      expect.fail("too short description should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("DescriptionTooShort");
+    }
+  });
+
   it("Casts a vote", async () => {
     const support = true;
 
     await program.methods
       .castVote(support)
       .accounts({
+        config: daoConfig,
         proposal: proposal.publicKey,
         voteAccount: voteAccount.publicKey,
         voter: provider.wallet.publicKey,
@@ -158,6 +968,213 @@ describe('nexus-dao', () => {
     expect(vote.support).to.equal(support);
     expect(vote.voter.toString()).to.equal(provider.wallet.publicKey.toString());
   });
+
+  it("Returns vote receipts before and after voting", async () => {
+    const voter = anchor.web3.Keypair.generate();
+    const [votePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vote"), openProposal.toBuffer(), voter.publicKey.toBuffer()],
+      program.programId
+    );
+    const query = () => program.methods
+      .getVoteReceipt()
+      .accounts({ proposal: openProposal, voter: voter.publicKey, voteAccount: votePda })
+      .view();
+
+    const before = await query();
+    expect(before.voted).to.be.false;
+    expect(before.support).to.be.null;
+
+    await program.methods
+      .castVote(true)
+      .accounts({
+        config: daoConfig,
+        proposal: openProposal,
+        voteAccount: votePda,
+        voter: voter.publicKey,
+        voterTokenAccount: voterTokenAccount,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([voter])
+      .rpc();
+
+    const after = await query();
+    expect(after.voted).to.be.true;
+    expect(after.support).to.be.true;
+  });
+
+  it("Requires the proposal's minimum balance to vote", async () => {
+    // `sybilProposal` was created while the config required 1,000 tokens to vote
+    const vote = (voter: anchor.web3.Keypair, voterTokenAccount: anchor.web3.PublicKey) =>
+      program.methods
+        .castVote(true)
+        .accounts({
+          config: daoConfig,
+          proposal: sybilProposal,
+          voteAccount: anchor.web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("vote"), sybilProposal.toBuffer(), voter.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          voter: voter.publicKey,
+          voterTokenAccount: voterTokenAccount,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([voter])
+        .rpc();
+
+    // Only the voter's own balance of the DAO mint counts
+    try {
+      await vote(dustVoter, thresholdVoterTokenAccount);
+      expect.fail("voting with someone else's token account should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintTokenOwner");
+    }
+    const payer = await provider.wallet.payer;
+    const otherMint = await createMint(provider.connection, payer, provider.wallet.publicKey, null, 0);
+    const otherMintAccount = await createAccount(provider.connection, payer, otherMint, dustVoter.publicKey);
+    await mintTo(provider.connection, payer, otherMint, otherMintAccount, payer, 1_000_000);
+    try {
+      await vote(dustVoter, otherMintAccount);
+      expect.fail("a balance of another mint should not vote");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintTokenMint");
+    }
+
+    try {
+      await vote(dustVoter, dustVoterTokenAccount);
+      expect.fail("a balance below the minimum should not vote");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("VoteBalanceTooLow");
+    }
+
+    await vote(thresholdVoter, thresholdVoterTokenAccount);
+    const proposalAccount = await program.account.proposal.fetch(sybilProposal);
+    expect(proposalAccount.yesVotes.toNumber()).to.equal(1_000);
+  });
+
+  it("Reports an exact tie as defeated", async () => {
+    // `tiedProposal` closed voting with equal yes and no weight
+    try {
+      await program.methods
+        .executeProposal()
+        .accounts({
+          proposal: tiedProposal,
+          executor: provider.wallet.publicKey,
+        })
+        .rpc();
+      expect.fail("tied proposal should not execute");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ProposalTied");
+    }
+  });
+
+  it("Updates the DAO config and rejects anyone but its authority", async () => {
+    const original = (await program.account.daoConfig.fetch(daoConfig)).params;
+    const params = {
+      quorum: new anchor.BN(2_000_000),
+      minVotingDelay: new anchor.BN(0),
+      maxVotingDelay: new anchor.BN(2 * 24 * 60 * 60),
+      minVotingPeriod: new anchor.BN(60 * 60),
+      maxVotingPeriod: new anchor.BN(14 * 24 * 60 * 60),
+      minVoteBalance: new anchor.BN(0),
+      minDescriptionLen: 16,
+    };
+    const update = (params: any, authority: anchor.web3.Keypair | null) => {
+      const call = program.methods
+        .updateDaoConfig(params)
+        .accounts({
+          config: daoConfig,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        });
+      return authority ? call.signers([authority]).rpc() : call.rpc();
+    };
+
+    try {
+      await update(params, anchor.web3.Keypair.generate());
+      expect.fail("only the config authority may update it");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("Unauthorized");
+    }
+
+    try {
+      await update({ ...params, minVotingPeriod: params.maxVotingPeriod.addn(1) }, null);
+      expect.fail("an inverted voting period range should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidDaoConfig");
+    }
+
+    await update(params, null);
+    const updated = (await program.account.daoConfig.fetch(daoConfig)).params;
+    expect(updated.quorum.toString()).to.equal("2000000");
+    expect(updated.maxVotingPeriod.toString()).to.equal(params.maxVotingPeriod.toString());
+    // Existing proposals keep the quorum they were created with
+    const sybil = await program.account.proposal.fetch(sybilProposal);
+    expect(sybil.quorum.toString()).to.equal(original.quorum.toString());
+
+    // Proposals outside the new bounds are refused
+    try {
+      await program.methods
+        .createProposal("Long vote", "A proposal that votes for too long", new anchor.BN(60), new anchor.BN(20 * 24 * 60 * 60))
+        .accounts({
+          config: daoConfig,
+          proposal: proposal.publicKey,
+          proposer: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([proposal])
+        .rpc();
+      expect.fail("a voting period above the configured maximum should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidVotingWindow");
+    }
+
+    await update(original, null);
+  });
+
+  it("Refuses to close a proposal mid-voting", async () => {
+    try {
+      await program.methods
+        .closeProposal()
+        .accounts({ proposal: openProposal, proposer: provider.wallet.publicKey })
+        .rpc();
+      expect.fail("open proposal should not close");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("VotingNotEnded");
+    }
+  });
+
+  it("Closes an executed proposal and its votes, returning rent", async () => {
+    // `executedProposal` was executed with a single vote, `executedVote`, cast by `executedVoter`
+    const voterBefore = await provider.connection.getBalance(executedVoter.publicKey);
+    const voteRent = await provider.connection.getBalance(executedVote);
+
+    await program.methods
+      .closeVote()
+      .accounts({
+        proposal: executedProposal,
+        voteAccount: executedVote,
+        voter: executedVoter.publicKey,
+      })
+      .signers([executedVoter])
+      .rpc();
+
+    const voterAfter = await provider.connection.getBalance(executedVoter.publicKey);
+    expect(voterAfter).to.be.above(voterBefore);
+    expect(voterAfter).to.be.at.most(voterBefore + voteRent);
+    expect(await provider.connection.getAccountInfo(executedVote)).to.be.null;
+
+    const proposalRent = await provider.connection.getBalance(executedProposal);
+    const proposerBefore = await provider.connection.getBalance(provider.wallet.publicKey);
+
+    await program.methods
+      .closeProposal()
+      .accounts({ proposal: executedProposal, proposer: provider.wallet.publicKey })
+      .rpc();
+
+    const proposerAfter = await provider.connection.getBalance(provider.wallet.publicKey);
+    expect(proposerAfter).to.be.above(proposerBefore);
+    expect(proposerAfter).to.be.at.most(proposerBefore + proposalRent);
+    expect(await provider.connection.getAccountInfo(executedProposal)).to.be.null;
+  });
 });
 
 // Save as: migrations/deploy.ts
@@ -198,6 +1215,26 @@ async function main() {
     .signers([tokenAuthority])
     .rpc();
 
+  // Cap supply at the initial allocation
+  const [tokenState] = await anchor.web3.PublicKey.findProgramAddress(
+    [Buffer.from("token_state")],
+    tokenProgram.programId
+  );
+  await tokenProgram.methods
+    .initializeTokenState(
+      new anchor.BN("100000000000000000"), // 100M tokens
+      new anchor.BN("20000000000000000")   // 20M tokens for team and advisor vesting
+    )
+    .accounts({
+      tokenState: tokenState,
+      tokenMint: mintPda,
+      tokenAuthority: tokenAuthority.publicKey,
+      payer: provider.wallet.publicKey,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .signers([tokenAuthority])
+    .rpc();
+
   // Deploy DAO Program
   console.log("Deploying DAO Program...");
   const daoProgram = anchor.workspace.NexusDao as Program<NexusDao>;
@@ -207,6 +1244,35 @@ async function main() {
   const votingPeriod = new anchor.BN(7 * 24 * 60 * 60); // 1 week
   const quorum = new anchor.BN(1_000_000); // 1M tokens
 
+  const [daoConfig] = await anchor.web3.PublicKey.findProgramAddress(
+    [Buffer.from("dao_config")],
+    daoProgram.programId
+  );
+  // The DAO config can only be initialized by the program's upgrade authority
+  const [daoProgramData] = anchor.web3.PublicKey.findProgramAddressSync(
+    [daoProgram.programId.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+  await daoProgram.methods
+    .initializeDaoConfig({
+      quorum: quorum,
+      minVotingDelay: votingDelay,
+      maxVotingDelay: votingDelay.muln(24),
+      minVotingPeriod: new anchor.BN(24 * 60 * 60),
+      maxVotingPeriod: votingPeriod.muln(2),
+      minVoteBalance: new anchor.BN(0),
+      minDescriptionLen: 16,
+    })
+    .accounts({
+      config: daoConfig,
+      tokenMint: mintPda,
+      authority: provider.wallet.publicKey,
+      program: daoProgram.programId,
+      programData: daoProgramData,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .rpc();
+
   console.log("Deployment Complete!");
   console.log("Token Program ID:", tokenProgram.programId.toString());
   console.log("DAO Program ID:", daoProgram.programId.toString());
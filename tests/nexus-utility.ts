@@ -0,0 +1,1387 @@
+// Save as: tests/nexus-utility.ts
+
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { NexusUtility } from '../target/types/nexus_utility';
+import { NexusEconomics } from '../target/types/nexus_economics';
+import { TOKEN_PROGRAM_ID, createAccount, transfer } from '@solana/spl-token';
+import { expect } from 'chai';
+import * as crypto from 'crypto';
+
+describe('nexus-utility', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.NexusUtility as Program<NexusUtility>;
+  const economicsProgram = anchor.workspace.NexusEconomics as Program<NexusEconomics>;
+
+  let service: anchor.web3.PublicKey;
+  let stream: anchor.web3.PublicKey;
+  let inactiveStream: anchor.web3.PublicKey;
+  let agent: anchor.web3.PublicKey;
+  let feeAuthority: anchor.web3.PublicKey;
+  let economics: anchor.web3.PublicKey;
+  let economicsProgramId: anchor.web3.PublicKey;
+  let venexusTreasury: anchor.web3.PublicKey;
+  let ainexusTreasury: anchor.web3.PublicKey;
+  let rogueTreasury: anchor.web3.PublicKey;
+  let tokenMint: anchor.web3.PublicKey;
+  let userTokenAccount: anchor.web3.PublicKey;
+  let feeAccount: anchor.web3.PublicKey;
+  let corruptedService: anchor.web3.PublicKey;
+  let lockedUser: anchor.web3.Keypair;
+  let lockedUserTokenAccount: anchor.web3.PublicKey;
+  let lockedUserLock: anchor.web3.PublicKey;
+  let meteredStream: anchor.web3.PublicKey;
+  let retiringService: anchor.web3.PublicKey;
+  let retiringFeeAccount: anchor.web3.PublicKey;
+  let sweepDestination: anchor.web3.PublicKey;
+  let refundService: anchor.web3.PublicKey;
+  let refundFeeAuthority: anchor.web3.PublicKey;
+  let refundFeeAccount: anchor.web3.PublicKey;
+  let expiredStream: anchor.web3.PublicKey;
+  let expiredStreamOwner: anchor.web3.PublicKey;
+  let sqrtDecayService: anchor.web3.PublicKey;
+  let flatDecayService: anchor.web3.PublicKey;
+  let midpointLock: anchor.web3.PublicKey;
+  let brokeUser: anchor.web3.Keypair;
+  let brokeUserTokenAccount: anchor.web3.PublicKey;
+  let brokeUserStream: anchor.web3.PublicKey;
+  let storageFloorService: anchor.web3.PublicKey;
+  let storageFloorFeeAuthority: anchor.web3.PublicKey;
+  let storageFloorFeeAccount: anchor.web3.PublicKey;
+  let lockPaymentService: anchor.web3.PublicKey;
+  let lockPaymentFeeAuthority: anchor.web3.PublicKey;
+  let lockPaymentFeeAccount: anchor.web3.PublicKey;
+  let staker: anchor.web3.Keypair;
+  let stakerTokenAccount: anchor.web3.PublicKey;
+  let stakerLock: anchor.web3.PublicKey;
+
+  // Initializes a service configured like `service` apart from `overrides`,
+  // under a fresh authority, with a fee account owned by its fee authority PDA
+  const createService = async (overrides: object) => {
+    const authority = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(authority.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+    );
+    const [newService] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("service"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    const [newFeeAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_authority"), newService.toBuffer()],
+      program.programId
+    );
+    const base = await program.account.serviceState.fetch(service);
+
+    await program.methods
+      .initializeService({ ...base.config, ...overrides })
+      .accounts({
+        service: newService,
+        authority: authority.publicKey,
+        economics: economics,
+        venexusTreasury: base.venexusTreasury,
+        ainexusTreasury: base.ainexusTreasury,
+        protocolTreasury: base.protocolTreasury,
+        economicsProgram: economicsProgramId,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    const newFeeAccount = await createAccount(
+      provider.connection,
+      await provider.wallet.payer,
+      tokenMint,
+      newFeeAuthority,
+      anchor.web3.Keypair.generate()
+    );
+
+    return { service: newService, authority, feeAuthority: newFeeAuthority, feeAccount: newFeeAccount };
+  };
+
+  before(async () => {
+    // A 2 second refund window and a 5% cancellation fee
+    ({ service: refundService, feeAuthority: refundFeeAuthority, feeAccount: refundFeeAccount } =
+      await createService({ refundWindow: new anchor.BN(2), cancellationFeeBps: 500 }));
+    // initialize_service does not validate the per-user limits, so a zero limit
+    // can be set up directly
+    ({ service: corruptedService } = await createService({ maxStreamsPerUser: new anchor.BN(0) }));
+
+    // `expiredStream` is bought by a fresh owner with a 1 second retention
+    // window, so it has lapsed by the time the reap test runs
+    const payer = await provider.wallet.payer;
+    const owner = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(owner.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+    );
+    const ownerTokenAccount = await createAccount(provider.connection, payer, tokenMint, owner.publicKey);
+    await transfer(provider.connection, payer, userTokenAccount, ownerTokenAccount, provider.wallet.publicKey, 1_000);
+    const lapsing = anchor.web3.Keypair.generate();
+    await program.methods
+      .createStream({
+        streamType: { analytics: {} },
+        dataRate: new anchor.BN(1),
+        retentionPeriod: new anchor.BN(1),
+      }, null, { wallet: {} })
+      .accounts({
+        service: service,
+        stream: lapsing.publicKey,
+        user: owner.publicKey,
+        userTokenAccount: ownerTokenAccount,
+        lock: null,
+        feeAuthority: feeAuthority,
+        feeAccount: feeAccount,
+        economics: null,
+        custodyAuthority: null,
+        lockTokenAccount: null,
+        economicsProgram: null,
+        idempotencyRecord: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([lapsing, owner])
+      .rpc();
+    expiredStream = lapsing.publicKey;
+    expiredStreamOwner = owner.publicKey;
+
+    // Bills stores below one byte as one byte, at no less than 5,000 base units
+    ({ service: storageFloorService, feeAuthority: storageFloorFeeAuthority, feeAccount: storageFloorFeeAccount } =
+      await createService({ minBillableSize: new anchor.BN(1), minStorageFee: new anchor.BN(5_000) }));
+
+    // A 20% discount for paying from locked stake, into a fee account that
+    // `economics` (under the wallet's authority) approves for lock payments
+    ({ service: lockPaymentService, feeAuthority: lockPaymentFeeAuthority, feeAccount: lockPaymentFeeAccount } =
+      await createService({ lockPaymentDiscountBps: 2_000 }));
+    await economicsProgram.methods
+      .addPaymentAccount(lockPaymentFeeAccount)
+      .accounts({ economics: economics, authority: provider.wallet.publicKey })
+      .rpc();
+
+    // `staker` holds 500 base units and locks another 300, together below the
+    // first tier, so both payment methods are priced at the same tier
+    staker = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(staker.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+    );
+    stakerTokenAccount = await createAccount(provider.connection, payer, tokenMint, staker.publicKey);
+    await transfer(provider.connection, payer, userTokenAccount, stakerTokenAccount, provider.wallet.publicKey, 800);
+    const lock = anchor.web3.Keypair.generate();
+    await economicsProgram.methods
+      .createLock(new anchor.BN(300), new anchor.BN(30 * 24 * 60 * 60))
+      .accounts({
+        lock: lock.publicKey,
+        economics: economics,
+        owner: staker.publicKey,
+        ownerTokenAccount: stakerTokenAccount,
+        tokenMint: tokenMint,
+        custodyAuthority: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("custody_authority"), economics.toBuffer()],
+          economicsProgramId
+        )[0],
+        lockTokenAccount: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("custody"), lock.publicKey.toBuffer()],
+          economicsProgramId
+        )[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([lock, staker])
+      .rpc();
+    stakerLock = lock.publicKey;
+
+    // Services that differ from `service`, which uses Linear, only in the decay curve
+    ({ service: sqrtDecayService } = await createService({ decayCurve: { sqrt: {} } }));
+    ({ service: flatDecayService } = await createService({ decayCurve: { none: {} } }));
+    // A two year lock is weighed like a four year lock half way through
+    const midpoint = anchor.web3.Keypair.generate();
+    await economicsProgram.methods
+      .createLock(new anchor.BN(1_000_000), new anchor.BN(2 * 365 * 24 * 60 * 60))
+      .accounts({
+        lock: midpoint.publicKey,
+        economics: economics,
+        owner: provider.wallet.publicKey,
+        ownerTokenAccount: userTokenAccount,
+        tokenMint: tokenMint,
+        custodyAuthority: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("custody_authority"), economics.toBuffer()],
+          economicsProgramId
+        )[0],
+        lockTokenAccount: anchor.web3.PublicKey.findProgramAddressSync(
+          [Buffer.from("custody"), midpoint.publicKey.toBuffer()],
+          economicsProgramId
+        )[0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([midpoint])
+      .rpc();
+    midpointLock = midpoint.publicKey;
+
+    // `brokeUser` is funded with exactly the quoted fee for `brokeUserStream`,
+    // so its token account is empty once the stream is bought
+    brokeUser = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(brokeUser.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+    );
+    brokeUserTokenAccount = await createAccount(provider.connection, payer, tokenMint, brokeUser.publicKey);
+    const retentionPeriod = new anchor.BN(24 * 60 * 60);
+    const streamFee = await program.methods
+      .quoteFee({ stream: { retentionPeriod: retentionPeriod, payment: { wallet: {} } } })
+      .accounts({ service: service, user: brokeUser.publicKey, userTokenAccount: brokeUserTokenAccount, lock: null })
+      .view();
+    await transfer(provider.connection, payer, userTokenAccount, brokeUserTokenAccount, provider.wallet.publicKey, streamFee.toNumber());
+    const paidStream = anchor.web3.Keypair.generate();
+    await program.methods
+      .createStream({ streamType: { analytics: {} }, dataRate: new anchor.BN(1), retentionPeriod: retentionPeriod }, null, { wallet: {} })
+      .accounts({
+        service: service,
+        stream: paidStream.publicKey,
+        user: brokeUser.publicKey,
+        userTokenAccount: brokeUserTokenAccount,
+        lock: null,
+        feeAuthority: feeAuthority,
+        feeAccount: feeAccount,
+        economics: null,
+        custodyAuthority: null,
+        lockTokenAccount: null,
+        economicsProgram: null,
+        idempotencyRecord: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([paidStream, brokeUser])
+      .rpc();
+    brokeUserStream = paidStream.publicKey;
+  });
+
+  it("Reports a healthy service state", async () => {
+    const status = await program.methods
+      .healthCheck()
+      .accounts({ service: service })
+      .view();
+
+    expect(status).to.deep.equal({ healthy: {} });
+  });
+
+  it("Flags a crafted service with zero limits", async () => {
+    // `corruptedService` was initialized with max_streams_per_user = 0
+    const status = await program.methods
+      .healthCheck()
+      .accounts({ service: corruptedService })
+      .view();
+
+    expect(status).to.deep.equal({ invalidConfig: {} });
+  });
+
+  it("Derives the service address from its authority", async () => {
+    // `service` is loaded into the test validator at the wallet's service PDA
+    const [derived, bump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("service"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    expect(derived.toString()).to.equal(service.toString());
+
+    const serviceAccount = await program.account.serviceState.fetch(service);
+    expect(serviceAccount.authority.toString()).to.equal(provider.wallet.publicKey.toString());
+    expect(serviceAccount.bump).to.equal(bump);
+  });
+
+  it("Refuses to initialize an existing service", async () => {
+    const existing = await program.account.serviceState.fetch(service);
+
+    try {
+      await program.methods
+        .initializeService(existing.config)
+        .accounts({
+          service: service,
+          authority: provider.wallet.publicKey,
+          economics: economics,
+          venexusTreasury: venexusTreasury,
+          ainexusTreasury: ainexusTreasury,
+          protocolTreasury: rogueTreasury,
+          economicsProgram: economicsProgramId,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("existing service should not be re-initialized");
+    } catch (err) {
+      expect(err.logs.join("\n")).to.include("already in use");
+    }
+
+    const after = await program.account.serviceState.fetch(service);
+    expect(after.protocolTreasury.toString()).to.equal(existing.protocolTreasury.toString());
+  });
+
+  it("Renews an active stream", async () => {
+    const extension = new anchor.BN(30 * 24 * 60 * 60); // 30 days
+    const before = await program.account.streamAccount.fetch(stream);
+    const feesBefore = await provider.connection.getTokenAccountBalance(feeAccount);
+
+    await program.methods
+      .renewStream(extension)
+      .accounts({
+        service: service,
+        stream: stream,
+        user: provider.wallet.publicKey,
+        userTokenAccount: userTokenAccount,
+        lock: null,
+        feeAuthority: feeAuthority,
+        feeAccount: feeAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const after = await program.account.streamAccount.fetch(stream);
+    expect(after.expiresAt.toNumber()).to.be.at.least(before.expiresAt.add(extension).toNumber());
+    expect(after.config.retentionPeriod.toNumber()).to.equal(
+      before.config.retentionPeriod.add(extension).toNumber()
+    );
+
+    // The renewal fee lands in the service fee account and counts as paid
+    const feesAfter = await provider.connection.getTokenAccountBalance(feeAccount);
+    const charged = Number(feesAfter.value.amount) - Number(feesBefore.value.amount);
+    expect(charged).to.be.greaterThan(0);
+    expect(after.feePaid.sub(before.feePaid).toNumber()).to.equal(charged);
+  });
+
+  it("Discounts streams committed to longer retention", async () => {
+    // Service config earns 5% per 30 days of retention, capped at 20%
+    const create = async (retentionDays: number) => {
+      const newStream = anchor.web3.Keypair.generate();
+      const before = await provider.connection.getTokenAccountBalance(feeAccount);
+      await program.methods
+        .createStream({
+          streamType: { analytics: {} },
+          dataRate: new anchor.BN(1),
+          retentionPeriod: new anchor.BN(retentionDays * 24 * 60 * 60),
+        }, null, { wallet: {} })
+        .accounts({
+          service: service,
+          stream: newStream.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          economics: null,
+          custodyAuthority: null,
+          lockTokenAccount: null,
+          economicsProgram: null,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([newStream])
+        .rpc();
+      const after = await provider.connection.getTokenAccountBalance(feeAccount);
+      return Number(after.value.amount) - Number(before.value.amount);
+    };
+
+    const shortFee = await create(7);
+    const longFee = await create(90);
+    const cappedFee = await create(365);
+
+    // Same tier throughout, so only the retention discount differs
+    expect(longFee).to.equal(Math.floor((shortFee * 8_500) / 10_000));
+    expect(cappedFee).to.equal(Math.floor((shortFee * 8_000) / 10_000));
+  });
+
+  it("Prices agents by type", async () => {
+    // Service config prices Anomaly agents at 2x and Pattern agents at 1x
+    const deploy = async (agent: anchor.web3.Keypair, agentType: object) => {
+      const before = await provider.connection.getTokenAccountBalance(feeAccount);
+      await program.methods
+        .deployAiAgent({ agentType, model: "nexus-v1", parameters: Buffer.alloc(16) }, null)
+        .accounts({
+          service: service,
+          agent: agent.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([agent])
+        .rpc();
+      const after = await provider.connection.getTokenAccountBalance(feeAccount);
+      return Number(after.value.amount) - Number(before.value.amount);
+    };
+
+    const patternFee = await deploy(anchor.web3.Keypair.generate(), { pattern: {} });
+    const anomalyFee = await deploy(anchor.web3.Keypair.generate(), { anomaly: {} });
+    expect(anomalyFee).to.equal(patternFee * 2);
+  });
+
+  it("Counts locked stake towards the fee tier", async () => {
+    // `lockedUser` holds 500 liquid tokens (no tier) and `lockedUserLock` carries
+    // enough veNEXUS power to reach tier 2
+    const deploy = async (lock: anchor.web3.PublicKey | null) => {
+      const agent = anchor.web3.Keypair.generate();
+      const before = await provider.connection.getTokenAccountBalance(feeAccount);
+      await program.methods
+        .deployAiAgent({ agentType: { pattern: {} }, model: "nexus-v1", parameters: Buffer.alloc(16) }, null)
+        .accounts({
+          service: service,
+          agent: agent.publicKey,
+          user: lockedUser.publicKey,
+          userTokenAccount: lockedUserTokenAccount,
+          lock: lock,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([agent, lockedUser])
+        .rpc();
+      const after = await provider.connection.getTokenAccountBalance(feeAccount);
+      return Number(after.value.amount) - Number(before.value.amount);
+    };
+
+    const liquidOnlyFee = await deploy(null);
+    const boostedFee = await deploy(lockedUserLock);
+    expect(boostedFee).to.equal(Math.floor((liquidOnlyFee * 7) / 10));
+  });
+
+  it("Deploys an agent with max-size fields", async () => {
+    // Custom agents allow the full 1024 byte parameter blob
+    const agent = anchor.web3.Keypair.generate();
+
+    await program.methods
+      .deployAiAgent({ agentType: { custom: {} }, model: "m".repeat(64), parameters: Buffer.alloc(1024) }, null)
+      .accounts({
+        service: service,
+        agent: agent.publicKey,
+        user: provider.wallet.publicKey,
+        userTokenAccount: userTokenAccount,
+        lock: null,
+        feeAuthority: feeAuthority,
+        feeAccount: feeAccount,
+        idempotencyRecord: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([agent])
+      .rpc();
+
+    const agentAccount = await program.account.agentAccount.fetch(agent.publicKey);
+    expect(agentAccount.config.model.length).to.equal(64);
+    expect(agentAccount.config.parameters.length).to.equal(1024);
+  });
+
+  it("Rejects oversized parameters for an agent type", async () => {
+    const agent = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .deployAiAgent({ agentType: { pattern: {} }, model: "nexus-v1", parameters: Buffer.alloc(4096) }, null)
+        .accounts({
+          service: service,
+          agent: agent.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([agent])
+        .rpc();
+      expect.fail("oversized parameters should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("AgentParametersTooLarge");
+    }
+  });
+
+  it("Retires an agent and refunds its rent", async () => {
+    const serviceBefore = await program.account.serviceState.fetch(service);
+    const rent = await provider.connection.getBalance(agent);
+    const ownerBefore = await provider.connection.getBalance(provider.wallet.publicKey);
+
+    // Another service cannot have its agent count decremented by this agent
+    try {
+      await program.methods
+        .retireAgent()
+        .accounts({
+          service: refundService,
+          agent: agent,
+          user: provider.wallet.publicKey,
+        })
+        .rpc();
+      expect.fail("an agent should only retire through its own service");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+    }
+
+    await program.methods
+      .retireAgent()
+      .accounts({
+        service: service,
+        agent: agent,
+        user: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const serviceAfter = await program.account.serviceState.fetch(service);
+    const ownerAfter = await provider.connection.getBalance(provider.wallet.publicKey);
+    expect(serviceAfter.totalAgents.toNumber()).to.equal(serviceBefore.totalAgents.toNumber() - 1);
+    expect(await provider.connection.getAccountInfo(agent)).to.be.null;
+    // Refund less the transaction fee
+    expect(ownerAfter).to.be.above(ownerBefore + rent - 10_000);
+  });
+
+  it("Bills compressed data less than uncompressed data of equal size", async () => {
+    const size = new anchor.BN(10);
+    const store = async (compression: boolean) => {
+      const storage = anchor.web3.Keypair.generate();
+      const before = await provider.connection.getTokenAccountBalance(feeAccount);
+      await program.methods
+        .storeData(size, { dataType: { raw: {} }, encryption: false, compression }, Array.from(crypto.randomBytes(32)), null, null)
+        .accounts({
+          service: service,
+          storage: storage.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([storage])
+        .rpc();
+      const after = await provider.connection.getTokenAccountBalance(feeAccount);
+      return Number(after.value.amount) - Number(before.value.amount);
+    };
+
+    const uncompressedFee = await store(false);
+    const compressedFee = await store(true);
+    expect(compressedFee).to.be.below(uncompressedFee);
+  });
+
+  it("Rejects empty stores and bills tiny ones at the fee floor", async () => {
+    // `storageFloorService` has min_billable_size = 1 and min_storage_fee = 5,000,
+    // well above the tiered fee for one byte; its fees are held in `storageFloorFeeAccount`
+    const store = (size: number) => {
+      const storage = anchor.web3.Keypair.generate();
+      return program.methods
+        .storeData(new anchor.BN(size), { dataType: { raw: {} }, encryption: false, compression: false }, Array.from(crypto.randomBytes(32)), null, null)
+        .accounts({
+          service: storageFloorService,
+          storage: storage.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: storageFloorFeeAuthority,
+          feeAccount: storageFloorFeeAccount,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([storage])
+        .rpc();
+    };
+
+    try {
+      await store(0);
+      expect.fail("a zero-size store should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidStorageSize");
+    }
+
+    const before = await provider.connection.getTokenAccountBalance(storageFloorFeeAccount);
+    await store(1);
+    const after = await provider.connection.getTokenAccountBalance(storageFloorFeeAccount);
+    expect(Number(after.value.amount) - Number(before.value.amount)).to.equal(5_000);
+  });
+
+  it("Records a content commitment for stored data", async () => {
+    const content = Buffer.from("analytics batch 42");
+    const contentHash = Array.from(crypto.createHash("sha256").update(content).digest());
+    const irysTxId = "A".repeat(21) + "-" + "b".repeat(20) + "_";
+    const store = (storage: anchor.web3.Keypair, hash: number[], txId: string | null) =>
+      program.methods
+        .storeData(new anchor.BN(content.length), { dataType: { raw: {} }, encryption: false, compression: false }, hash, txId, null)
+        .accounts({
+          service: service,
+          storage: storage.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([storage]);
+
+    const storage = anchor.web3.Keypair.generate();
+    let event;
+    const listener = program.addEventListener("DataStored", (e) => { event = e; });
+    await store(storage, contentHash, irysTxId).rpc();
+    await program.removeEventListener(listener);
+
+    const account = await program.account.storageAccount.fetch(storage.publicKey);
+    expect(Array.from(account.contentHash)).to.deep.equal(contentHash);
+    expect(account.irysTxId).to.equal(irysTxId);
+    expect(Array.from(event.contentHash)).to.deep.equal(contentHash);
+    expect(event.irysTxId).to.equal(irysTxId);
+
+    try {
+      await store(anchor.web3.Keypair.generate(), contentHash, "not-a-tx-id").rpc();
+      expect.fail("a malformed Irys transaction id should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidIrysTxId");
+    }
+
+    try {
+      await store(anchor.web3.Keypair.generate(), new Array(32).fill(0), null).rpc();
+      expect.fail("an empty content hash should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidContentHash");
+    }
+  });
+
+  it("Rejects routing fees to an unregistered treasury", async () => {
+    try {
+      await program.methods
+        .routeFees(new anchor.BN(1_000), { stream: {} })
+        .accounts({
+          service: service,
+          authority: provider.wallet.publicKey,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          economics: economics,
+          venexusTreasury: venexusTreasury,
+          ainexusTreasury: ainexusTreasury,
+          protocolTreasury: rogueTreasury,
+          tokenMint: tokenMint,
+          economicsProgram: economicsProgramId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("mismatched treasury should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("EconomicsMismatch");
+    }
+  });
+
+  it("Bills usage only beyond the contracted data rate", async () => {
+    // `meteredStream` contracts 1 byte/s and its owner approved `feeAuthority`
+    // as a delegate on `userTokenAccount`
+    const serviceAccount = await program.account.serviceState.fetch(service);
+    const report = (consumed: number) => program.methods
+      .reportUsage(new anchor.BN(consumed))
+      .accounts({
+        service: service,
+        authority: provider.wallet.publicKey,
+        stream: meteredStream,
+        ownerTokenAccount: userTokenAccount,
+        feeAuthority: feeAuthority,
+        feeAccount: feeAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const feeBalance = async () =>
+      Number((await provider.connection.getTokenAccountBalance(feeAccount)).value.amount);
+
+    try {
+      await report(0);
+      expect.fail("an empty usage report should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidUsage");
+    }
+
+    let before = await feeBalance();
+    await report(1);
+    expect(await feeBalance()).to.equal(before);
+
+    // 5 MB on top of a few seconds' allowance bills exactly 5 MB of overage
+    const mb = 1024 * 1024;
+    before = await feeBalance();
+    await report(5 * mb + 60);
+    expect((await feeBalance()) - before).to.equal(5 * serviceAccount.config.overageFeePerMb.toNumber());
+
+    const streamAccount = await program.account.streamAccount.fetch(meteredStream);
+    expect(streamAccount.usage.toNumber()).to.equal(5 * mb + 61);
+    expect(streamAccount.overageBilled.toNumber()).to.equal(5 * mb);
+  });
+
+  it("Records but does not charge fee-exempt accounts", async () => {
+    const createStream = async () => {
+      const newStream = anchor.web3.Keypair.generate();
+      const before = await provider.connection.getTokenAccountBalance(feeAccount);
+      await program.methods
+        .createStream({
+          streamType: { analytics: {} },
+          dataRate: new anchor.BN(1),
+          retentionPeriod: new anchor.BN(7 * 24 * 60 * 60),
+        }, null, { wallet: {} })
+        .accounts({
+          service: service,
+          stream: newStream.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          economics: null,
+          custodyAuthority: null,
+          lockTokenAccount: null,
+          economicsProgram: null,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([newStream])
+        .rpc();
+      const after = await provider.connection.getTokenAccountBalance(feeAccount);
+      return Number(after.value.amount) - Number(before.value.amount);
+    };
+    const setExempt = (exempt: boolean) =>
+      (exempt ? program.methods.addFeeExemption(provider.wallet.publicKey) : program.methods.removeFeeExemption(provider.wallet.publicKey))
+        .accounts({ service: service, authority: provider.wallet.publicKey })
+        .rpc();
+
+    await setExempt(true);
+    const streamsBefore = (await program.account.serviceState.fetch(service)).totalStreams.toNumber();
+    expect(await createStream()).to.equal(0);
+    expect((await program.account.serviceState.fetch(service)).totalStreams.toNumber()).to.equal(streamsBefore + 1);
+
+    try {
+      await setExempt(true);
+      expect.fail("an account should not be exempted twice");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("AlreadyFeeExempt");
+    }
+
+    await setExempt(false);
+    expect(await createStream()).to.be.above(0);
+  });
+
+  it("Charges a retried stream creation only once per idempotency key", async () => {
+    const key = Array.from(crypto.randomBytes(32));
+    const [idempotencyRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("idempotency"), provider.wallet.publicKey.toBuffer(), Buffer.from(key)],
+      program.programId
+    );
+    // Each retry signs with a fresh stream keypair, as a client rebuilding the transaction would
+    const createStream = async (newStream: anchor.web3.Keypair) => {
+      await program.methods
+        .createStream({
+          streamType: { analytics: {} },
+          dataRate: new anchor.BN(1),
+          retentionPeriod: new anchor.BN(7 * 24 * 60 * 60),
+        }, key, { wallet: {} })
+        .accounts({
+          service: service,
+          stream: newStream.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          economics: null,
+          custodyAuthority: null,
+          lockTokenAccount: null,
+          economicsProgram: null,
+          idempotencyRecord: idempotencyRecord,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([newStream])
+        .rpc();
+    };
+
+    const first = anchor.web3.Keypair.generate();
+    const retry = anchor.web3.Keypair.generate();
+    const feesBefore = await provider.connection.getTokenAccountBalance(feeAccount);
+    const streamsBefore = (await program.account.serviceState.fetch(service)).totalStreams.toNumber();
+
+    await createStream(first);
+    const feesAfterFirst = await provider.connection.getTokenAccountBalance(feeAccount);
+    await createStream(retry);
+    const feesAfterRetry = await provider.connection.getTokenAccountBalance(feeAccount);
+
+    expect(Number(feesAfterFirst.value.amount)).to.be.above(Number(feesBefore.value.amount));
+    expect(feesAfterRetry.value.amount).to.equal(feesAfterFirst.value.amount);
+    expect((await program.account.serviceState.fetch(service)).totalStreams.toNumber()).to.equal(streamsBefore + 1);
+
+    // The retry's account is closed again and the record points at the original stream
+    expect(await provider.connection.getAccountInfo(retry.publicKey)).to.be.null;
+    const record = await program.account.idempotencyRecord.fetch(idempotencyRecord);
+    expect(record.resource.toString()).to.equal(first.publicKey.toString());
+  });
+
+  it("Refunds an unused stream only inside the refund window", async () => {
+    // `refundService` has a 2 second refund window and a 5% cancellation fee
+    const createStream = async (fees = refundFeeAccount) => {
+      const newStream = anchor.web3.Keypair.generate();
+      await program.methods
+        .createStream({
+          streamType: { analytics: {} },
+          dataRate: new anchor.BN(1),
+          retentionPeriod: new anchor.BN(7 * 24 * 60 * 60),
+        }, null, { wallet: {} })
+        .accounts({
+          service: refundService,
+          stream: newStream.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: refundFeeAuthority,
+          feeAccount: fees,
+          economics: null,
+          custodyAuthority: null,
+          lockTokenAccount: null,
+          economicsProgram: null,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([newStream])
+        .rpc();
+      return newStream.publicKey;
+    };
+    const refund = (target: anchor.web3.PublicKey) => program.methods
+      .refundOperation()
+      .accounts({
+        service: refundService,
+        stream: target,
+        agent: null,
+        storage: null,
+        user: provider.wallet.publicKey,
+        userTokenAccount: userTokenAccount,
+        feeAuthority: refundFeeAuthority,
+        feeAccount: refundFeeAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const balance = async () =>
+      Number((await provider.connection.getTokenAccountBalance(userTokenAccount)).value.amount);
+
+    try {
+      await createStream(feeAccount);
+      expect.fail("fees should only be paid into the service's own fee account");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintTokenOwner");
+    }
+
+    const promptStream = await createStream();
+    const feePaid = (await program.account.streamAccount.fetch(promptStream)).feePaid.toNumber();
+    expect(feePaid).to.be.above(0);
+
+    const before = await balance();
+    await refund(promptStream);
+    const cancellationFee = Math.ceil((feePaid * 500) / 10_000);
+    expect((await balance()) - before).to.equal(feePaid - cancellationFee);
+    expect(await provider.connection.getAccountInfo(promptStream)).to.be.null;
+
+    const lateStream = await createStream();
+    await new Promise((resolve) => setTimeout(resolve, 3_000));
+    try {
+      await refund(lateStream);
+      expect.fail("refund after the window should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("RefundWindowClosed");
+    }
+
+    // Only the service that charged the fee can refund it
+    try {
+      await program.methods
+        .refundOperation()
+        .accounts({
+          service: service,
+          stream: lateStream,
+          agent: null,
+          storage: null,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("a stream should only be refunded by the service that charged it");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+    }
+    expect((await program.account.streamAccount.fetch(lateStream)).active).to.be.true;
+  });
+
+  it("Reprices a stream when its data rate changes", async () => {
+    // `service` allows data rates up to 1,000 and its fees are held by the fee authority PDA
+    const [feeAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_authority"), service.toBuffer()],
+      program.programId
+    );
+    const newStream = anchor.web3.Keypair.generate();
+    await program.methods
+      .createStream({
+        streamType: { analytics: {} },
+        dataRate: new anchor.BN(1),
+        retentionPeriod: new anchor.BN(30 * 24 * 60 * 60),
+      }, null, { wallet: {} })
+      .accounts({
+        service: service,
+        stream: newStream.publicKey,
+        user: provider.wallet.publicKey,
+        userTokenAccount: userTokenAccount,
+        lock: null,
+        feeAuthority: feeAuthority,
+        feeAccount: feeAccount,
+        economics: null,
+        custodyAuthority: null,
+        lockTokenAccount: null,
+        economicsProgram: null,
+        idempotencyRecord: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([newStream])
+      .rpc();
+    const feePaid = (await program.account.streamAccount.fetch(newStream.publicKey)).feePaid.toNumber();
+
+    const update = (dataRate: number, payer = userTokenAccount) => program.methods
+      .updateStream(new anchor.BN(dataRate), null)
+      .accounts({
+        service: service,
+        stream: newStream.publicKey,
+        user: provider.wallet.publicKey,
+        userTokenAccount: payer,
+        feeAuthority: feeAuthority,
+        feeAccount: feeAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const balance = async () =>
+      Number((await provider.connection.getTokenAccountBalance(userTokenAccount)).value.amount);
+
+    // Doubling the rate on an almost unused stream costs about the original fee again
+    let before = await balance();
+    await update(2);
+    const charged = before - (await balance());
+    expect(charged).to.be.at.most(feePaid);
+    expect(charged).to.be.closeTo(feePaid, Math.ceil(feePaid / 100));
+
+    const upgraded = await program.account.streamAccount.fetch(newStream.publicKey);
+    expect(upgraded.config.dataRate.toNumber()).to.equal(2);
+    expect(upgraded.feePaid.toNumber()).to.equal(feePaid + charged);
+
+    // Going back down refunds the difference
+    before = await balance();
+    await update(1);
+    expect((await balance()) - before).to.be.closeTo(charged, Math.ceil(feePaid / 100));
+
+    // Price changes settle against the account that bought the stream
+    const otherAccount = await createAccount(
+      provider.connection,
+      await provider.wallet.payer,
+      tokenMint,
+      provider.wallet.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    try {
+      await update(2, otherAccount);
+      expect.fail("another token account should not settle the stream");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("PaymentAccountMismatch");
+    }
+
+    try {
+      await update(1_001);
+      expect.fail("a data rate above the service limit should be rejected");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidStreamConfig");
+    }
+  });
+
+  it("Lets a keeper reap an expired stream but not a live one", async () => {
+    // `expiredStream` is past its 1 second retention window, owned by `expiredStreamOwner`
+    const reap = (target: anchor.web3.PublicKey, owner: anchor.web3.PublicKey, from = service) => program.methods
+      .reapExpiredStream()
+      .accounts({
+        service: from,
+        stream: target,
+        owner: owner,
+        keeper: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await reap(stream, provider.wallet.publicKey);
+      expect.fail("a stream inside its retention window should not be reaped");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("StreamNotExpired");
+    }
+
+    const rent = (await provider.connection.getAccountInfo(expiredStream)).lamports;
+    const ownerBefore = await provider.connection.getBalance(expiredStreamOwner);
+    const streamsBefore = (await program.account.serviceState.fetch(service)).totalStreams.toNumber();
+
+    // Reaping through another service would decrement the wrong stream count
+    try {
+      await reap(expiredStream, expiredStreamOwner, refundService);
+      expect.fail("a stream should only be reaped through its own service");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintHasOne");
+    }
+
+    await reap(expiredStream, expiredStreamOwner);
+
+    // The owner gets the rent back less the keeper's 100,000 lamport bounty
+    expect(await provider.connection.getAccountInfo(expiredStream)).to.be.null;
+    expect((await provider.connection.getBalance(expiredStreamOwner)) - ownerBefore).to.equal(rent - 100_000);
+    expect((await program.account.serviceState.fetch(service)).totalStreams.toNumber()).to.equal(streamsBefore - 1);
+  });
+
+  it("Tears down a service: deactivate, sweep fees, close", async () => {
+    // `retiringService` has collected fees in `retiringFeeAccount` and has open streams
+    const [retiringFeeAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_authority"), retiringService.toBuffer()],
+      program.programId
+    );
+    const close = (force: boolean) => program.methods
+      .closeService(force)
+      .accounts({
+        service: retiringService,
+        authority: provider.wallet.publicKey,
+        feeAuthority: retiringFeeAuthority,
+        feeAccount: retiringFeeAccount,
+      })
+      .rpc();
+
+    await program.methods
+      .deactivateService()
+      .accounts({ service: retiringService, authority: provider.wallet.publicKey })
+      .rpc();
+
+    // New streams are refused once the service is deactivated
+    const newStream = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .createStream({
+          streamType: { analytics: {} },
+          dataRate: new anchor.BN(1),
+          retentionPeriod: new anchor.BN(7 * 24 * 60 * 60),
+        }, null, { wallet: {} })
+        .accounts({
+          service: retiringService,
+          stream: newStream.publicKey,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: retiringFeeAuthority,
+          feeAccount: retiringFeeAccount,
+          economics: null,
+          custodyAuthority: null,
+          lockTokenAccount: null,
+          economicsProgram: null,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([newStream])
+        .rpc();
+      expect.fail("deactivated service should refuse new streams");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ServiceInactive");
+    }
+
+    try {
+      await close(false);
+      expect.fail("unswept fees should block closing");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("FeesNotSwept");
+    }
+
+    const collected = await provider.connection.getTokenAccountBalance(retiringFeeAccount);
+    const before = await provider.connection.getTokenAccountBalance(sweepDestination);
+    await program.methods
+      .sweepServiceFees()
+      .accounts({
+        service: retiringService,
+        authority: provider.wallet.publicKey,
+        feeAuthority: retiringFeeAuthority,
+        feeAccount: retiringFeeAccount,
+        destination: sweepDestination,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const after = await provider.connection.getTokenAccountBalance(sweepDestination);
+    expect(Number(after.value.amount) - Number(before.value.amount)).to.equal(Number(collected.value.amount));
+
+    try {
+      await close(false);
+      expect.fail("open streams should block closing without force");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ServiceInUse");
+    }
+
+    await close(true);
+    expect(await provider.connection.getAccountInfo(retiringService)).to.be.null;
+  });
+
+  it("Weighs a half-elapsed lock according to the decay curve", async () => {
+    // `sqrtDecayService` and `flatDecayService` use the Sqrt and None curves;
+    // `service` uses Linear. `midpointLock` locks 1,000,000 tokens for two years,
+    // half the four year maximum.
+    const amount = 1_000_000;
+    const powerUnder = async (curveService: anchor.web3.PublicKey) =>
+      (await program.methods
+        .getVotingPower()
+        .accounts({ service: curveService, lock: midpointLock })
+        .view()).toNumber();
+
+    const linear = await powerUnder(service);
+    const sqrt = await powerUnder(sqrtDecayService);
+    const flat = await powerUnder(flatDecayService);
+
+    expect(linear).to.be.closeTo(amount / 2, amount / 100);
+    expect(sqrt).to.be.closeTo(amount * Math.SQRT1_2, amount / 100);
+    expect(flat).to.equal(amount);
+    expect(linear).to.be.lessThan(sqrt);
+    expect(sqrt).to.be.lessThan(flat);
+  });
+
+  it("Reports InsufficientFunds before any fee transfer from an empty account", async () => {
+    // `brokeUser` has an empty token account and `brokeUserStream`, an active
+    // stream it already paid for
+    const common = {
+      service: service,
+      user: brokeUser.publicKey,
+      userTokenAccount: brokeUserTokenAccount,
+      lock: null,
+      feeAuthority: feeAuthority,
+      feeAccount: feeAccount,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const created = { ...common, idempotencyRecord: null, systemProgram: anchor.web3.SystemProgram.programId };
+    const stream = anchor.web3.Keypair.generate();
+    const agent = anchor.web3.Keypair.generate();
+    const storage = anchor.web3.Keypair.generate();
+
+    const attempts = [
+      program.methods
+        .createStream({ streamType: { analytics: {} }, dataRate: new anchor.BN(1), retentionPeriod: new anchor.BN(24 * 60 * 60) }, null, { wallet: {} })
+        .accounts({ ...created, stream: stream.publicKey, economics: null, custodyAuthority: null, lockTokenAccount: null, economicsProgram: null })
+        .signers([stream, brokeUser]),
+      program.methods
+        .deployAiAgent({ agentType: { pattern: {} }, model: "nexus-v1", parameters: Buffer.alloc(16) }, null)
+        .accounts({ ...created, agent: agent.publicKey })
+        .signers([agent, brokeUser]),
+      program.methods
+        .storeData(new anchor.BN(10), { dataType: { raw: {} }, encryption: false, compression: false }, Array.from(crypto.randomBytes(32)), null, null)
+        .accounts({ ...created, storage: storage.publicKey })
+        .signers([storage, brokeUser]),
+      program.methods
+        .renewStream(new anchor.BN(24 * 60 * 60))
+        .accounts({ ...common, stream: brokeUserStream })
+        .signers([brokeUser]),
+      program.methods
+        .updateStream(new anchor.BN(1_000), null)
+        .accounts({ ...common, stream: brokeUserStream })
+        .signers([brokeUser]),
+    ];
+
+    for (const attempt of attempts) {
+      try {
+        await attempt.rpc();
+        expect.fail("an unfunded payment should be rejected");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("InsufficientFunds");
+      }
+    }
+  });
+
+  it("Rejects creating a stream against another user's token account", async () => {
+    // `userTokenAccount` is funded but belongs to the wallet, not `brokeUser`
+    const stream = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .createStream({ streamType: { analytics: {} }, dataRate: new anchor.BN(1), retentionPeriod: new anchor.BN(24 * 60 * 60) }, null, { wallet: {} })
+        .accounts({
+          service: service,
+          stream: stream.publicKey,
+          user: brokeUser.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          economics: null,
+          custodyAuthority: null,
+          lockTokenAccount: null,
+          economicsProgram: null,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([stream, brokeUser])
+        .rpc();
+      expect.fail("a stream should only be paid from the user's own account");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("ConstraintTokenOwner");
+    }
+  });
+
+  it("Pays a stream fee from locked stake at a discount", async () => {
+    // `lockPaymentService` has lock_payment_discount_bps = 2,000 and `economics`
+    // approves `lockPaymentFeeAccount` for lock payments
+    const [custodyAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("custody_authority"), economics.toBuffer()],
+      economicsProgramId
+    );
+    const [stakerLockCustody] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("custody"), stakerLock.toBuffer()],
+      economicsProgramId
+    );
+    const balance = async (account: anchor.web3.PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+    const create = async (payment: object, lockAccounts: object) => {
+      const newStream = anchor.web3.Keypair.generate();
+      await program.methods
+        .createStream({
+          streamType: { analytics: {} },
+          dataRate: new anchor.BN(1),
+          retentionPeriod: new anchor.BN(24 * 60 * 60),
+        }, null, payment)
+        .accounts({
+          service: lockPaymentService,
+          stream: newStream.publicKey,
+          user: staker.publicKey,
+          userTokenAccount: stakerTokenAccount,
+          lock: stakerLock,
+          feeAuthority: lockPaymentFeeAuthority,
+          feeAccount: lockPaymentFeeAccount,
+          economics: null,
+          custodyAuthority: null,
+          lockTokenAccount: null,
+          economicsProgram: null,
+          ...lockAccounts,
+          idempotencyRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([newStream, staker])
+        .rpc();
+      return (await program.account.streamAccount.fetch(newStream.publicKey)).feePaid.toNumber();
+    };
+    const lockAccounts = {
+      economics: economics,
+      custodyAuthority: custodyAuthority,
+      lockTokenAccount: stakerLockCustody,
+      economicsProgram: economicsProgramId,
+    };
+
+    const walletFee = await create({ wallet: {} }, {});
+
+    const custodyBefore = await balance(stakerLockCustody);
+    const walletBefore = await balance(stakerTokenAccount);
+    const feesBefore = await balance(lockPaymentFeeAccount);
+    const refundable = await create({ lock: {} }, lockAccounts);
+    const lockFee = (await balance(lockPaymentFeeAccount)) - feesBefore;
+
+    expect(lockFee).to.equal(Math.floor(walletFee * 0.8));
+    expect(custodyBefore - (await balance(stakerLockCustody))).to.equal(lockFee);
+    expect(await balance(stakerTokenAccount)).to.equal(walletBefore);
+    // Principal spent from a lock is not refundable
+    expect(refundable).to.equal(0);
+
+    try {
+      await create({ lock: {} }, {});
+      expect.fail("paying from a lock needs the economics accounts");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("LockPaymentAccountsMissing");
+    }
+  });
+
+  it("Quotes exactly the fee each operation then charges", async () => {
+    const quote = (query: object) => program.methods
+      .quoteFee(query)
+      .accounts({
+        service: service,
+        user: provider.wallet.publicKey,
+        userTokenAccount: userTokenAccount,
+        lock: null,
+      })
+      .view();
+    const accounts = {
+      service: service,
+      user: provider.wallet.publicKey,
+      userTokenAccount: userTokenAccount,
+      lock: null,
+      feeAuthority: feeAuthority,
+      feeAccount: feeAccount,
+      idempotencyRecord: null,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    const retentionPeriod = new anchor.BN(60 * 24 * 60 * 60);
+    const streamQuote = await quote({ stream: { retentionPeriod, payment: { wallet: {} } } });
+    const newStream = anchor.web3.Keypair.generate();
+    await program.methods
+      .createStream({ streamType: { analytics: {} }, dataRate: new anchor.BN(1), retentionPeriod }, null, { wallet: {} })
+      .accounts({
+        ...accounts,
+        stream: newStream.publicKey,
+        economics: null,
+        custodyAuthority: null,
+        lockTokenAccount: null,
+        economicsProgram: null,
+      })
+      .signers([newStream])
+      .rpc();
+    const stream = await program.account.streamAccount.fetch(newStream.publicKey);
+    expect(stream.feePaid.toString()).to.equal(streamQuote.toString());
+
+    const agentQuote = await quote({ agent: { agentType: { prediction: {} } } });
+    const newAgent = anchor.web3.Keypair.generate();
+    await program.methods
+      .deployAiAgent({ agentType: { prediction: {} }, model: "nexus-v1", parameters: Buffer.alloc(16) }, null)
+      .accounts({ ...accounts, agent: newAgent.publicKey })
+      .signers([newAgent])
+      .rpc();
+    const agent = await program.account.agentAccount.fetch(newAgent.publicKey);
+    expect(agent.feePaid.toString()).to.equal(agentQuote.toString());
+
+    const dataConfig = { dataType: { raw: {} }, encryption: true, compression: true };
+    const storageQuote = await quote({ storage: { size: new anchor.BN(4_096), dataConfig } });
+    const newStorage = anchor.web3.Keypair.generate();
+    await program.methods
+      .storeData(new anchor.BN(4_096), dataConfig, Array.from(crypto.randomBytes(32)), null, null)
+      .accounts({ ...accounts, storage: newStorage.publicKey })
+      .signers([newStorage])
+      .rpc();
+    const storage = await program.account.storageAccount.fetch(newStorage.publicKey);
+    expect(storage.feePaid.toString()).to.equal(storageQuote.toString());
+  });
+
+  it("Rejects renewing a deactivated stream", async () => {
+    try {
+      await program.methods
+        .renewStream(new anchor.BN(24 * 60 * 60))
+        .accounts({
+          service: service,
+          stream: inactiveStream,
+          user: provider.wallet.publicKey,
+          userTokenAccount: userTokenAccount,
+          lock: null,
+          feeAuthority: feeAuthority,
+          feeAccount: feeAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("inactive stream should not renew");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("StreamInactive");
+    }
+  });
+});